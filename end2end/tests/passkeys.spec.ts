@@ -0,0 +1,116 @@
+import { test, expect, type Page } from "@playwright/test";
+
+const BASE_URL = "http://localhost:3000";
+
+/**
+ * Give the browser a software authenticator that verifies the user on every
+ * request. Returns a function listing the passkeys it holds.
+ */
+async function addVirtualAuthenticator(page: Page) {
+  const cdp = await page.context().newCDPSession(page);
+  await cdp.send("WebAuthn.enable");
+  const { authenticatorId } = await cdp.send(
+    "WebAuthn.addVirtualAuthenticator",
+    {
+      options: {
+        protocol: "ctap2",
+        transport: "internal",
+        hasResidentKey: true,
+        hasUserVerification: true,
+        isUserVerified: true,
+        automaticPresenceSimulation: true,
+      },
+    },
+  );
+
+  return async () => {
+    const { credentials } = await cdp.send("WebAuthn.getCredentials", {
+      authenticatorId,
+    });
+    return credentials;
+  };
+}
+
+/** Open a page once it is hydrated, so that its buttons work */
+async function open(page: Page, path: string) {
+  await page.goto(`${BASE_URL}${path}`);
+  await page.waitForLoadState("networkidle");
+}
+
+async function logOut(page: Page, username: string) {
+  await open(page, "/logout");
+  await expect(page).toHaveURL(`${BASE_URL}/`);
+  await expect(page.getByText(`Hi, ${username}`)).toHaveCount(0);
+}
+
+async function logInWithPasskey(page: Page, username: string) {
+  await open(page, "/login");
+  await page.locator("#username").fill(username);
+  await page.getByRole("button", { name: "Login with a passkey" }).click();
+  await expect(page).toHaveURL(`${BASE_URL}/`);
+  await expect(page.getByText(`Hi, ${username}`).first()).toBeVisible();
+}
+
+test.describe("passkeys", () => {
+  test.skip(
+    ({ browserName }) => browserName !== "chromium",
+    "Virtual authenticators need the Chrome DevTools Protocol",
+  );
+
+  test("sign up without a password, then log back in", async ({ page }) => {
+    const passkeys = await addVirtualAuthenticator(page);
+    const username = `passkey-${Date.now()}`;
+
+    await open(page, "/signup");
+    await page.locator("#username").fill(username);
+    await page.getByRole("button", { name: "Sign up with a passkey" }).click();
+    await expect(page).toHaveURL(`${BASE_URL}/`);
+    await expect(page.getByText(`Hi, ${username}`).first()).toBeVisible();
+    expect(await passkeys()).toHaveLength(1);
+
+    await logOut(page, username);
+    await logInWithPasskey(page, username);
+    expect((await passkeys())[0].signCount).toBeGreaterThan(0);
+  });
+
+  test("add a passkey to an account with a password", async ({ page }) => {
+    const passkeys = await addVirtualAuthenticator(page);
+    const username = `password-${Date.now()}`;
+    const password = "correct horse battery";
+
+    await open(page, "/signup");
+    await page.locator("#username").fill(username);
+    await page.locator("#password").fill(password);
+    await page.locator("#confirm-password").fill(password);
+    await page.getByRole("button", { name: "Sign Up" }).click();
+    await expect(page.getByText("Registration successful!")).toBeVisible();
+
+    await open(page, "/login");
+    await page.locator("#username").fill(username);
+    await page.locator("#password").fill(password);
+    await page.getByRole("button", { name: "Login", exact: true }).click();
+    await expect(page.getByText(`Hi, ${username}`).first()).toBeVisible();
+
+    await open(page, "/account");
+    await page.locator("#passkey-name").fill("Virtual authenticator");
+    await page.getByRole("button", { name: "Add a passkey" }).click();
+    await expect(page.getByText("Virtual authenticator")).toBeVisible();
+    expect(await passkeys()).toHaveLength(1);
+
+    await logOut(page, username);
+    await logInWithPasskey(page, username);
+  });
+
+  test("a username without passkeys can't log in with one", async ({
+    page,
+  }) => {
+    await addVirtualAuthenticator(page);
+
+    await open(page, "/login");
+    await page.locator("#username").fill(`nobody-${Date.now()}`);
+    await page.getByRole("button", { name: "Login with a passkey" }).click();
+    await expect(page.getByRole("alert")).toContainText(
+      "Invalid username or password",
+    );
+  });
+});
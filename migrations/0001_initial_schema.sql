@@ -0,0 +1,27 @@
+CREATE TABLE users (
+    id INTEGER PRIMARY KEY,
+    username TEXT NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL,
+    is_admin BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    theme_preference TEXT NOT NULL DEFAULT 'system'
+);
+
+CREATE TABLE posts (
+    id INTEGER PRIMARY KEY,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    published BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE TABLE sessions (
+    id TEXT PRIMARY KEY,
+    user_id INTEGER,
+    username TEXT,
+    is_admin BOOLEAN,
+    expires TIMESTAMP NOT NULL,
+    theme_preference TEXT NOT NULL DEFAULT 'system',
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
@@ -0,0 +1,13 @@
+-- SQLite can't add a UNIQUE column, uniqueness is enforced by the index.
+-- Existing posts get a slug derived from their title after migrating, see `init_db`.
+ALTER TABLE posts ADD COLUMN slug TEXT;
+
+CREATE UNIQUE INDEX posts_slug_idx ON posts (slug);
+
+-- Previous slugs of renamed posts, so that old URLs keep working
+CREATE TABLE post_slugs (
+    slug TEXT PRIMARY KEY,
+    post_id INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE
+);
@@ -0,0 +1,7 @@
+-- Excerpt written by the author, if any
+ALTER TABLE posts ADD COLUMN excerpt TEXT;
+
+-- Derived from the content on save. Existing posts are filled in after migrating, see `init_db`.
+ALTER TABLE posts ADD COLUMN auto_excerpt TEXT;
+ALTER TABLE posts ADD COLUMN word_count INTEGER;
+ALTER TABLE posts ADD COLUMN reading_time INTEGER;
@@ -0,0 +1,7 @@
+-- When a post goes live. NULL for drafts, in the future for scheduled posts.
+-- `published` is set once it is live and its publish hooks have run.
+ALTER TABLE posts ADD COLUMN publish_at TEXT;
+
+UPDATE posts SET publish_at = created_at WHERE published = TRUE;
+
+CREATE INDEX posts_publish_at_idx ON posts (publish_at);
@@ -0,0 +1,18 @@
+-- Every saved version of a post, the last one being its current version
+CREATE TABLE post_revisions (
+    id INTEGER PRIMARY KEY,
+    post_id INTEGER NOT NULL,
+    author_id INTEGER,
+    created_at TEXT NOT NULL,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    published BOOLEAN NOT NULL,
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE,
+    FOREIGN KEY (author_id) REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX post_revisions_post_id_idx ON post_revisions (post_id);
+
+-- The history of existing posts starts at their current version, by an unknown author
+INSERT INTO post_revisions (post_id, created_at, title, content, published)
+SELECT id, updated_at, title, content, published FROM posts ORDER BY id;
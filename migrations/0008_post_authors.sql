@@ -0,0 +1,14 @@
+-- Editors may write posts and edit their own, admins may edit all posts
+ALTER TABLE users ADD COLUMN is_editor BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE sessions ADD COLUMN is_editor BOOLEAN;
+
+-- Public profile of authors
+ALTER TABLE users ADD COLUMN bio TEXT NOT NULL DEFAULT '';
+ALTER TABLE users ADD COLUMN avatar_url TEXT;
+
+ALTER TABLE posts ADD COLUMN author_id INTEGER REFERENCES users(id) ON DELETE SET NULL;
+
+-- Only admins could write posts until now, attribute them to the first one
+UPDATE posts SET author_id = (SELECT id FROM users WHERE is_admin = TRUE ORDER BY id LIMIT 1);
+
+CREATE INDEX posts_author_id_idx ON posts (author_id);
@@ -0,0 +1,58 @@
+-- Users are granted roles, and roles are granted named permissions
+CREATE TABLE roles (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE permissions (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE role_permissions (
+    role_id INTEGER NOT NULL,
+    permission_id INTEGER NOT NULL,
+    PRIMARY KEY (role_id, permission_id),
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE,
+    FOREIGN KEY (permission_id) REFERENCES permissions(id) ON DELETE CASCADE
+);
+
+CREATE TABLE user_roles (
+    user_id INTEGER NOT NULL,
+    role_id INTEGER NOT NULL,
+    PRIMARY KEY (user_id, role_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE
+);
+
+CREATE INDEX user_roles_role_id_idx ON user_roles (role_id);
+
+INSERT INTO roles (name) VALUES ('reader'), ('author'), ('editor'), ('admin');
+
+INSERT INTO permissions (name) VALUES
+    ('posts.create'),
+    ('posts.edit_own'),
+    ('posts.edit_all'),
+    ('posts.view_drafts'),
+    ('users.manage');
+
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT roles.id, permissions.id FROM roles, permissions
+WHERE (roles.name = 'author' AND permissions.name IN ('posts.create', 'posts.edit_own'))
+   OR (roles.name = 'editor' AND permissions.name IN ('posts.create', 'posts.edit_own', 'posts.edit_all', 'posts.view_drafts'))
+   OR roles.name = 'admin';
+
+-- Editors could only edit their own posts, which is what authors do now
+INSERT INTO user_roles (user_id, role_id)
+SELECT users.id, roles.id FROM users, roles
+WHERE roles.name = CASE
+    WHEN users.is_admin THEN 'admin'
+    WHEN users.is_editor THEN 'author'
+    ELSE 'reader'
+END;
+
+-- Permissions are resolved from the roles when loading a session
+ALTER TABLE users DROP COLUMN is_admin;
+ALTER TABLE users DROP COLUMN is_editor;
+ALTER TABLE sessions DROP COLUMN is_admin;
+ALTER TABLE sessions DROP COLUMN is_editor;
@@ -0,0 +1,8 @@
+-- Disabled users may not log in
+ALTER TABLE users ADD COLUMN disabled BOOLEAN NOT NULL DEFAULT FALSE;
+
+INSERT INTO permissions (name) VALUES ('admin.access');
+
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT roles.id, permissions.id FROM roles, permissions
+WHERE roles.name = 'admin' AND permissions.name = 'admin.access';
@@ -0,0 +1,13 @@
+-- Optional, used to send password reset links
+ALTER TABLE users ADD COLUMN email TEXT;
+
+CREATE UNIQUE INDEX users_email_idx ON users (email COLLATE NOCASE);
+
+-- Only a hash of each token is stored, the token itself is only ever in the email
+CREATE TABLE password_reset_tokens (
+    token_hash TEXT PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    expires TEXT NOT NULL
+);
+
+CREATE INDEX password_reset_tokens_user_id_idx ON password_reset_tokens (user_id);
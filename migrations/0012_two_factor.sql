@@ -0,0 +1,23 @@
+-- TOTP secret, encrypted with the server key. Set while enrolling, used once enabled.
+ALTER TABLE users ADD COLUMN totp_secret TEXT;
+ALTER TABLE users ADD COLUMN totp_enabled BOOLEAN NOT NULL DEFAULT FALSE;
+-- Time step of the last accepted code, so that a code can't be used twice
+ALTER TABLE users ADD COLUMN totp_last_step INTEGER;
+
+-- Single-use codes to log in without the authenticator, only their hashes are stored
+CREATE TABLE recovery_codes (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash TEXT NOT NULL,
+    PRIMARY KEY (user_id, code_hash)
+);
+
+-- Logins waiting for their second factor, after the password was verified
+CREATE TABLE login_challenges (
+    token_hash TEXT PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    expires TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0
+);
+
+-- The permissions of these roles are withheld from users without two-factor authentication
+ALTER TABLE roles ADD COLUMN requires_two_factor BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,27 @@
+-- Random user handle given to authenticators, assigned with the first passkey
+ALTER TABLE users ADD COLUMN webauthn_id TEXT;
+
+CREATE UNIQUE INDEX users_webauthn_id_idx ON users (webauthn_id);
+
+-- Passkeys users log in with
+CREATE TABLE credentials (
+    -- Credential id, hex encoded
+    id TEXT PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    -- The passkey as serialized by webauthn-rs: its COSE public key and verification policy
+    public_key TEXT NOT NULL,
+    -- Signature counter of the last login, lower values point to a cloned authenticator
+    sign_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP
+);
+
+CREATE INDEX credentials_user_id_idx ON credentials (user_id);
+
+-- Registrations and logins waiting for the answer of the authenticator
+CREATE TABLE passkey_ceremonies (
+    token_hash TEXT PRIMARY KEY,
+    state TEXT NOT NULL,
+    expires TEXT NOT NULL
+);
@@ -0,0 +1,26 @@
+-- Accounts at OpenID Connect providers users log in with
+CREATE TABLE identities (
+    -- Id of the provider in the configuration
+    provider TEXT NOT NULL,
+    -- Stable id of the account at the provider, the `sub` claim of its ID tokens
+    subject TEXT NOT NULL,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Address the provider gave at the last login, for display only
+    email TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP,
+    PRIMARY KEY (provider, subject)
+);
+
+CREATE INDEX identities_user_id_idx ON identities (user_id);
+
+-- Logins sent to a provider and waiting for the user to come back
+CREATE TABLE oidc_logins (
+    state_hash TEXT PRIMARY KEY,
+    provider TEXT NOT NULL,
+    nonce TEXT NOT NULL,
+    pkce_verifier TEXT NOT NULL,
+    -- Set when a logged in user links an account instead of logging in
+    link_user_id INTEGER,
+    expires TEXT NOT NULL
+);
@@ -0,0 +1,11 @@
+-- Recent failed logins per username and per IP address, to slow down password guessing
+CREATE TABLE login_failures (
+    -- "username" or "ip"
+    scope TEXT NOT NULL,
+    key TEXT NOT NULL,
+    failures INTEGER NOT NULL,
+    last_failure_at TEXT NOT NULL,
+    -- Logins of this username or from this address aren't checked before then
+    blocked_until TEXT,
+    PRIMARY KEY (scope, key)
+);
@@ -0,0 +1,17 @@
+-- Sessions are found by the hash of their cookie, and have an absolute lifetime counted from
+-- their creation on top of their sliding expiry. Existing sessions were stored by the raw
+-- cookie value and can't be converted, so everyone logs in again.
+CREATE TABLE sessions_new (
+    id_hash TEXT PRIMARY KEY,
+    user_id INTEGER,
+    username TEXT,
+    theme_preference TEXT NOT NULL DEFAULT 'system',
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires TIMESTAMP NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+DROP TABLE sessions;
+ALTER TABLE sessions_new RENAME TO sessions;
+
+CREATE INDEX sessions_user_id_idx ON sessions (user_id);
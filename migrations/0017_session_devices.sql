@@ -0,0 +1,12 @@
+-- The device each session was started on, shown to its user. The address and the time are
+-- those of the last request.
+ALTER TABLE sessions ADD COLUMN user_agent TEXT;
+ALTER TABLE sessions ADD COLUMN ip TEXT;
+ALTER TABLE sessions ADD COLUMN last_seen_at TIMESTAMP;
+-- Whether the user asked to stay logged in, which makes the session last longer and outlive
+-- the browser
+ALTER TABLE sessions ADD COLUMN remember BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Carried through the second factor and the provider redirect until the session starts
+ALTER TABLE login_challenges ADD COLUMN remember BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE oidc_logins ADD COLUMN remember BOOLEAN NOT NULL DEFAULT FALSE;
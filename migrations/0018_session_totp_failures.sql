@@ -0,0 +1,3 @@
+-- Wrong codes given in a session to change its user's two-factor authentication, the session
+-- ends once there are too many
+ALTER TABLE sessions ADD COLUMN totp_failures INTEGER NOT NULL DEFAULT 0;
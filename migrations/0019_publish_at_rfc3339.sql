@@ -0,0 +1,9 @@
+-- Publication times are compared with SQLite's date functions, which don't read the format
+-- they were stored in until now ("2024-01-01 12:00:00.123456789 UTC"). Stored as RFC 3339
+-- in UTC from now on.
+UPDATE posts
+SET publish_at = strftime('%Y-%m-%dT%H:%M:%fZ', substr(publish_at, 1, length(publish_at) - 4))
+WHERE publish_at LIKE '% UTC';
+
+DROP INDEX posts_publish_at_idx;
+CREATE INDEX posts_publish_at_idx ON posts (datetime(publish_at));
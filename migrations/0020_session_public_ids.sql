@@ -0,0 +1,5 @@
+-- Sessions were listed and revoked by row id, which SQLite hands out again once the newest
+-- session is deleted: a stale list could revoke a newer, unrelated session. Random ids instead.
+ALTER TABLE sessions ADD COLUMN public_id TEXT;
+UPDATE sessions SET public_id = lower(hex(randomblob(16)));
+CREATE UNIQUE INDEX sessions_public_id_idx ON sessions (public_id);
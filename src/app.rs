@@ -2,20 +2,28 @@ use crate::components::header::Header;
 use crate::models::user::User;
 use crate::pages::*;
 use crate::server::auth::get_current_user;
+use crate::server::csrf::CSRF_META;
 use leptos::prelude::*;
-use leptos_meta::{provide_meta_context, MetaTags, Stylesheet, Title};
+use leptos_meta::{provide_meta_context, Link, MetaTags, Stylesheet, Title};
 use leptos_router::{
-    components::{Route, Router, Routes},
+    components::{ParentRoute, Route, Router, Routes},
     path, SsrMode,
 };
 
 pub fn shell(options: LeptosOptions) -> impl IntoView {
+    // Server functions send it back, see `CsrfClient`
+    #[cfg(feature = "ssr")]
+    let csrf_token = crate::server::utils::csrf::issue_token();
+    #[cfg(not(feature = "ssr"))]
+    let csrf_token = String::new();
+
     view! {
         <!DOCTYPE html>
         <html lang="en">
             <head>
                 <meta charset="utf-8" />
                 <meta name="viewport" content="width=device-width, initial-scale=1" />
+                <meta name=CSRF_META content=csrf_token />
                 <AutoReload options=options.clone() />
                 <HydrationScripts options />
                 <MetaTags />
@@ -51,6 +59,11 @@ pub fn App() -> impl IntoView {
         // sets the document title
         <Title text="Léo Coletta - Software Engineer" />
 
+        // Feed autodiscovery
+        <Link rel="alternate" type_="application/rss+xml" title="RSS" href="/feed.xml" />
+        <Link rel="alternate" type_="application/atom+xml" title="Atom" href="/atom.xml" />
+        <Link rel="alternate" type_="application/feed+json" title="JSON Feed" href="/feed.json" />
+
         // Wrap the entire app with the ThemeProvider
         <Router>
             <Header />
@@ -72,16 +85,52 @@ pub fn App() -> impl IntoView {
                         <Route path=path!("login") view=LoginPage ssr=SsrMode::Async />
                         <Route path=path!("signup") view=SignupPage ssr=SsrMode::Async />
                         <Route path=path!("logout") view=LogoutPage ssr=SsrMode::Async />
+                        <Route path=path!("oidc/callback") view=OidcCallbackPage ssr=SsrMode::Async />
+                        <Route
+                            path=path!("forgot-password")
+                            view=ForgotPasswordPage
+                            ssr=SsrMode::Async
+                        />
+                        <Route
+                            path=path!("reset-password")
+                            view=ResetPasswordPage
+                            ssr=SsrMode::Async
+                        />
+                        <Route path=path!("account") view=AccountPage ssr=SsrMode::Async />
                         <Route path=path!("blog") view=BlogPage ssr=SsrMode::Async />
                         <Route path=path!("blog/new") view=NewPostPage ssr=SsrMode::Async />
-                        <Route path=path!("blog/:id") view=PostPage ssr=SsrMode::Async />
-                        <Route path=path!("blog/:id/edit") view=EditPostPage ssr=SsrMode::Async />
+                        <Route path=path!("blog/:slug") view=PostPage ssr=SsrMode::Async />
+                        <Route path=path!("blog/:slug/edit") view=EditPostPage ssr=SsrMode::Async />
+                        <Route
+                            path=path!("blog/:slug/history")
+                            view=PostHistoryPage
+                            ssr=SsrMode::Async
+                        />
+                        <Route path=path!("authors/:username") view=AuthorPage ssr=SsrMode::Async />
+                        <Route path=path!("search") view=SearchPage ssr=SsrMode::Async />
+                        <ParentRoute path=path!("admin") view=AdminLayout ssr=SsrMode::Async>
+                            <Route path=path!("") view=AdminUsersPage />
+                            <Route path=path!("roles") view=AdminRolesPage />
+                            <Route path=path!("posts") view=AdminPostsPage />
+                            <Route path=path!("sessions") view=AdminSessionsPage />
+                        </ParentRoute>
+                        <Route path=path!("tags") view=TagsPage ssr=SsrMode::Async />
+                        <Route path=path!("tags/:tag") view=TagPage ssr=SsrMode::Async />
                     </Routes>
                 </div>
             </main>
             <footer class="bg-gradient-to-r from-primary-700 to-accent-700 dark:from-primary-800 dark:to-accent-800 text-white p-4 mt-auto shadow-inner">
-                <div class="container mx-auto text-center">
+                <div class="container mx-auto text-center flex flex-col sm:flex-row justify-center items-center gap-2 sm:gap-4">
                     <p>"© 2025 Léo Coletta. All rights reserved."</p>
+                    // Feeds are served outside of the router
+                    <a
+                        href="/feed.xml"
+                        rel="external"
+                        class="inline-flex items-center gap-1 hover:text-white/80 transition-colors"
+                    >
+                        <span class="i-mdi-rss"></span>
+                        "RSS"
+                    </a>
                 </div>
             </footer>
         </Router>
@@ -0,0 +1,347 @@
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand};
+use leptos::prelude::ServerFnError;
+use libsql::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::server::utils::migrations::MigrationError;
+
+/// Version of the export format, bumped when it changes incompatibly
+const EXPORT_VERSION: u32 = 1;
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Léo Coletta's blog")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the web server (the default)
+    Serve,
+    /// Create a user with the admin role, or grant the admin role to an existing user
+    CreateAdmin {
+        username: String,
+        /// Read the password from the first line of stdin instead of prompting for it
+        #[arg(long)]
+        password_stdin: bool,
+    },
+    /// Change the password of a user and log them out everywhere
+    SetPassword {
+        username: String,
+        /// Read the password from the first line of stdin instead of prompting for it
+        #[arg(long)]
+        password_stdin: bool,
+    },
+    /// Turn off two-factor authentication for a user who lost their authenticator and recovery codes
+    ResetTwoFactor { username: String },
+    /// Apply pending database migrations and exit
+    Migrate,
+    /// Delete expired sessions
+    PurgeSessions {
+        /// Delete every session, logging everybody out
+        #[arg(long)]
+        all: bool,
+    },
+    /// Export every post as JSON
+    Export {
+        /// File to write to, stdout when omitted
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Import posts from a JSON export. Posts whose slug is already taken are skipped.
+    Import { file: PathBuf },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CliError {
+    #[error(transparent)]
+    Database(#[from] libsql::Error),
+    #[error(transparent)]
+    Migration(#[from] MigrationError),
+    #[error("{0}")]
+    Server(ServerFnError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("{0}")]
+    Invalid(String),
+}
+
+// `ServerFnError` is not an `std::error::Error`, so it can't be a source
+impl From<ServerFnError> for CliError {
+    fn from(e: ServerFnError) -> Self {
+        CliError::Server(e)
+    }
+}
+
+type Result<T> = std::result::Result<T, CliError>;
+
+/// A post as found in exports, independent of database ids
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedPost {
+    pub slug: String,
+    pub title: String,
+    pub content: String,
+    /// Excerpt written by the author, if any
+    pub excerpt: Option<String>,
+    pub published: bool,
+    pub publish_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Username of the author, posts of unknown authors are imported without one
+    pub author: Option<String>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Export {
+    pub version: u32,
+    pub posts: Vec<ExportedPost>,
+}
+
+/// Run a maintenance command against the initialized database
+pub async fn run(command: Command) -> Result<()> {
+    let conn = crate::server::utils::db::get_db();
+
+    match command {
+        Command::Serve => unreachable!("the server is started by main"),
+        Command::CreateAdmin {
+            username,
+            password_stdin,
+        } => create_admin(conn, username, password_stdin).await,
+        Command::SetPassword {
+            username,
+            password_stdin,
+        } => set_password(conn, username, password_stdin).await,
+        Command::ResetTwoFactor { username } => {
+            let Some(id) = find_user_id(conn, &username).await? else {
+                return Err(CliError::Invalid(format!("No user named {username}")));
+            };
+            crate::server::two_factor::reset_two_factor(conn, id).await?;
+            println!("Turned off two-factor authentication for {username}");
+            Ok(())
+        }
+        // Migrations are applied when the database is initialized
+        Command::Migrate => Ok(()),
+        Command::PurgeSessions { all } => {
+            let deleted = if all {
+                conn.execute("DELETE FROM sessions", ()).await?
+            } else {
+                crate::server::session_gc::purge_sessions_in_batches(conn, 1000)
+                    .await?
+                    .deleted
+            };
+            println!("Deleted {deleted} sessions");
+            Ok(())
+        }
+        Command::Export { output } => export(conn, output).await,
+        Command::Import { file } => import(conn, file).await,
+    }
+}
+
+/// Read a password, either from stdin or by prompting for it twice
+fn read_password(from_stdin: bool) -> Result<String> {
+    let password = if from_stdin {
+        let mut line = String::new();
+        std::io::stdin().lock().read_line(&mut line)?;
+        line.trim_end_matches(['\r', '\n']).to_string()
+    } else {
+        let prompt = |prompt| {
+            rpassword::prompt_password(prompt).map_err(|e| {
+                CliError::Invalid(format!(
+                    "Could not prompt for the password ({e}), use --password-stdin"
+                ))
+            })
+        };
+        let password = prompt("Password: ")?;
+        if prompt("Confirm password: ")? != password {
+            return Err(CliError::Invalid("Passwords do not match".to_string()));
+        }
+        password
+    };
+
+    if password.is_empty() {
+        return Err(CliError::Invalid(
+            "The password may not be empty".to_string(),
+        ));
+    }
+
+    Ok(password)
+}
+
+async fn find_user_id(conn: &Connection, username: &str) -> Result<Option<i64>> {
+    let mut rows = conn
+        .query("SELECT id FROM users WHERE username = ?", params![username])
+        .await?;
+
+    Ok(match rows.next().await? {
+        Some(row) => Some(row.get(0)?),
+        None => None,
+    })
+}
+
+async fn create_admin(conn: &Connection, username: String, password_stdin: bool) -> Result<()> {
+    use crate::server::auth::create_user;
+
+    if let Some(id) = find_user_id(conn, &username).await? {
+        conn.execute(
+            "INSERT OR IGNORE INTO user_roles (user_id, role_id)
+             SELECT ?, id FROM roles WHERE name = 'admin'",
+            params![id],
+        )
+        .await?;
+        println!("Granted the admin role to {username}");
+        return Ok(());
+    }
+
+    let password = read_password(password_stdin)?;
+    create_user(conn, username.clone(), password, None, "admin").await?;
+    println!("Created admin {username}");
+
+    Ok(())
+}
+
+async fn set_password(conn: &Connection, username: String, password_stdin: bool) -> Result<()> {
+    use crate::server::auth::hash_password;
+
+    let Some(id) = find_user_id(conn, &username).await? else {
+        return Err(CliError::Invalid(format!("No user named {username}")));
+    };
+
+    let password_hash = hash_password(&read_password(password_stdin)?)?;
+    conn.execute(
+        "UPDATE users SET password_hash = ? WHERE id = ?",
+        params![password_hash, id],
+    )
+    .await?;
+    let sessions = conn
+        .execute("DELETE FROM sessions WHERE user_id = ?", params![id])
+        .await?;
+    println!("Changed the password of {username} and revoked {sessions} sessions");
+
+    Ok(())
+}
+
+async fn export(conn: &Connection, output: Option<PathBuf>) -> Result<()> {
+    use crate::server::blog::{post_from_row, POST_COLUMNS};
+    use crate::server::tags::load_tags;
+
+    let mut rows = conn
+        .query(
+            &format!("SELECT {POST_COLUMNS} FROM posts ORDER BY posts.created_at"),
+            (),
+        )
+        .await?;
+    let mut posts = Vec::new();
+    while let Some(row) = rows.next().await? {
+        posts.push(post_from_row(&row)?);
+    }
+    load_tags(conn, &mut posts).await?;
+
+    let export = Export {
+        version: EXPORT_VERSION,
+        posts: posts
+            .into_iter()
+            .map(|post| ExportedPost {
+                slug: post.slug,
+                title: post.title,
+                content: post.content,
+                excerpt: post.excerpt,
+                published: post.published,
+                publish_at: post.publish_at,
+                created_at: post.created_at,
+                updated_at: post.updated_at,
+                author: post.author.map(|author| author.username),
+                tags: post.tags.into_iter().map(|tag| tag.name).collect(),
+            })
+            .collect(),
+    };
+
+    let count = export.posts.len();
+    match output {
+        Some(path) => {
+            let file = std::fs::File::create(&path)?;
+            serde_json::to_writer_pretty(std::io::BufWriter::new(file), &export)?;
+            eprintln!("Exported {count} posts to {}", path.display());
+        }
+        None => {
+            let mut stdout = std::io::stdout().lock();
+            serde_json::to_writer_pretty(&mut stdout, &export)?;
+            writeln!(stdout)?;
+            eprintln!("Exported {count} posts");
+        }
+    }
+
+    Ok(())
+}
+
+async fn import(conn: &Connection, file: PathBuf) -> Result<()> {
+    use crate::models::post::reading_time;
+    use crate::server::blog::{find_post_by_slug, format_publish_at};
+    use crate::server::revisions::record_revision;
+    use crate::server::tags::set_post_tags;
+    use crate::server::utils::markdown::summarize_markdown;
+
+    let export: Export =
+        serde_json::from_reader(std::io::BufReader::new(std::fs::File::open(&file)?))?;
+    if export.version != EXPORT_VERSION {
+        return Err(CliError::Invalid(format!(
+            "Unsupported export version {}, expected {EXPORT_VERSION}",
+            export.version
+        )));
+    }
+
+    let (mut imported, mut skipped) = (0, 0);
+    for post in export.posts {
+        if find_post_by_slug(conn, &post.slug).await?.is_some() {
+            eprintln!("Skipping {}, its slug is taken", post.slug);
+            skipped += 1;
+            continue;
+        }
+
+        let author_id = match &post.author {
+            Some(username) => find_user_id(conn, username).await?,
+            None => None,
+        };
+        let summary = summarize_markdown(&post.content);
+
+        let mut rows = conn
+            .query(
+                "INSERT INTO posts (slug, title, content, created_at, updated_at, published, publish_at, excerpt, auto_excerpt, word_count, reading_time, author_id)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) RETURNING id",
+                params![
+                    post.slug,
+                    post.title,
+                    post.content,
+                    post.created_at.to_string(),
+                    post.updated_at.to_string(),
+                    post.published,
+                    post.publish_at.map(format_publish_at),
+                    post.excerpt,
+                    summary.excerpt,
+                    summary.word_count,
+                    reading_time(summary.word_count),
+                    author_id
+                ],
+            )
+            .await?;
+        let Some(row) = rows.next().await? else {
+            return Err(CliError::Invalid("Failed to insert post".to_string()));
+        };
+        let id: i64 = row.get(0)?;
+
+        set_post_tags(conn, id, &post.tags).await?;
+        record_revision(conn, id, author_id).await?;
+        imported += 1;
+    }
+
+    println!("Imported {imported} posts, skipped {skipped}");
+
+    Ok(())
+}
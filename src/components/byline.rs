@@ -0,0 +1,52 @@
+use crate::models::author::Author;
+use leptos::prelude::*;
+use leptos_router::components::A;
+
+/// Picture of an author, or the initial of their username when they have none
+#[component]
+pub fn Avatar(author: Author, #[prop(into)] class: String) -> impl IntoView {
+    match author.avatar_url {
+        Some(url) => view! {
+            <img
+                src=url
+                alt=format!("Avatar of {}", author.username)
+                class=format!("{class} rounded-full object-cover")
+            />
+        }
+        .into_any(),
+        None => {
+            let initial = author
+                .username
+                .chars()
+                .next()
+                .map(|c| c.to_uppercase().to_string())
+                .unwrap_or_default();
+            view! {
+                <span
+                    class=format!(
+                        "{class} rounded-full inline-flex items-center justify-center bg-primary-500 text-white font-bold",
+                    )
+                    aria-hidden="true"
+                >
+                    {initial}
+                </span>
+            }
+            .into_any()
+        }
+    }
+}
+
+/// Author of a post, linking to their profile
+#[component]
+pub fn Byline(author: Option<Author>) -> impl IntoView {
+    author.map(|author| {
+        let href = format!("/authors/{}", author.username);
+        let username = author.username.clone();
+        view! {
+            <A href=href attr:class="inline-flex items-center gap-2 hover:underline">
+                <Avatar author class="w-6 h-6 text-xs" />
+                {username}
+            </A>
+        }
+    })
+}
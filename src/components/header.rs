@@ -1,5 +1,7 @@
 use crate::app::CurrentUser;
+use crate::components::search_box::SearchBox;
 use crate::components::theme_switcher::ThemeSwitcher;
+use crate::models::permission::Permission;
 use leptos::either::EitherOr;
 use leptos::prelude::*;
 use leptos_router::components::A;
@@ -84,6 +86,13 @@ pub fn Header() -> impl IntoView {
                             <span class="i-mdi-post"></span>
                             "Blog"
                         </A>
+                        <A
+                            href="/tags"
+                            attr:class="hover:text-white/80 font-medium transition-all duration-300 hover:-translate-y-1 flex items-center gap-1"
+                        >
+                            <span class="i-mdi-tag-multiple"></span>
+                            "Tags"
+                        </A>
                         <a
                             href="https://github.com/leo91000"
                             target="_blank"
@@ -94,6 +103,8 @@ pub fn Header() -> impl IntoView {
                             "GitHub"
                         </a>
 
+                        <SearchBox input_class="w-40 focus:w-56 bg-white/20 focus:bg-white/30 placeholder-white/70 text-white focus:ring-white/50" />
+
                         <Await future=current_user.into_future() let:server_user>
                             {server_user
                                 .clone()
@@ -101,7 +112,7 @@ pub fn Header() -> impl IntoView {
                                     |user| {
                                         view! {
                                             <div class="flex space-x-4 items-center">
-                                                {if user.is_admin {
+                                                {if user.has_permission(Permission::CreatePosts) {
                                                     Some(
                                                         view! {
                                                             <A
@@ -116,9 +127,27 @@ pub fn Header() -> impl IntoView {
                                                 } else {
                                                     None
                                                 }}
-                                                <span class="font-medium text-white/90 mr-2">
+                                                {if user.has_permission(Permission::AccessAdmin) {
+                                                    Some(
+                                                        view! {
+                                                            <A
+                                                                href="/admin"
+                                                                attr:class="hover:text-white/80 font-medium transition-all duration-300 flex items-center gap-1"
+                                                            >
+                                                                <span class="i-mdi-shield-account"></span>
+                                                                "Admin"
+                                                            </A>
+                                                        },
+                                                    )
+                                                } else {
+                                                    None
+                                                }}
+                                                <A
+                                                    href="/account"
+                                                    attr:class="font-medium text-white/90 hover:text-white mr-2"
+                                                >
                                                     {format!("Hi, {}", user.username)}
-                                                </span>
+                                                </A>
                                                 <A
                                                     href="/logout"
                                                     attr:class="px-3 py-1 rounded-full bg-white/20 hover:bg-white/30 transition-all duration-300 font-medium text-sm flex items-center gap-1"
@@ -153,6 +182,10 @@ pub fn Header() -> impl IntoView {
                 }>
 
                     <nav class="flex flex-col space-y-4 items-center justify-center transition-transform duration-300">
+                        // Close the menu when navigating to the results
+                        <div class="w-full max-w-xs" on:submit=move |_| set_mobile_menu_open.set(false)>
+                            <SearchBox input_class="bg-white/20 focus:bg-white/30 placeholder-white/70 text-white focus:ring-white/50" />
+                        </div>
                         <A
                             href="/"
                             attr:class=move || {
@@ -187,6 +220,23 @@ pub fn Header() -> impl IntoView {
                             <span class="i-mdi-post text-xl"></span>
                             "Blog"
                         </A>
+                        <A
+                            href="/tags"
+                            attr:class=move || {
+                                let mut base_class = "hover:text-white/80 font-medium flex items-center gap-2 p-2 transition-all duration-300"
+                                    .to_string();
+                                if mobile_menu_open.get() {
+                                    base_class += " opacity-100 translate-y-0 delay-100";
+                                } else {
+                                    base_class += " opacity-0 translate-y-2";
+                                }
+                                base_class
+                            }
+                            on:click=move |_| set_mobile_menu_open.set(false)
+                        >
+                            <span class="i-mdi-tag-multiple text-xl"></span>
+                            "Tags"
+                        </A>
                         <a
                             href="https://github.com/leo91000"
                             target="_blank"
@@ -224,7 +274,7 @@ pub fn Header() -> impl IntoView {
                                                 base_class
                                             }>
 
-                                                {if user.is_admin {
+                                                {if user.has_permission(Permission::CreatePosts) {
                                                     Some(
                                                         view! {
                                                             <A
@@ -240,9 +290,29 @@ pub fn Header() -> impl IntoView {
                                                 } else {
                                                     None
                                                 }}
-                                                <span class="font-medium text-white/90 px-2 py-1 text-center">
+                                                {if user.has_permission(Permission::AccessAdmin) {
+                                                    Some(
+                                                        view! {
+                                                            <A
+                                                                href="/admin"
+                                                                attr:class="px-4 py-2 rounded-full bg-white/20 hover:bg-white/30 transition-all duration-300 font-medium flex items-center gap-2 justify-center"
+                                                                on:click=move |_| set_mobile_menu_open.set(false)
+                                                            >
+                                                                <span class="i-mdi-shield-account text-xl"></span>
+                                                                "Admin"
+                                                            </A>
+                                                        },
+                                                    )
+                                                } else {
+                                                    None
+                                                }}
+                                                <A
+                                                    href="/account"
+                                                    attr:class="font-medium text-white/90 hover:text-white px-2 py-1 text-center"
+                                                    on:click=move |_| set_mobile_menu_open.set(false)
+                                                >
                                                     {format!("Hi, {}", user.username)}
-                                                </span>
+                                                </A>
                                                 <A
                                                     href="/logout"
                                                     attr:class="px-4 py-2 rounded-full bg-white/20 hover:bg-white/30 transition-all duration-300 font-medium flex items-center gap-2 justify-center"
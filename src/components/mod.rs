@@ -1,2 +1,9 @@
+pub mod byline;
 pub mod header;
+pub mod post_card;
+pub mod reading_time;
+pub mod redirect;
+pub mod schedule_input;
+pub mod search_box;
+pub mod tag_list;
 pub mod theme_switcher;
@@ -0,0 +1,45 @@
+use crate::components::byline::Byline;
+use crate::components::reading_time::ReadingTime;
+use crate::components::tag_list::TagList;
+use crate::models::post::PostSummary;
+use leptos::prelude::*;
+use leptos_router::components::A;
+
+/// Preview of a post in a listing
+#[component]
+pub fn PostCard(post: PostSummary) -> impl IntoView {
+    // Format date for display
+    let format_date = |date: chrono::DateTime<chrono::Utc>| date.format("%B %d, %Y").to_string();
+
+    view! {
+        <article class="bg-white/80 dark:bg-primary-800/80 backdrop-blur-sm p-6 rounded-xl shadow-lg hover:shadow-xl transition-all duration-300 border-l-4 border-primary-500 dark:border-primary-400 card">
+            <h2 class="text-2xl font-bold mb-2">
+                <A
+                    href=format!("/blog/{}", post.slug)
+                    attr:class="text-primary-700 dark:text-primary-400 hover:text-primary-500 dark:hover:text-primary-300 transition-colors duration-300"
+                >
+                    {post.title.clone()}
+                </A>
+            </h2>
+            <div class="text-gray-500 dark:text-gray-300 mb-4 flex flex-wrap items-center gap-x-4 text-sm">
+                <span class="flex items-center">
+                    <span class="inline-block w-2 h-2 rounded-full bg-accent-500 mr-2"></span>
+                    {format_date(post.created_at)}
+                </span>
+                <Byline author=post.author />
+                <ReadingTime word_count=post.word_count reading_time=post.reading_time />
+            </div>
+            <p class="prose dark:prose-invert text-gray-700 dark:text-gray-200">{post.excerpt}</p>
+            <div class="mt-4 flex flex-wrap items-center justify-between gap-4">
+                <A
+                    href=format!("/blog/{}", post.slug)
+                    attr:class="inline-flex items-center px-4 py-2 bg-gradient-to-r from-primary-500 to-accent-500 text-white rounded-lg hover:from-primary-600 hover:to-accent-600 transition-all duration-300 text-sm font-medium gap-1"
+                >
+                    "Read more"
+                    <span class="i-mdi-arrow-right"></span>
+                </A>
+                <TagList tags=post.tags />
+            </div>
+        </article>
+    }
+}
@@ -0,0 +1,14 @@
+use leptos::prelude::*;
+
+/// Estimated reading time and word count of a post
+#[component]
+pub fn ReadingTime(word_count: i64, reading_time: i64) -> impl IntoView {
+    let words = if word_count == 1 { "word" } else { "words" };
+
+    view! {
+        <span class="inline-flex items-center gap-1" title=format!("{word_count} {words}")>
+            <span class="i-mdi-clock-outline"></span>
+            {format!("{reading_time} min read · {word_count} {words}")}
+        </span>
+    }
+}
@@ -0,0 +1,31 @@
+use leptos::prelude::*;
+use leptos_router::{hooks::use_navigate, NavigateOptions};
+
+/// Permanently redirect to `path`.
+///
+/// During server rendering this answers with `301 Moved Permanently`, so crawlers and
+/// shared links update to the canonical URL. In the browser it replaces the history entry.
+#[component]
+pub fn PermanentRedirect(path: String) -> impl IntoView {
+    #[cfg(feature = "ssr")]
+    if let Some(response) = use_context::<leptos_axum::ResponseOptions>() {
+        use axum::http::{header::LOCATION, HeaderValue, StatusCode};
+
+        if let Ok(location) = HeaderValue::from_str(&path) {
+            response.set_status(StatusCode::MOVED_PERMANENTLY);
+            response.insert_header(LOCATION, location);
+        }
+        return;
+    }
+
+    let navigate = use_navigate();
+    request_animation_frame(move || {
+        navigate(
+            &path,
+            NavigateOptions {
+                replace: true,
+                ..Default::default()
+            },
+        );
+    });
+}
@@ -0,0 +1,55 @@
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use leptos::prelude::*;
+
+/// Format of `datetime-local` input values
+const INPUT_FORMAT: &str = "%Y-%m-%dT%H:%M";
+
+/// Parse a `datetime-local` input value, in the browser's time zone
+pub fn parse_schedule(value: &str) -> Option<DateTime<Utc>> {
+    let naive = NaiveDateTime::parse_from_str(value, INPUT_FORMAT).ok()?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|local| local.to_utc())
+}
+
+/// Format a publication time as a `datetime-local` input value, in the browser's time zone
+pub fn format_schedule(publish_at: DateTime<Utc>) -> String {
+    publish_at
+        .with_timezone(&Local)
+        .format(INPUT_FORMAT)
+        .to_string()
+}
+
+/// Publication time of a post that isn't published right away
+#[component]
+pub fn ScheduleInput(
+    published: ReadSignal<bool>,
+    value: ReadSignal<String>,
+    set_value: WriteSignal<String>,
+) -> impl IntoView {
+    view! {
+        <div class="mb-6">
+            <label
+                for="publish_at"
+                class="block text-gray-700 dark:text-gray-200 font-medium mb-2 flex items-center gap-1"
+            >
+                <span class="i-mdi-calendar-clock"></span>
+                "Schedule"
+            </label>
+            <input
+                type="datetime-local"
+                id="publish_at"
+                class="w-full px-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200 disabled:opacity-50"
+                disabled=published
+                on:input=move |ev| {
+                    set_value.set(event_target_value(&ev));
+                }
+                prop:value=value
+            />
+            <p class="mt-1 text-sm text-gray-500 dark:text-gray-400">
+                "Publish automatically at this time. Leave empty to keep a draft."
+            </p>
+        </div>
+    }
+}
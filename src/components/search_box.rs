@@ -0,0 +1,29 @@
+use leptos::prelude::*;
+use leptos_router::components::Form;
+
+/// Search form submitting to the `/search` page
+#[component]
+pub fn SearchBox(
+    /// Initial query
+    #[prop(optional, into)]
+    value: String,
+    /// Classes of the text input
+    #[prop(optional, into)]
+    input_class: String,
+) -> impl IntoView {
+    view! {
+        <Form method="get" action="/search">
+            <label class="relative flex items-center">
+                <span class="sr-only">"Search posts"</span>
+                <span class="i-mdi-magnify absolute left-3 opacity-70 pointer-events-none"></span>
+                <input
+                    type="search"
+                    name="q"
+                    placeholder="Search..."
+                    value=value
+                    class=format!("w-full pl-9 pr-3 py-1.5 rounded-full focus:outline-none focus:ring-2 transition-all duration-300 {input_class}")
+                />
+            </label>
+        </Form>
+    }
+}
@@ -0,0 +1,28 @@
+use crate::models::tag::Tag;
+use leptos::prelude::*;
+use leptos_router::components::A;
+
+/// Tags of a post, each linking to the posts sharing it
+#[component]
+pub fn TagList(tags: Vec<Tag>) -> impl IntoView {
+    view! {
+        <ul class="flex flex-wrap gap-2">
+            {tags
+                .into_iter()
+                .map(|tag| {
+                    view! {
+                        <li>
+                            <A
+                                href=format!("/tags/{}", tag.slug)
+                                attr:class="inline-flex items-center gap-1 px-3 py-1 rounded-full text-sm bg-primary-100 dark:bg-primary-700 text-primary-700 dark:text-primary-200 hover:bg-primary-200 dark:hover:bg-primary-600 transition-colors"
+                            >
+                                <span class="i-mdi-tag-outline"></span>
+                                {tag.name}
+                            </A>
+                        </li>
+                    }
+                })
+                .collect_view()}
+        </ul>
+    }
+}
@@ -1,18 +1,77 @@
 #[cfg(feature = "ssr")]
 #[tokio::main]
 async fn main() {
+    use blog::{
+        cli::{run, Cli, Command},
+        server::utils::db::init_db,
+    };
+    use clap::Parser;
+    use tracing::{error, info};
+    use tracing_subscriber::{
+        fmt::writer::BoxMakeWriter, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter,
+    };
+
+    let command = Cli::parse().command.unwrap_or(Command::Serve);
+
+    // Commands log to stderr so that their output may be piped
+    let writer = match command {
+        Command::Serve => BoxMakeWriter::new(std::io::stdout),
+        _ => BoxMakeWriter::new(std::io::stderr),
+    };
+
+    // Initialize tracing
+    tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .with(tracing_subscriber::fmt::layer().with_writer(writer))
+        .init();
+
+    info!("Initializing database");
+    // Refuse to start on an unknown, modified or newer schema
+    if let Err(e) = init_db().await {
+        error!("Failed to initialize database: {e}");
+        std::process::exit(1);
+    }
+
+    match command {
+        Command::Serve => serve().await,
+        command => {
+            if let Err(e) = run(command).await {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+async fn serve() {
     use std::time::Duration;
 
     use axum::{
         http::{Request, Response},
-        Router,
+        middleware, Router,
+    };
+    use blog::{
+        app::*,
+        server::{
+            feeds::{feed_routes, site_url},
+            scheduler::spawn_publish_scheduler,
+            session_gc::spawn_session_gc,
+            utils::{
+                csrf::{init_csrf, protect_server_fns},
+                mail::init_mail,
+                oidc::init_oidc,
+                session::init_sessions,
+                throttle::init_login_throttle,
+                totp::init_totp,
+                webauthn::init_webauthn,
+            },
+        },
     };
-    use blog::{app::*, server::utils::db::init_db};
     use leptos::prelude::*;
     use leptos_axum::{generate_route_list, LeptosRoutes};
     use tower_http::trace::{self, MakeSpan, OnRequest, OnResponse, TraceLayer};
-    use tracing::{info, Level, Span};
-    use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+    use tracing::{error, info, Level, Span};
 
     #[derive(Clone)]
     struct FilteredMakeSpan {
@@ -92,17 +151,45 @@ async fn main() {
         }
     }
 
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    spawn_publish_scheduler();
 
-    info!("Initializing database");
-    init_db().await.expect("Failed to initialize database");
     let conf = get_configuration(None).unwrap();
     let addr = conf.leptos_options.site_addr;
     let leptos_options = conf.leptos_options;
+
+    if let Err(e) = init_sessions(&site_url(&leptos_options)) {
+        error!("Failed to set up sessions: {e}");
+        std::process::exit(1);
+    }
+    if let Err(e) = init_csrf(&site_url(&leptos_options)) {
+        error!("Failed to set up CSRF protection: {e}");
+        std::process::exit(1);
+    }
+    if let Err(e) = init_mail(site_url(&leptos_options)) {
+        error!("Failed to set up the mail transport: {e}");
+        std::process::exit(1);
+    }
+    if let Err(e) = init_totp(&site_url(&leptos_options)) {
+        error!("Failed to set up two-factor authentication: {e}");
+        std::process::exit(1);
+    }
+    if let Err(e) = init_webauthn(&site_url(&leptos_options)) {
+        error!("Failed to set up passkeys: {e}");
+        std::process::exit(1);
+    }
+    if let Err(e) = init_oidc(&site_url(&leptos_options)) {
+        error!("Failed to set up OpenID Connect providers: {e}");
+        std::process::exit(1);
+    }
+    if let Err(e) = init_login_throttle() {
+        error!("Failed to set up login throttling: {e}");
+        std::process::exit(1);
+    }
+    if let Err(e) = spawn_session_gc() {
+        error!("Failed to start purging expired sessions: {e}");
+        std::process::exit(1);
+    }
+
     // Generate the list of routes in your Leptos App
     let routes = generate_route_list(App);
 
@@ -113,11 +200,13 @@ async fn main() {
         .on_response(FilteredOnResponse::new());
 
     let app = Router::new()
+        .merge(feed_routes())
         .leptos_routes(&leptos_options, routes, {
             let leptos_options = leptos_options.clone();
             move || shell(leptos_options.clone())
         })
         .fallback(leptos_axum::file_and_error_handler(shell))
+        .layer(middleware::from_fn(protect_server_fns))
         .layer(trace_layer)
         .with_state(leptos_options);
 
@@ -125,9 +214,13 @@ async fn main() {
     // `axum::Server` is a re-export of `hyper::Server`
     info!("listening on http://{}", &addr);
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
-    axum::serve(listener, app.into_make_service())
-        .await
-        .unwrap();
+    // Client addresses are needed to throttle logins
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await
+    .unwrap();
 }
 
 #[cfg(not(feature = "ssr"))]
@@ -0,0 +1,52 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A user as listed in the admin dashboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminUser {
+    pub id: i64,
+    pub username: String,
+    pub roles: Vec<String>,
+    pub disabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub post_count: i64,
+}
+
+/// A post as listed in the admin dashboard, drafts included
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminPost {
+    pub id: i64,
+    pub slug: String,
+    pub title: String,
+    /// Username of the author, `None` when unknown or deleted
+    pub author: Option<String>,
+    pub published: bool,
+    pub publish_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Action applied to every selected post of the admin dashboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BulkPostAction {
+    Publish,
+    Unpublish,
+    Delete,
+}
+
+/// A session of a logged in user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminSession {
+    /// Random public id of the session. The session id itself is a secret and never leaves the
+    /// server, and row ids are reused once sessions are deleted.
+    pub id: String,
+    pub username: Option<String>,
+    pub expires: DateTime<Utc>,
+    /// Whether this is the session of the admin looking at the list
+    pub current: bool,
+}
+
+/// Active sessions of logged in users
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionList {
+    pub sessions: Vec<AdminSession>,
+}
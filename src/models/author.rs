@@ -0,0 +1,28 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::post::PostSummary;
+
+/// Author of a post, as shown in bylines
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Author {
+    pub username: String,
+    pub avatar_url: Option<String>,
+}
+
+/// Public profile of an author, with their published posts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorProfile {
+    pub author: Author,
+    pub bio: String,
+    pub joined_at: DateTime<Utc>,
+    pub posts: Vec<PostSummary>,
+}
+
+/// Changes to the profile of the current user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileUpdate {
+    pub bio: String,
+    /// Removes the avatar when empty
+    pub avatar_url: String,
+}
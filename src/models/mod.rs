@@ -1,3 +1,12 @@
+pub mod admin;
+pub mod author;
+pub mod oidc;
+pub mod passkey;
+pub mod permission;
 pub mod post;
+pub mod revision;
+pub mod search;
 pub mod session;
+pub mod tag;
+pub mod two_factor;
 pub mod user;
@@ -0,0 +1,35 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::user::User;
+
+/// An OpenID Connect provider users can log in with, as shown on the login page
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OidcProviderInfo {
+    pub id: String,
+    pub name: String,
+}
+
+/// An account at a provider linked to the current user, as listed in their account settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IdentityInfo {
+    pub provider: String,
+    /// Display name of the provider, the id when it isn't configured anymore
+    pub provider_name: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// Outcome of coming back from a provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum OidcLoginResponse {
+    LoggedIn(User),
+    /// The user has two-factor authentication, see [`LoginResponse`](super::user::LoginResponse)
+    TwoFactorRequired {
+        challenge: String,
+    },
+    /// The account at the provider was linked to the user who started the login
+    Linked,
+}
@@ -0,0 +1,19 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Options for the browser to create or use a passkey, with the ceremony to send back
+/// along with the answer of the authenticator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasskeyCeremony<T> {
+    pub ceremony: String,
+    pub options: T,
+}
+
+/// A passkey of the current user, as listed in their account settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PasskeyInfo {
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,59 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// What a user is allowed to do. Permissions are granted to roles, and roles to users,
+/// both stored in the database under the names below.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub enum Permission {
+    #[serde(rename = "posts.create")]
+    CreatePosts,
+    #[serde(rename = "posts.edit_own")]
+    EditOwnPosts,
+    #[serde(rename = "posts.edit_all")]
+    EditAllPosts,
+    #[serde(rename = "posts.view_drafts")]
+    ViewDrafts,
+    #[serde(rename = "users.manage")]
+    ManageUsers,
+    #[serde(rename = "admin.access")]
+    AccessAdmin,
+}
+
+impl Permission {
+    pub fn name(self) -> &'static str {
+        match self {
+            Permission::CreatePosts => "posts.create",
+            Permission::EditOwnPosts => "posts.edit_own",
+            Permission::EditAllPosts => "posts.edit_all",
+            Permission::ViewDrafts => "posts.view_drafts",
+            Permission::ManageUsers => "users.manage",
+            Permission::AccessAdmin => "admin.access",
+        }
+    }
+}
+
+impl FromStr for Permission {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "posts.create" => Ok(Permission::CreatePosts),
+            "posts.edit_own" => Ok(Permission::EditOwnPosts),
+            "posts.edit_all" => Ok(Permission::EditAllPosts),
+            "posts.view_drafts" => Ok(Permission::ViewDrafts),
+            "users.manage" => Ok(Permission::ManageUsers),
+            "admin.access" => Ok(Permission::AccessAdmin),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A role and the permissions it grants
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Role {
+    pub name: String,
+    pub permissions: Vec<Permission>,
+    /// Users without two-factor authentication don't get the permissions of this role
+    pub requires_two_factor: bool,
+}
@@ -1,27 +1,284 @@
 use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use super::author::Author;
+use super::tag::Tag;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Post {
     pub id: i64,
+    pub slug: String,
     pub title: String,
     pub content: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Whether the post went live, see [`Post::is_visible`]
     pub published: bool,
+    /// When the post goes or went live, `None` for drafts
+    pub publish_at: Option<DateTime<Utc>>,
+    pub tags: Vec<Tag>,
+    /// Excerpt written by the author
+    pub excerpt: Option<String>,
+    /// First paragraph of the content, used when there is no excerpt
+    pub auto_excerpt: String,
+    pub word_count: i64,
+    /// Estimated reading time in minutes, see [`reading_time`]
+    pub reading_time: i64,
+    pub author_id: Option<i64>,
+    /// `None` when the author's account was deleted
+    pub author: Option<Author>,
+}
+
+impl Post {
+    /// Whether readers can see the post, i.e. its publication time has come.
+    /// This doesn't wait for the scheduler to set `published`.
+    pub fn is_visible(&self) -> bool {
+        self.publish_at
+            .is_some_and(|publish_at| publish_at <= Utc::now())
+    }
+
+    /// Whether the post is set to go live later
+    pub fn is_scheduled(&self) -> bool {
+        self.publish_at
+            .is_some_and(|publish_at| publish_at > Utc::now())
+    }
+
+    /// The author's excerpt, or the one derived from the content
+    pub fn summary(&self) -> &str {
+        self.excerpt.as_deref().unwrap_or(&self.auto_excerpt)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewPost {
     pub title: String,
+    /// Generated from the title when empty
+    pub slug: String,
     pub content: String,
+    /// Publish right away, or keep the post live when it already is
     pub published: bool,
+    /// When to publish the post if `published` is false, `None` for a draft
+    #[serde(default)]
+    pub publish_at: Option<DateTime<Utc>>,
+    /// Tag names, created on the fly when they don't exist yet.
+    /// Empty lists are omitted by the form encoding, hence the default.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Derived from the first paragraph of the content when empty
+    #[serde(default)]
+    pub excerpt: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdatePostData {
     pub id: i64,
     pub title: String,
+    /// Generated from the title when empty
+    pub slug: String,
     pub content: String,
+    /// Publish right away, or keep the post live when it already is
     pub published: bool,
+    /// When to publish the post if `published` is false, `None` for a draft
+    #[serde(default)]
+    pub publish_at: Option<DateTime<Utc>>,
+    /// Tag names, created on the fly when they don't exist yet.
+    /// Empty lists are omitted by the form encoding, hence the default.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Derived from the first paragraph of the content when empty
+    #[serde(default)]
+    pub excerpt: Option<String>,
+}
+
+/// Average reading speed used for reading time estimates
+pub const WORDS_PER_MINUTE: i64 = 200;
+
+/// Estimated reading time in minutes of a text, at least one minute
+pub fn reading_time(word_count: i64) -> i64 {
+    ((word_count + WORDS_PER_MINUTE - 1) / WORDS_PER_MINUTE).max(1)
+}
+
+/// A post without its full content, as shown in listings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostSummary {
+    pub id: i64,
+    pub slug: String,
+    pub title: String,
+    /// See [`Post::summary`]
+    pub excerpt: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub published: bool,
+    pub tags: Vec<Tag>,
+    pub word_count: i64,
+    pub reading_time: i64,
+    pub author: Option<Author>,
+}
+
+impl From<Post> for PostSummary {
+    fn from(post: Post) -> Self {
+        Self {
+            excerpt: post.excerpt.unwrap_or(post.auto_excerpt),
+            id: post.id,
+            slug: post.slug,
+            title: post.title,
+            created_at: post.created_at,
+            updated_at: post.updated_at,
+            published: post.published,
+            tags: post.tags,
+            word_count: post.word_count,
+            reading_time: post.reading_time,
+            author: post.author,
+        }
+    }
+}
+
+/// Position of a post in the listing order, newest first.
+///
+/// In URLs it is written as `<created_at in nanoseconds>-<id>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: i64,
+}
+
+impl fmt::Display for PostCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nanos = self.created_at.timestamp_nanos_opt().unwrap_or_default();
+        write!(f, "{nanos}-{}", self.id)
+    }
+}
+
+impl FromStr for PostCursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid cursor: {s}");
+        let (nanos, id) = s.rsplit_once('-').ok_or_else(invalid)?;
+
+        Ok(Self {
+            created_at: DateTime::from_timestamp_nanos(nanos.parse().map_err(|_| invalid())?),
+            id: id.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// A page of the post listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostList {
+    pub posts: Vec<PostSummary>,
+    /// Start of the next page, with older posts
+    pub next_cursor: Option<PostCursor>,
+    /// Whether there are newer posts than the ones of this page
+    pub has_previous: bool,
+    /// Start of the previous page, with newer posts. `None` when it is the first page.
+    pub previous_cursor: Option<PostCursor>,
+}
+
+/// A post along with its Markdown content rendered to sanitized HTML
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedPost {
+    pub post: Post,
+    pub html: String,
+}
+
+/// Turn a title or name into a URL slug, e.g. `"Héllo, Wörld!"` becomes `"hello-world"`
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::with_capacity(title.len());
+
+    for c in title.chars().flat_map(char::to_lowercase) {
+        let c = match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => 'a',
+            'ç' => 'c',
+            'è' | 'é' | 'ê' | 'ë' => 'e',
+            'ì' | 'í' | 'î' | 'ï' => 'i',
+            'ñ' => 'n',
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => 'o',
+            'ù' | 'ú' | 'û' | 'ü' => 'u',
+            'ý' | 'ÿ' => 'y',
+            c => c,
+        };
+
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Slug of a post URL, never empty nor purely numeric
+pub fn post_slug(title: &str) -> String {
+    let slug = slugify(title);
+    if slug.is_empty() {
+        "post".to_string()
+    } else if slug.chars().all(|c| c.is_ascii_digit()) {
+        // Numeric slugs would be mistaken for legacy `/blog/:id` URLs
+        format!("post-{slug}")
+    } else {
+        slug
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugs_are_lowercase_ascii_words() {
+        assert_eq!(slugify("Héllo, Wörld!"), "hello-world");
+        assert_eq!(
+            slugify("  Rust & WebAssembly -- 2024  "),
+            "rust-webassembly-2024"
+        );
+        assert_eq!(slugify("Ça coûte 10€"), "ca-coute-10");
+        assert_eq!(slugify("日本語"), "");
+    }
+
+    #[test]
+    fn post_slugs_are_never_empty_nor_numeric() {
+        assert_eq!(post_slug("Hello"), "hello");
+        assert_eq!(post_slug("!!!"), "post");
+        assert_eq!(post_slug("2024"), "post-2024");
+        assert_eq!(post_slug("2024 in review"), "2024-in-review");
+    }
+
+    #[test]
+    fn reading_time_rounds_up_to_whole_minutes() {
+        for (word_count, minutes) in [(0, 1), (1, 1), (200, 1), (201, 2), (1000, 5)] {
+            assert_eq!(reading_time(word_count), minutes, "{word_count}");
+        }
+    }
+
+    #[test]
+    fn cursors_round_trip_through_urls() {
+        let cursor = PostCursor {
+            created_at: DateTime::from_timestamp_nanos(1_714_564_800_123_456_789),
+            id: 42,
+        };
+        assert_eq!(cursor.to_string(), "1714564800123456789-42");
+        assert_eq!("1714564800123456789-42".parse(), Ok(cursor));
+
+        // Dates before 1970 are negative
+        let cursor = PostCursor {
+            created_at: DateTime::from_timestamp_nanos(-5),
+            id: 1,
+        };
+        assert_eq!(cursor.to_string().parse(), Ok(cursor));
+    }
+
+    #[test]
+    fn invalid_cursors_are_refused() {
+        for cursor in ["", "42", "abc-1", "1-abc", "1-", "-1"] {
+            assert_eq!(
+                cursor.parse::<PostCursor>(),
+                Err(format!("Invalid cursor: {cursor}")),
+                "{cursor}"
+            );
+        }
+    }
 }
@@ -0,0 +1,48 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::post::Post;
+
+/// A saved version of a post, without its content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Revision {
+    pub id: i64,
+    pub post_id: i64,
+    /// Username of the author of the change, `None` when unknown or deleted
+    pub author: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub title: String,
+    pub published: bool,
+}
+
+/// A post along with its revisions, newest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostHistory {
+    pub post: Post,
+    pub revisions: Vec<Revision>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiffTag {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// A line of a diff, with its 1-based line numbers in the old and new contents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffLine {
+    pub tag: DiffTag,
+    pub old_number: Option<usize>,
+    pub new_number: Option<usize>,
+    pub text: String,
+}
+
+/// Line-level differences between the contents of two revisions of a post
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisionDiff {
+    pub from: Revision,
+    pub to: Revision,
+    /// Groups of changed lines with a few unchanged lines around them
+    pub hunks: Vec<Vec<DiffLine>>,
+}
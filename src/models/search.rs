@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+use super::post::Post;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub post: Post,
+    /// Escaped title, with matched terms wrapped in `<mark>`
+    pub title_html: String,
+    /// Escaped excerpt of the content around the matches, with matched terms wrapped in `<mark>`
+    pub snippet_html: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResults {
+    pub results: Vec<SearchResult>,
+    /// 1-based page number
+    pub page: u32,
+    pub has_more: bool,
+}
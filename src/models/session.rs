@@ -1,7 +1,11 @@
+use std::collections::BTreeSet;
 use std::str::FromStr;
 
+use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::permission::Permission;
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq, PartialOrd, Ord, Default)]
 pub enum ThemePreference {
     #[default]
@@ -27,6 +31,15 @@ impl FromStr for ThemePreference {
 }
 
 impl ThemePreference {
+    /// The name [`FromStr`] parses
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ThemePreference::Light => "light",
+            ThemePreference::Dark => "dark",
+            ThemePreference::System => "system",
+        }
+    }
+
     #[cfg(feature = "ssr")]
     pub fn from_libsql_value(value: libsql::Value) -> Self {
         match value {
@@ -72,6 +85,76 @@ impl From<&ThemePreference> for libsql::Value {
 pub struct SessionUser {
     pub id: Option<i64>,
     pub username: Option<String>,
-    pub is_admin: bool,
+    /// Resolved from the roles of the user, empty for anonymous sessions
+    #[serde(default)]
+    pub permissions: BTreeSet<Permission>,
     pub theme_preference: ThemePreference,
 }
+
+impl SessionUser {
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.permissions.contains(&permission)
+    }
+
+    /// Whether the user may edit or delete a post written by `author_id`
+    pub fn can_edit_post(&self, author_id: Option<i64>) -> bool {
+        self.has_permission(Permission::EditAllPosts)
+            || (self.has_permission(Permission::EditOwnPosts)
+                && self.id.is_some()
+                && self.id == author_id)
+    }
+}
+
+/// A session of the current user, as listed in their account settings
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DeviceSession {
+    /// Public id of the session, see [`AdminSession::id`](crate::models::admin::AdminSession::id)
+    pub id: String,
+    pub user_agent: Option<String>,
+    /// Address of the last request
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    /// Whether the user asked to stay logged in
+    pub remembered: bool,
+    /// Whether this is the session of the device looking at the list
+    pub current: bool,
+}
+
+impl DeviceSession {
+    /// Browser and operating system, as far as the user agent tells
+    pub fn device_name(&self) -> String {
+        let Some(user_agent) = &self.user_agent else {
+            return "Unknown device".to_string();
+        };
+
+        // Checked in order, Chrome based browsers also claim to be Chrome and Safari
+        let browser = [
+            ("Edg/", "Edge"),
+            ("OPR/", "Opera"),
+            ("Firefox/", "Firefox"),
+            ("Chrome/", "Chrome"),
+            ("Safari/", "Safari"),
+        ]
+        .into_iter()
+        .find(|(token, _)| user_agent.contains(token))
+        .map(|(_, name)| name);
+        let system = [
+            ("Android", "Android"),
+            ("iPhone", "iOS"),
+            ("iPad", "iPadOS"),
+            ("Windows", "Windows"),
+            ("Mac OS X", "macOS"),
+            ("Linux", "Linux"),
+        ]
+        .into_iter()
+        .find(|(token, _)| user_agent.contains(token))
+        .map(|(_, name)| name);
+
+        match (browser, system) {
+            (Some(browser), Some(system)) => format!("{browser} on {system}"),
+            (Some(name), None) | (None, Some(name)) => name.to_string(),
+            (None, None) => user_agent.clone(),
+        }
+    }
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// A TOTP secret waiting to be confirmed, as shown to the user enrolling it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TotpEnrollment {
+    /// Base32 encoded secret, for authenticator apps that can't scan the QR code
+    pub secret: String,
+    /// `otpauth://` provisioning URI
+    pub uri: String,
+    /// The provisioning URI as an SVG QR code
+    pub qr_svg: String,
+}
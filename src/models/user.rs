@@ -1,18 +1,27 @@
+use std::collections::BTreeSet;
+
 use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::permission::Permission;
 use super::session::{SessionUser, ThemePreference};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, PartialOrd, Ord)]
 pub struct User {
     pub id: i64,
     pub username: String,
+    /// Where password reset links are sent, if the user gave one
+    pub email: Option<String>,
     #[cfg(feature = "ssr")]
     #[cfg_attr(feature = "ssr", serde(skip_deserializing))]
     pub password_hash: String,
-    pub is_admin: bool,
+    pub roles: Vec<String>,
+    pub permissions: BTreeSet<Permission>,
     pub created_at: DateTime<Utc>,
     pub theme_preference: ThemePreference,
+    pub two_factor_enabled: bool,
+    /// Whether one of the roles of the user requires two-factor authentication
+    pub two_factor_required: bool,
 }
 
 impl User {
@@ -20,20 +29,46 @@ impl User {
         SessionUser {
             id: Some(self.id),
             username: Some(self.username.clone()),
-            is_admin: self.is_admin,
+            permissions: self.permissions.clone(),
             theme_preference: self.theme_preference,
         }
     }
+
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.permissions.contains(&permission)
+    }
+
+    /// See [`SessionUser::can_edit_post`]
+    pub fn can_edit_post(&self, author_id: Option<i64>) -> bool {
+        self.has_permission(Permission::EditAllPosts)
+            || (self.has_permission(Permission::EditOwnPosts) && author_id == Some(self.id))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewUser {
     pub username: String,
     pub password: String,
+    #[serde(default)]
+    pub email: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoginCredentials {
     pub username: String,
     pub password: String,
+    /// Keep the user logged in for longer, also after the browser is closed
+    #[serde(default)]
+    pub remember: bool,
+}
+
+/// Outcome of a correct username and password
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum LoginResponse {
+    LoggedIn(User),
+    /// The user has two-factor authentication, the login is completed by sending a code
+    /// along with this challenge
+    TwoFactorRequired {
+        challenge: String,
+    },
 }
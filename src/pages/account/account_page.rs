@@ -0,0 +1,734 @@
+use crate::app::CurrentUser;
+use crate::models::oidc::IdentityInfo;
+use crate::models::passkey::PasskeyInfo;
+use crate::models::session::DeviceSession;
+use crate::models::two_factor::TotpEnrollment;
+use crate::pages::auth::oidc::OidcProviderButtons;
+use crate::pages::auth::passkey::create_passkey;
+use crate::server::auth::{
+    delete_passkey, finish_passkey_registration, get_passkeys, start_passkey_registration,
+    update_email,
+};
+use crate::server::oidc::{get_identities, unlink_identity};
+use crate::server::session::{get_device_sessions, sign_out_device, sign_out_everywhere};
+use crate::server::two_factor::{
+    begin_totp_enrollment, confirm_totp_enrollment, disable_two_factor, regenerate_recovery_codes,
+};
+use leptos::{ev, prelude::*, task::spawn_local};
+
+/// Settings of the logged in user
+#[component]
+pub fn AccountPage() -> impl IntoView {
+    let user_resource = expect_context::<CurrentUser>();
+    // Outlives the refetch of the user that follows enabling two-factor authentication
+    let recovery_codes = RwSignal::new(None::<Vec<String>>);
+
+    view! {
+        <div class="max-w-2xl mx-auto w-full">
+            <Suspense fallback=|| {
+                view! { <div class="dark:text-gray-300">"Loading..."</div> }
+            }>
+                {move || {
+                    let Some(user) = user_resource.get().flatten() else {
+                        return view! {
+                            <p class="text-red-500 dark:text-red-400">
+                                "You must be logged in to access this page."
+                            </p>
+                        }
+                            .into_any();
+                    };
+
+                    view! {
+                        <h1 class="text-3xl font-bold mb-6 dark:text-white flex items-center gap-2">
+                            <span class="i-mdi-account-cog text-primary-500 dark:text-primary-400"></span>
+                            {user.username}
+                        </h1>
+                        {(user.two_factor_required && !user.two_factor_enabled)
+                            .then(|| {
+                                view! {
+                                    <div
+                                        class="bg-yellow-100 dark:bg-yellow-900/30 border border-yellow-300 dark:border-yellow-700 text-yellow-800 dark:text-yellow-200 px-4 py-3 rounded-lg mb-6 flex items-center gap-2"
+                                        role="alert"
+                                    >
+                                        <span class="i-mdi-shield-alert text-lg"></span>
+                                        <span>
+                                            "One of your roles requires two-factor authentication. Its permissions are withheld until you set it up below."
+                                        </span>
+                                    </div>
+                                }
+                            })}
+                        <EmailSettings email=user.email.unwrap_or_default() />
+                        <PasskeySettings />
+                        <LinkedAccountSettings />
+                        <TwoFactorSettings enabled=user.two_factor_enabled recovery_codes />
+                        <DeviceSettings />
+                    }
+                        .into_any()
+                }}
+            </Suspense>
+        </div>
+    }
+}
+
+#[component]
+fn EmailSettings(email: String) -> impl IntoView {
+    let (email, set_email) = signal(email);
+    let (error, set_error) = signal(Option::<String>::None);
+    let (saved, set_saved) = signal(false);
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        set_error.set(None);
+        set_saved.set(false);
+
+        spawn_local(async move {
+            match update_email(Some(email.get_untracked())).await {
+                Ok(()) => set_saved.set(true),
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+        });
+    };
+
+    view! {
+        <section class="bg-white/90 dark:bg-primary-800/90 p-6 rounded-xl shadow-lg dark:shadow-primary-900/50 border border-gray-100 dark:border-primary-700 mb-6">
+            <h2 class="text-xl font-bold mb-2 dark:text-white flex items-center gap-2">
+                <span class="i-mdi-email-outline"></span>
+                "Email"
+            </h2>
+            <p class="text-sm text-gray-600 dark:text-gray-300 mb-4">
+                "Password reset links are sent to this address. Leave it empty to remove it."
+            </p>
+
+            {move || {
+                error
+                    .get()
+                    .map(|err| {
+                        view! {
+                            <div
+                                class="bg-red-100 dark:bg-red-900/30 border border-red-300 dark:border-red-700 text-red-700 dark:text-red-300 px-4 py-3 rounded-lg mb-4 flex items-center gap-2"
+                                role="alert"
+                            >
+                                <span class="i-mdi-alert-circle text-lg"></span>
+                                <span class="block sm:inline">{err}</span>
+                            </div>
+                        }
+                    })
+            }}
+
+            <form on:submit=on_submit class="flex gap-2">
+                <input
+                    type="email"
+                    id="email"
+                    class="flex-grow px-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                    placeholder="you@example.com"
+                    on:input=move |ev| {
+                        set_email.set(event_target_value(&ev));
+                        set_saved.set(false);
+                    }
+                    prop:value=email
+                />
+                <button
+                    type="submit"
+                    class="bg-gradient-to-r from-primary-500 to-accent-500 text-white py-2 px-4 rounded-lg hover:from-primary-600 hover:to-accent-600 transition-all duration-200 font-medium flex items-center gap-2 hover:cursor-pointer"
+                >
+                    <span class=move || {
+                        if saved.get() { "i-mdi-check" } else { "i-mdi-content-save" }
+                    }></span>
+                    "Save"
+                </button>
+            </form>
+        </section>
+    }
+}
+
+#[component]
+fn TwoFactorSettings(
+    enabled: bool,
+    recovery_codes: RwSignal<Option<Vec<String>>>,
+) -> impl IntoView {
+    let user_resource = expect_context::<CurrentUser>();
+    let enrollment = RwSignal::new(None::<TotpEnrollment>);
+    let (code, set_code) = signal(String::new());
+    let (error, set_error) = signal(Option::<String>::None);
+
+    let begin = move |_| {
+        set_error.set(None);
+        spawn_local(async move {
+            match begin_totp_enrollment().await {
+                Ok(started) => enrollment.set(Some(started)),
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+        });
+    };
+
+    let confirm = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        set_error.set(None);
+        spawn_local(async move {
+            match confirm_totp_enrollment(code.get_untracked()).await {
+                Ok(codes) => {
+                    recovery_codes.set(Some(codes));
+                    user_resource.refetch();
+                }
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+        });
+    };
+
+    let regenerate = move |_| {
+        set_error.set(None);
+        spawn_local(async move {
+            match regenerate_recovery_codes(code.get_untracked()).await {
+                Ok(codes) => {
+                    set_code.set(String::new());
+                    recovery_codes.set(Some(codes));
+                }
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+        });
+    };
+
+    let disable = move |_| {
+        set_error.set(None);
+        spawn_local(async move {
+            match disable_two_factor(code.get_untracked()).await {
+                Ok(()) => user_resource.refetch(),
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+        });
+    };
+
+    let code_input = move |placeholder: &'static str| {
+        view! {
+            <input
+                type="text"
+                autocomplete="one-time-code"
+                class="flex-grow px-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                placeholder=placeholder
+                on:input=move |ev| set_code.set(event_target_value(&ev))
+                prop:value=code
+            />
+        }
+    };
+
+    let button_class = "bg-gradient-to-r from-primary-500 to-accent-500 text-white py-2 px-4 rounded-lg hover:from-primary-600 hover:to-accent-600 transition-all duration-200 font-medium flex items-center gap-2 hover:cursor-pointer";
+
+    view! {
+        <section class="bg-white/90 dark:bg-primary-800/90 p-6 rounded-xl shadow-lg dark:shadow-primary-900/50 border border-gray-100 dark:border-primary-700 mb-6">
+            <h2 class="text-xl font-bold mb-2 dark:text-white flex items-center gap-2">
+                <span class="i-mdi-shield-key-outline"></span>
+                "Two-Factor Authentication"
+            </h2>
+
+            {move || {
+                error
+                    .get()
+                    .map(|err| {
+                        view! {
+                            <div
+                                class="bg-red-100 dark:bg-red-900/30 border border-red-300 dark:border-red-700 text-red-700 dark:text-red-300 px-4 py-3 rounded-lg mb-4 flex items-center gap-2"
+                                role="alert"
+                            >
+                                <span class="i-mdi-alert-circle text-lg"></span>
+                                <span class="block sm:inline">{err}</span>
+                            </div>
+                        }
+                    })
+            }}
+
+            {move || {
+                if let Some(codes) = recovery_codes.get() {
+                    view! {
+                        <p class="text-sm text-gray-600 dark:text-gray-300 mb-4">
+                            "Keep these recovery codes somewhere safe. Each of them logs you in once without your authenticator, and they won't be shown again."
+                        </p>
+                        <ul class="grid grid-cols-2 gap-2 font-mono mb-4 dark:text-gray-200">
+                            {codes.into_iter().map(|code| view! { <li>{code}</li> }).collect_view()}
+                        </ul>
+                        <button
+                            type="button"
+                            class=button_class
+                            on:click=move |_| recovery_codes.set(None)
+                        >
+                            <span class="i-mdi-check"></span>
+                            "I saved them"
+                        </button>
+                    }
+                        .into_any()
+                } else if enabled {
+                    view! {
+                        <p class="text-sm text-gray-600 dark:text-gray-300 mb-4">
+                            "Enabled. Logging in asks for a code from your authenticator app. Enter one to get new recovery codes or to turn it off."
+                        </p>
+                        <div class="flex flex-wrap gap-2">
+                            {code_input("Code or recovery code")}
+                            <button type="button" class=button_class on:click=regenerate>
+                                <span class="i-mdi-refresh"></span>
+                                "New recovery codes"
+                            </button>
+                            <button
+                                type="button"
+                                class="bg-white dark:bg-primary-800 border border-red-300 dark:border-red-700 text-red-600 dark:text-red-400 py-2 px-4 rounded-lg hover:bg-red-50 dark:hover:bg-red-900/30 transition-all duration-200 font-medium flex items-center gap-2 hover:cursor-pointer"
+                                on:click=disable
+                            >
+                                <span class="i-mdi-shield-off"></span>
+                                "Turn off"
+                            </button>
+                        </div>
+                    }
+                        .into_any()
+                } else if let Some(started) = enrollment.get() {
+                    view! {
+                        <p class="text-sm text-gray-600 dark:text-gray-300 mb-4">
+                            "Scan this QR code with your authenticator app, then enter the code it shows to confirm."
+                        </p>
+                        <div class="bg-white p-2 rounded-lg inline-block mb-2" inner_html=started.qr_svg></div>
+                        <p class="text-sm text-gray-600 dark:text-gray-300 mb-4">
+                            "Can't scan it? Enter this key instead: "
+                            <code class="font-mono break-all dark:text-gray-200">{started.secret}</code>
+                        </p>
+                        <form on:submit=confirm class="flex gap-2">
+                            {code_input("123456")}
+                            <button type="submit" class=button_class>
+                                <span class="i-mdi-shield-check"></span>
+                                "Confirm"
+                            </button>
+                        </form>
+                    }
+                        .into_any()
+                } else {
+                    view! {
+                        <p class="text-sm text-gray-600 dark:text-gray-300 mb-4">
+                            "Protect your account with a code from an authenticator app on top of your password."
+                        </p>
+                        <button type="button" class=button_class on:click=begin>
+                            <span class="i-mdi-shield-plus"></span>
+                            "Set up"
+                        </button>
+                    }
+                        .into_any()
+                }
+            }}
+        </section>
+    }
+}
+
+#[component]
+fn PasskeySettings() -> impl IntoView {
+    let passkeys_resource = Resource::new(|| (), |_| get_passkeys());
+    let (name, set_name) = signal(String::new());
+    let (error, set_error) = signal(Option::<String>::None);
+
+    let add = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        set_error.set(None);
+
+        spawn_local(async move {
+            let result = async {
+                let started = start_passkey_registration().await?;
+                let credential = create_passkey(started.options).await?;
+                finish_passkey_registration(started.ceremony, credential, name.get_untracked())
+                    .await
+            }
+            .await;
+
+            match result {
+                Ok(()) => {
+                    set_name.set(String::new());
+                    passkeys_resource.refetch();
+                }
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+        });
+    };
+
+    let remove = Callback::new(move |id: String| {
+        if !window()
+            .confirm_with_message("Remove this passkey? It won't log you in anymore.")
+            .unwrap_or(false)
+        {
+            return;
+        }
+
+        spawn_local(async move {
+            match delete_passkey(id).await {
+                Ok(()) => passkeys_resource.refetch(),
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+        });
+    });
+
+    view! {
+        <section class="bg-white/90 dark:bg-primary-800/90 p-6 rounded-xl shadow-lg dark:shadow-primary-900/50 border border-gray-100 dark:border-primary-700 mb-6">
+            <h2 class="text-xl font-bold mb-2 dark:text-white flex items-center gap-2">
+                <span class="i-mdi-fingerprint"></span>
+                "Passkeys"
+            </h2>
+            <p class="text-sm text-gray-600 dark:text-gray-300 mb-4">
+                "Log in with your fingerprint, face or security key instead of your password."
+            </p>
+
+            {move || {
+                error
+                    .get()
+                    .map(|err| {
+                        view! {
+                            <div
+                                class="bg-red-100 dark:bg-red-900/30 border border-red-300 dark:border-red-700 text-red-700 dark:text-red-300 px-4 py-3 rounded-lg mb-4 flex items-center gap-2"
+                                role="alert"
+                            >
+                                <span class="i-mdi-alert-circle text-lg"></span>
+                                <span class="block sm:inline">{err}</span>
+                            </div>
+                        }
+                    })
+            }}
+
+            <Suspense fallback=|| {
+                view! { <p class="dark:text-gray-300">"Loading..."</p> }
+            }>
+                {move || Suspend::new(async move {
+                    match passkeys_resource.await {
+                        Err(e) => {
+                            view! {
+                                <p class="text-red-500 dark:text-red-400 mb-4">
+                                    "Error loading passkeys: " {e.to_string()}
+                                </p>
+                            }
+                                .into_any()
+                        }
+                        Ok(passkeys) if passkeys.is_empty() => ().into_any(),
+                        Ok(passkeys) => {
+                            view! {
+                                <ul class="divide-y divide-gray-100 dark:divide-primary-700 mb-4">
+                                    {passkeys
+                                        .into_iter()
+                                        .map(|passkey| view! { <PasskeyRow passkey on_remove=remove /> })
+                                        .collect_view()}
+                                </ul>
+                            }
+                                .into_any()
+                        }
+                    }
+                })}
+            </Suspense>
+
+            <form on:submit=add class="flex gap-2">
+                <input
+                    type="text"
+                    id="passkey-name"
+                    class="flex-grow px-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                    placeholder="Name, like \"Work laptop\""
+                    on:input=move |ev| set_name.set(event_target_value(&ev))
+                    prop:value=name
+                />
+                <button
+                    type="submit"
+                    class="bg-gradient-to-r from-primary-500 to-accent-500 text-white py-2 px-4 rounded-lg hover:from-primary-600 hover:to-accent-600 transition-all duration-200 font-medium flex items-center gap-2 hover:cursor-pointer"
+                >
+                    <span class="i-mdi-plus"></span>
+                    "Add a passkey"
+                </button>
+            </form>
+        </section>
+    }
+}
+
+#[component]
+fn PasskeyRow(passkey: PasskeyInfo, on_remove: Callback<String>) -> impl IntoView {
+    let id = passkey.id.clone();
+    let last_used = passkey.last_used_at.map_or_else(
+        || "never used".to_string(),
+        |date| format!("last used {}", date.format("%B %d, %Y %H:%M")),
+    );
+
+    view! {
+        <li class="py-2 flex items-center gap-2 dark:text-gray-200">
+            <span class="i-mdi-key-variant text-primary-500 dark:text-primary-400"></span>
+            <span class="flex-grow">
+                <span class="font-medium">{passkey.name}</span>
+                <span class="block text-xs text-gray-500 dark:text-gray-400">
+                    {format!("Added {}, {last_used}", passkey.created_at.format("%B %d, %Y"))}
+                </span>
+            </span>
+            <button
+                type="button"
+                class="text-red-600 dark:text-red-400 hover:underline text-sm hover:cursor-pointer"
+                on:click=move |_| on_remove.run(id.clone())
+            >
+                "Remove"
+            </button>
+        </li>
+    }
+}
+
+#[component]
+fn LinkedAccountSettings() -> impl IntoView {
+    let identities_resource = Resource::new(|| (), |_| get_identities());
+    let (error, set_error) = signal(Option::<String>::None);
+
+    let unlink = Callback::new(move |(provider, subject): (String, String)| {
+        if !window()
+            .confirm_with_message("Unlink this account? It won't log you in anymore.")
+            .unwrap_or(false)
+        {
+            return;
+        }
+
+        spawn_local(async move {
+            match unlink_identity(provider, subject).await {
+                Ok(()) => identities_resource.refetch(),
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+        });
+    });
+
+    view! {
+        <section class="bg-white/90 dark:bg-primary-800/90 p-6 rounded-xl shadow-lg dark:shadow-primary-900/50 border border-gray-100 dark:border-primary-700 mb-6">
+            <h2 class="text-xl font-bold mb-2 dark:text-white flex items-center gap-2">
+                <span class="i-mdi-link-variant"></span>
+                "Linked accounts"
+            </h2>
+            <p class="text-sm text-gray-600 dark:text-gray-300 mb-4">
+                "Log in with your account at another site instead of your password."
+            </p>
+
+            {move || {
+                error
+                    .get()
+                    .map(|err| {
+                        view! {
+                            <div
+                                class="bg-red-100 dark:bg-red-900/30 border border-red-300 dark:border-red-700 text-red-700 dark:text-red-300 px-4 py-3 rounded-lg mb-4 flex items-center gap-2"
+                                role="alert"
+                            >
+                                <span class="i-mdi-alert-circle text-lg"></span>
+                                <span class="block sm:inline">{err}</span>
+                            </div>
+                        }
+                    })
+            }}
+
+            <Suspense fallback=|| {
+                view! { <p class="dark:text-gray-300">"Loading..."</p> }
+            }>
+                {move || Suspend::new(async move {
+                    match identities_resource.await {
+                        Err(e) => {
+                            view! {
+                                <p class="text-red-500 dark:text-red-400 mb-4">
+                                    "Error loading linked accounts: " {e.to_string()}
+                                </p>
+                            }
+                                .into_any()
+                        }
+                        Ok(identities) if identities.is_empty() => ().into_any(),
+                        Ok(identities) => {
+                            view! {
+                                <ul class="divide-y divide-gray-100 dark:divide-primary-700 mb-4">
+                                    {identities
+                                        .into_iter()
+                                        .map(|identity| view! { <IdentityRow identity on_unlink=unlink /> })
+                                        .collect_view()}
+                                </ul>
+                            }
+                                .into_any()
+                        }
+                    }
+                })}
+            </Suspense>
+
+            <OidcProviderButtons
+                action="Link"
+                on_error=Callback::new(move |e| set_error.set(Some(e)))
+            />
+        </section>
+    }
+}
+
+#[component]
+fn IdentityRow(identity: IdentityInfo, on_unlink: Callback<(String, String)>) -> impl IntoView {
+    let key = (identity.provider.clone(), identity.subject.clone());
+    let last_used = identity.last_used_at.map_or_else(
+        || "never used".to_string(),
+        |date| format!("last used {}", date.format("%B %d, %Y %H:%M")),
+    );
+
+    view! {
+        <li class="py-2 flex items-center gap-2 dark:text-gray-200">
+            <span class="i-mdi-openid text-primary-500 dark:text-primary-400"></span>
+            <span class="flex-grow">
+                <span class="font-medium">{identity.provider_name}</span>
+                {identity.email.map(|email| view! { <span class="ml-1 text-sm">{email}</span> })}
+                <span class="block text-xs text-gray-500 dark:text-gray-400">
+                    {format!("Linked {}, {last_used}", identity.created_at.format("%B %d, %Y"))}
+                </span>
+            </span>
+            <button
+                type="button"
+                class="text-red-600 dark:text-red-400 hover:underline text-sm hover:cursor-pointer"
+                on:click=move |_| on_unlink.run(key.clone())
+            >
+                "Unlink"
+            </button>
+        </li>
+    }
+}
+
+#[component]
+fn DeviceSettings() -> impl IntoView {
+    let sessions_resource = Resource::new(|| (), |_| get_device_sessions());
+    let (error, set_error) = signal(Option::<String>::None);
+    let user_resource = expect_context::<CurrentUser>();
+    let navigate = leptos_router::hooks::use_navigate();
+
+    // Signing out this device leaves the page
+    let signed_out = {
+        let navigate = navigate.clone();
+        move || {
+            user_resource.set(None);
+            navigate("/", Default::default());
+        }
+    };
+
+    let sign_out = Callback::new({
+        let signed_out = signed_out.clone();
+        move |(id, current): (String, bool)| {
+            let signed_out = signed_out.clone();
+            spawn_local(async move {
+                match sign_out_device(id).await {
+                    Ok(()) if current => signed_out(),
+                    Ok(()) => sessions_resource.refetch(),
+                    Err(e) => set_error.set(Some(e.to_string())),
+                }
+            });
+        }
+    });
+
+    let on_sign_out_everywhere = move |_| {
+        if !window()
+            .confirm_with_message("Sign out on all your devices, this one included?")
+            .unwrap_or(false)
+        {
+            return;
+        }
+
+        let signed_out = signed_out.clone();
+        spawn_local(async move {
+            match sign_out_everywhere().await {
+                Ok(()) => signed_out(),
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+        });
+    };
+
+    view! {
+        <section class="bg-white/90 dark:bg-primary-800/90 p-6 rounded-xl shadow-lg dark:shadow-primary-900/50 border border-gray-100 dark:border-primary-700 mb-6">
+            <h2 class="text-xl font-bold mb-2 dark:text-white flex items-center gap-2">
+                <span class="i-mdi-devices"></span>
+                "Devices"
+            </h2>
+            <p class="text-sm text-gray-600 dark:text-gray-300 mb-4">
+                "Where you are logged in. Sign out a device you don't recognize or don't use anymore."
+            </p>
+
+            {move || {
+                error
+                    .get()
+                    .map(|err| {
+                        view! {
+                            <div
+                                class="bg-red-100 dark:bg-red-900/30 border border-red-300 dark:border-red-700 text-red-700 dark:text-red-300 px-4 py-3 rounded-lg mb-4 flex items-center gap-2"
+                                role="alert"
+                            >
+                                <span class="i-mdi-alert-circle text-lg"></span>
+                                <span class="block sm:inline">{err}</span>
+                            </div>
+                        }
+                    })
+            }}
+
+            <Suspense fallback=|| {
+                view! { <p class="dark:text-gray-300">"Loading..."</p> }
+            }>
+                {move || Suspend::new(async move {
+                    match sessions_resource.await {
+                        Err(e) => {
+                            view! {
+                                <p class="text-red-500 dark:text-red-400 mb-4">
+                                    "Error loading devices: " {e.to_string()}
+                                </p>
+                            }
+                                .into_any()
+                        }
+                        Ok(sessions) => {
+                            view! {
+                                <ul class="divide-y divide-gray-100 dark:divide-primary-700 mb-4">
+                                    {sessions
+                                        .into_iter()
+                                        .map(|session| view! { <DeviceRow session on_sign_out=sign_out /> })
+                                        .collect_view()}
+                                </ul>
+                            }
+                                .into_any()
+                        }
+                    }
+                })}
+            </Suspense>
+
+            <button
+                type="button"
+                class="text-red-600 dark:text-red-400 border border-red-300 dark:border-red-700 py-2 px-4 rounded-lg hover:bg-red-50 dark:hover:bg-red-900/30 transition-all duration-200 font-medium flex items-center gap-2 hover:cursor-pointer"
+                on:click=on_sign_out_everywhere
+            >
+                <span class="i-mdi-logout"></span>
+                "Sign out everywhere"
+            </button>
+        </section>
+    }
+}
+
+#[component]
+fn DeviceRow(session: DeviceSession, on_sign_out: Callback<(String, bool)>) -> impl IntoView {
+    let key = (session.id.clone(), session.current);
+    let device_name = session.device_name();
+    let mut details = vec![format!(
+        "Logged in {}, last seen {}",
+        session.created_at.format("%B %d, %Y"),
+        session.last_seen_at.format("%B %d, %Y %H:%M")
+    )];
+    details.extend(session.ip.map(|ip| format!("from {ip}")));
+    if session.remembered {
+        details.push("remembered".to_string());
+    }
+
+    view! {
+        <li class="py-2 flex items-center gap-2 dark:text-gray-200">
+            <span class="i-mdi-monitor-cellphone text-primary-500 dark:text-primary-400"></span>
+            <span class="flex-grow">
+                <span class="font-medium" title=session.user_agent>
+                    {device_name}
+                </span>
+                {session
+                    .current
+                    .then(|| {
+                        view! {
+                            <span class="ml-1 text-xs text-primary-600 dark:text-primary-400">
+                                "This device"
+                            </span>
+                        }
+                    })}
+                <span class="block text-xs text-gray-500 dark:text-gray-400">
+                    {details.join(", ")}
+                </span>
+            </span>
+            <button
+                type="button"
+                class="text-red-600 dark:text-red-400 hover:underline text-sm hover:cursor-pointer"
+                on:click=move |_| on_sign_out.run(key.clone())
+            >
+                "Sign out"
+            </button>
+        </li>
+    }
+}
@@ -0,0 +1,58 @@
+use crate::app::CurrentUser;
+use crate::models::permission::Permission;
+use leptos::prelude::*;
+use leptos_router::components::{Outlet, A};
+
+/// Admin section, with a tab for each of its pages
+#[component]
+pub fn AdminLayout() -> impl IntoView {
+    let user_resource = expect_context::<CurrentUser>();
+
+    let tab = |href: &'static str, icon: &'static str, label: &'static str| {
+        view! {
+            <A
+                href=href
+                exact=true
+                attr:class="px-4 py-2 rounded-full font-medium flex items-center gap-1 text-primary-700 dark:text-primary-200 hover:bg-primary-100 dark:hover:bg-primary-700 aria-[current=page]:bg-primary-500 aria-[current=page]:text-white transition-all duration-200"
+            >
+                <span class=icon></span>
+                {label}
+            </A>
+        }
+    };
+
+    view! {
+        <div class="max-w-5xl mx-auto w-full">
+            <Suspense fallback=|| {
+                view! { <div class="dark:text-gray-300">"Loading..."</div> }
+            }>
+                {move || {
+                    let user = user_resource.get().flatten();
+                    if user.is_none_or(|user| !user.has_permission(Permission::AccessAdmin)) {
+                        return view! {
+                            <p class="text-red-500 dark:text-red-400">
+                                "You must be an admin to access this page."
+                            </p>
+                        }
+                            .into_any();
+                    }
+
+                    view! {
+                        <h1 class="text-3xl font-bold mb-6 dark:text-white flex items-center gap-2">
+                            <span class="i-mdi-shield-account text-primary-500 dark:text-primary-400"></span>
+                            "Admin"
+                        </h1>
+                        <nav class="flex flex-wrap gap-2 mb-8">
+                            {tab("/admin", "i-mdi-account-multiple", "Users")}
+                            {tab("/admin/roles", "i-mdi-shield-key", "Roles")}
+                            {tab("/admin/posts", "i-mdi-file-document-multiple", "Posts")}
+                            {tab("/admin/sessions", "i-mdi-key-chain", "Sessions")}
+                        </nav>
+                        <Outlet />
+                    }
+                        .into_any()
+                }}
+            </Suspense>
+        </div>
+    }
+}
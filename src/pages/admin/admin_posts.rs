@@ -0,0 +1,188 @@
+use super::{confirm, run_action, ActionError};
+use crate::models::admin::{AdminPost, BulkPostAction};
+use crate::server::admin::{bulk_update_posts, get_admin_posts};
+use leptos::prelude::*;
+use leptos_router::components::A;
+use std::collections::BTreeSet;
+
+/// Every post, drafts included, with actions applied to the selected ones
+#[component]
+pub fn AdminPostsPage() -> impl IntoView {
+    let posts_resource = Resource::new(|| (), |_| get_admin_posts());
+    let selected = RwSignal::new(BTreeSet::<i64>::new());
+
+    let (error, set_error) = signal(None::<String>);
+    let (message, set_message) = signal(None::<String>);
+
+    let apply = move |action: BulkPostAction| {
+        let ids: Vec<i64> = selected.get_untracked().into_iter().collect();
+        if action == BulkPostAction::Delete
+            && !confirm(&format!("Delete {} posts for good?", ids.len()))
+        {
+            return;
+        }
+
+        run_action(bulk_update_posts(ids, action), set_error, move |count| {
+            set_message.set(Some(format!("{count} posts updated.")));
+            selected.set(BTreeSet::new());
+            posts_resource.refetch();
+        });
+    };
+
+    let action_button = move |action: BulkPostAction, icon: &'static str, label: &'static str| {
+        view! {
+            <button
+                type="button"
+                class="bg-white dark:bg-primary-800 border border-gray-300 dark:border-primary-600 dark:text-gray-200 py-1 px-3 rounded-lg hover:bg-primary-50 dark:hover:bg-primary-700 transition-all duration-200 font-medium flex items-center gap-1 hover:cursor-pointer disabled:opacity-50 disabled:cursor-not-allowed"
+                disabled=move || selected.with(|selected| selected.is_empty())
+                on:click=move |_| apply(action)
+            >
+                <span class=icon></span>
+                {label}
+            </button>
+        }
+    };
+
+    view! {
+        <div class="flex flex-wrap items-center gap-2 mb-4">
+            {action_button(BulkPostAction::Publish, "i-mdi-publish", "Publish")}
+            {action_button(BulkPostAction::Unpublish, "i-mdi-publish-off", "Unpublish")}
+            {action_button(BulkPostAction::Delete, "i-mdi-delete", "Delete")}
+            <span class="text-sm text-gray-500 dark:text-gray-400">
+                {move || format!("{} selected", selected.with(|selected| selected.len()))}
+            </span>
+            <span class="text-sm text-green-700 dark:text-green-400">{message}</span>
+        </div>
+
+        <ActionError error />
+
+        <Suspense fallback=|| {
+            view! { <p class="dark:text-gray-300">"Loading..."</p> }
+        }>
+            {move || Suspend::new(async move {
+                match posts_resource.await {
+                    Err(e) => {
+                        view! {
+                            <p class="text-red-500 dark:text-red-400">
+                                "Error loading posts: " {e.to_string()}
+                            </p>
+                        }
+                            .into_any()
+                    }
+                    Ok(posts) if posts.is_empty() => {
+                        view! { <p class="dark:text-gray-300">"No posts yet."</p> }.into_any()
+                    }
+                    Ok(posts) => {
+                        let ids: BTreeSet<i64> = posts.iter().map(|post| post.id).collect();
+                        let all_selected = {
+                            let ids = ids.clone();
+                            move || selected.with(|selected| *selected == ids)
+                        };
+
+                        view! {
+                            <table class="w-full text-sm bg-white dark:bg-primary-800 rounded-lg shadow dark:text-gray-200">
+                                <thead>
+                                    <tr class="text-left border-b border-gray-200 dark:border-primary-700">
+                                        <th class="p-2">
+                                            <input
+                                                type="checkbox"
+                                                title="Select all"
+                                                prop:checked=all_selected.clone()
+                                                on:change=move |_| {
+                                                    if all_selected() {
+                                                        selected.set(BTreeSet::new());
+                                                    } else {
+                                                        selected.set(ids.clone());
+                                                    }
+                                                }
+                                            />
+                                        </th>
+                                        <th class="p-2">"Title"</th>
+                                        <th class="p-2">"Author"</th>
+                                        <th class="p-2">"Status"</th>
+                                        <th class="p-2">"Updated"</th>
+                                        <th class="p-2"></th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {posts
+                                        .into_iter()
+                                        .map(|post| view! { <PostRow post selected /> })
+                                        .collect_view()}
+                                </tbody>
+                            </table>
+                        }
+                            .into_any()
+                    }
+                }
+            })}
+        </Suspense>
+    }
+}
+
+#[component]
+fn PostRow(post: AdminPost, selected: RwSignal<BTreeSet<i64>>) -> impl IntoView {
+    let id = post.id;
+
+    let (status_class, status) = match (post.published, post.publish_at) {
+        (true, _) => (
+            "bg-green-100 dark:bg-green-900/40 text-green-800 dark:text-green-200",
+            "Published".to_string(),
+        ),
+        (false, Some(publish_at)) => (
+            "bg-blue-100 dark:bg-blue-900/40 text-blue-800 dark:text-blue-200",
+            format!("Scheduled for {}", publish_at.format("%B %d, %Y %H:%M")),
+        ),
+        (false, None) => (
+            "bg-yellow-100 dark:bg-yellow-900/40 text-yellow-800 dark:text-yellow-200",
+            "Draft".to_string(),
+        ),
+    };
+
+    view! {
+        <tr class="border-b border-gray-100 dark:border-primary-700 last:border-0">
+            <td class="p-2">
+                <input
+                    type="checkbox"
+                    prop:checked=move || selected.with(|selected| selected.contains(&id))
+                    on:change=move |ev| {
+                        let checked = event_target_checked(&ev);
+                        selected
+                            .update(|selected| {
+                                if checked {
+                                    selected.insert(id);
+                                } else {
+                                    selected.remove(&id);
+                                }
+                            });
+                    }
+                />
+            </td>
+            <td class="p-2">
+                <A
+                    href=format!("/blog/{}", post.slug)
+                    attr:class="text-primary-600 dark:text-primary-400 hover:underline"
+                >
+                    {post.title}
+                </A>
+            </td>
+            <td class="p-2">{post.author.unwrap_or_else(|| "Unknown".to_string())}</td>
+            <td class="p-2">
+                <span class=format!(
+                    "text-xs font-medium px-2 py-0.5 rounded-full whitespace-nowrap {status_class}",
+                )>{status}</span>
+            </td>
+            <td class="p-2 whitespace-nowrap">
+                {post.updated_at.format("%B %d, %Y %H:%M").to_string()}
+            </td>
+            <td class="p-2 text-right">
+                <A
+                    href=format!("/blog/{}/edit", post.slug)
+                    attr:class="text-primary-600 dark:text-primary-400 hover:underline"
+                >
+                    "Edit"
+                </A>
+            </td>
+        </tr>
+    }
+}
@@ -0,0 +1,98 @@
+use super::ActionError;
+use crate::models::permission::Role;
+use crate::server::roles::{get_roles, set_role_requires_two_factor};
+use leptos::{prelude::*, task::spawn_local};
+
+/// Roles with their permissions, and whether they require two-factor authentication
+#[component]
+pub fn AdminRolesPage() -> impl IntoView {
+    let roles_resource = Resource::new(|| (), |_| get_roles());
+
+    let (error, set_error) = signal(None::<String>);
+    let handle_require = Callback::new(move |(role, required): (String, bool)| {
+        spawn_local(async move {
+            let result = set_role_requires_two_factor(role, required).await;
+            set_error.set(result.err().map(|e| e.to_string()));
+            // Also on failure, to reset the checkbox
+            roles_resource.refetch();
+        });
+    });
+
+    view! {
+        <p class="mb-4 text-sm text-gray-500 dark:text-gray-400">
+            "Users without two-factor authentication don't get the permissions of roles requiring it, until they set it up from their account page."
+        </p>
+
+        <ActionError error />
+
+        <Suspense fallback=|| {
+            view! { <p class="dark:text-gray-300">"Loading..."</p> }
+        }>
+            {move || Suspend::new(async move {
+                match roles_resource.await {
+                    Err(e) => {
+                        view! {
+                            <p class="text-red-500 dark:text-red-400">
+                                "Error loading roles: " {e.to_string()}
+                            </p>
+                        }
+                            .into_any()
+                    }
+                    Ok(roles) => {
+                        view! {
+                            <table class="w-full text-sm bg-white dark:bg-primary-800 rounded-lg shadow dark:text-gray-200">
+                                <thead>
+                                    <tr class="text-left border-b border-gray-200 dark:border-primary-700">
+                                        <th class="p-2">"Role"</th>
+                                        <th class="p-2">"Permissions"</th>
+                                        <th class="p-2">"Requires 2FA"</th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {roles
+                                        .into_iter()
+                                        .map(|role| view! { <RoleRow role on_require=handle_require /> })
+                                        .collect_view()}
+                                </tbody>
+                            </table>
+                        }
+                            .into_any()
+                    }
+                }
+            })}
+        </Suspense>
+    }
+}
+
+#[component]
+fn RoleRow(role: Role, on_require: Callback<(String, bool)>) -> impl IntoView {
+    let name = role.name.clone();
+
+    view! {
+        <tr class="border-b border-gray-100 dark:border-primary-700 last:border-0">
+            <td class="p-2 font-medium">{role.name}</td>
+            <td class="p-2">
+                <div class="flex flex-wrap gap-1">
+                    {role
+                        .permissions
+                        .into_iter()
+                        .map(|permission| {
+                            view! {
+                                <span class="text-xs font-mono px-2 py-0.5 rounded-full bg-primary-100 dark:bg-primary-700">
+                                    {permission.name()}
+                                </span>
+                            }
+                        })
+                        .collect_view()}
+                </div>
+            </td>
+            <td class="p-2">
+                <input
+                    type="checkbox"
+                    prop:checked=role.requires_two_factor
+                    on:change=move |ev| on_require.run((name.clone(), event_target_checked(&ev)))
+                />
+            </td>
+        </tr>
+    }
+}
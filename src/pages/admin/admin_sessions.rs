@@ -0,0 +1,95 @@
+use super::{run_action, ActionError};
+use crate::models::admin::AdminSession;
+use crate::server::admin::{get_admin_sessions, revoke_session};
+use leptos::prelude::*;
+
+/// Active sessions of logged in users, any of which may be revoked
+#[component]
+pub fn AdminSessionsPage() -> impl IntoView {
+    let sessions_resource = Resource::new(|| (), |_| get_admin_sessions());
+
+    let (error, set_error) = signal(None::<String>);
+    let handle_revoke = Callback::new(move |id: String| {
+        run_action(revoke_session(id), set_error, move |_| {
+            sessions_resource.refetch()
+        });
+    });
+
+    view! {
+        <ActionError error />
+
+        <Suspense fallback=|| {
+            view! { <p class="dark:text-gray-300">"Loading..."</p> }
+        }>
+            {move || Suspend::new(async move {
+                match sessions_resource.await {
+                    Err(e) => {
+                        view! {
+                            <p class="text-red-500 dark:text-red-400">
+                                "Error loading sessions: " {e.to_string()}
+                            </p>
+                        }
+                            .into_any()
+                    }
+                    Ok(list) => {
+                        view! {
+                            <table class="w-full text-sm bg-white dark:bg-primary-800 rounded-lg shadow dark:text-gray-200">
+                                <thead>
+                                    <tr class="text-left border-b border-gray-200 dark:border-primary-700">
+                                        <th class="p-2">"User"</th>
+                                        <th class="p-2">"Expires"</th>
+                                        <th class="p-2"></th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {list
+                                        .sessions
+                                        .into_iter()
+                                        .map(|session| {
+                                            view! { <SessionRow session on_revoke=handle_revoke /> }
+                                        })
+                                        .collect_view()}
+                                </tbody>
+                            </table>
+                        }
+                            .into_any()
+                    }
+                }
+            })}
+        </Suspense>
+    }
+}
+
+#[component]
+fn SessionRow(session: AdminSession, on_revoke: Callback<String>) -> impl IntoView {
+    let id = session.id;
+
+    view! {
+        <tr class="border-b border-gray-100 dark:border-primary-700 last:border-0">
+            <td class="p-2">
+                {session.username.unwrap_or_else(|| "Deleted user".to_string())}
+                {session
+                    .current
+                    .then(|| {
+                        view! {
+                            <span class="ml-2 text-xs font-medium px-2 py-0.5 rounded-full bg-primary-100 dark:bg-primary-700">
+                                "This session"
+                            </span>
+                        }
+                    })}
+            </td>
+            <td class="p-2 whitespace-nowrap">
+                {session.expires.format("%B %d, %Y %H:%M").to_string()}
+            </td>
+            <td class="p-2 text-right">
+                <button
+                    type="button"
+                    class="text-red-600 dark:text-red-400 hover:underline hover:cursor-pointer"
+                    on:click=move |_| on_revoke.run(id.clone())
+                >
+                    "Revoke"
+                </button>
+            </td>
+        </tr>
+    }
+}
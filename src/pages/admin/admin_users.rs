@@ -0,0 +1,235 @@
+use super::{confirm, run_action, ActionError};
+use crate::app::CurrentUser;
+use crate::models::admin::AdminUser;
+use crate::server::admin::{delete_user, get_admin_users, set_user_disabled};
+use crate::server::roles::{get_roles, grant_role, revoke_role};
+use leptos::prelude::*;
+use leptos_router::{
+    components::{Form, A},
+    hooks::use_query_map,
+};
+
+/// Users, searchable by username, with their roles and status
+#[component]
+pub fn AdminUsersPage() -> impl IntoView {
+    let query = use_query_map();
+    let search_query = move || query.get().get("q").unwrap_or_default();
+
+    let user_resource = expect_context::<CurrentUser>();
+    let users_resource = Resource::new(search_query, get_admin_users);
+    let roles_resource = OnceResource::new(get_roles());
+
+    let (error, set_error) = signal(None::<String>);
+    let refetch = move |_| users_resource.refetch();
+
+    let handle_grant = Callback::new(move |(username, role): (String, String)| {
+        run_action(grant_role(username, role), set_error, refetch);
+    });
+    let handle_revoke = Callback::new(move |(username, role): (String, String)| {
+        run_action(revoke_role(username, role), set_error, refetch);
+    });
+    let handle_disable = Callback::new(move |(id, disabled): (i64, bool)| {
+        run_action(set_user_disabled(id, disabled), set_error, refetch);
+    });
+    let handle_delete = Callback::new(move |user: AdminUser| {
+        if confirm(&format!(
+            "Delete {}? Their posts will be kept without an author.",
+            user.username
+        )) {
+            run_action(delete_user(user.id), set_error, refetch);
+        }
+    });
+
+    view! {
+        <Form method="get" action="/admin" attr:class="mb-6 flex gap-2">
+            <input
+                type="search"
+                name="q"
+                placeholder="Search users"
+                class="flex-grow px-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                prop:value=search_query
+            />
+            <button
+                type="submit"
+                class="bg-gradient-to-r from-primary-500 to-accent-500 text-white py-2 px-4 rounded-lg hover:from-primary-600 hover:to-accent-600 transition-all duration-200 font-medium flex items-center gap-2 hover:cursor-pointer"
+            >
+                <span class="i-mdi-magnify"></span>
+                "Search"
+            </button>
+        </Form>
+
+        <ActionError error />
+
+        <Suspense fallback=|| {
+            view! { <p class="dark:text-gray-300">"Loading..."</p> }
+        }>
+            {move || Suspend::new(async move {
+                let current_user_id = user_resource.await.map(|user| user.id);
+                let roles: Vec<String> = roles_resource
+                    .await
+                    .map(|roles| roles.into_iter().map(|role| role.name).collect())
+                    .unwrap_or_default();
+
+                match users_resource.await {
+                    Err(e) => {
+                        view! {
+                            <p class="text-red-500 dark:text-red-400">
+                                "Error loading users: " {e.to_string()}
+                            </p>
+                        }
+                            .into_any()
+                    }
+                    Ok(users) if users.is_empty() => {
+                        view! { <p class="dark:text-gray-300">"No users match."</p> }.into_any()
+                    }
+                    Ok(users) => {
+                        view! {
+                            <table class="w-full text-sm bg-white dark:bg-primary-800 rounded-lg shadow dark:text-gray-200">
+                                <thead>
+                                    <tr class="text-left border-b border-gray-200 dark:border-primary-700">
+                                        <th class="p-2">"Username"</th>
+                                        <th class="p-2">"Joined"</th>
+                                        <th class="p-2">"Posts"</th>
+                                        <th class="p-2">"Roles"</th>
+                                        <th class="p-2">"Status"</th>
+                                        <th class="p-2"></th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {users
+                                        .into_iter()
+                                        .map(|user| {
+                                            view! {
+                                                <UserRow
+                                                    is_self=Some(user.id) == current_user_id
+                                                    user
+                                                    roles=roles.clone()
+                                                    on_grant=handle_grant
+                                                    on_revoke=handle_revoke
+                                                    on_disable=handle_disable
+                                                    on_delete=handle_delete
+                                                />
+                                            }
+                                        })
+                                        .collect_view()}
+                                </tbody>
+                            </table>
+                        }
+                            .into_any()
+                    }
+                }
+            })}
+        </Suspense>
+    }
+}
+
+#[component]
+fn UserRow(
+    user: AdminUser,
+    /// Every existing role
+    roles: Vec<String>,
+    is_self: bool,
+    on_grant: Callback<(String, String)>,
+    on_revoke: Callback<(String, String)>,
+    on_disable: Callback<(i64, bool)>,
+    on_delete: Callback<AdminUser>,
+) -> impl IntoView {
+    let id = user.id;
+    let disabled = user.disabled;
+    let username = user.username.clone();
+    let deleted = user.clone();
+    let grantable: Vec<String> = roles
+        .into_iter()
+        .filter(|role| !user.roles.contains(role))
+        .collect();
+
+    view! {
+        <tr class="border-b border-gray-100 dark:border-primary-700 last:border-0">
+            <td class="p-2">
+                <A
+                    href=format!("/authors/{}", user.username)
+                    attr:class="text-primary-600 dark:text-primary-400 hover:underline"
+                >
+                    {user.username.clone()}
+                </A>
+            </td>
+            <td class="p-2 whitespace-nowrap">{user.created_at.format("%B %d, %Y").to_string()}</td>
+            <td class="p-2">{user.post_count}</td>
+            <td class="p-2">
+                <div class="flex flex-wrap items-center gap-1">
+                    {user
+                        .roles
+                        .iter()
+                        .map(|role| {
+                            let revoked = (username.clone(), role.clone());
+                            view! {
+                                <span class="inline-flex items-center gap-1 text-xs font-medium px-2 py-0.5 rounded-full bg-primary-100 dark:bg-primary-700">
+                                    {role.clone()}
+                                    <button
+                                        type="button"
+                                        class="i-mdi-close hover:cursor-pointer"
+                                        title="Revoke"
+                                        on:click=move |_| on_revoke.run(revoked.clone())
+                                    ></button>
+                                </span>
+                            }
+                        })
+                        .collect_view()}
+                    {(!grantable.is_empty())
+                        .then(|| {
+                            let username = username.clone();
+                            view! {
+                                <select
+                                    class="text-xs px-1 py-0.5 rounded border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50"
+                                    on:change=move |ev| {
+                                        let role = event_target_value(&ev);
+                                        if !role.is_empty() {
+                                            on_grant.run((username.clone(), role));
+                                        }
+                                    }
+                                >
+                                    <option value="" selected>
+                                        "Add role…"
+                                    </option>
+                                    {grantable
+                                        .iter()
+                                        .map(|role| view! { <option value=role.clone()>{role.clone()}</option> })
+                                        .collect_view()}
+                                </select>
+                            }
+                        })}
+                </div>
+            </td>
+            <td class="p-2">
+                {if disabled {
+                    view! { <span class="text-red-600 dark:text-red-400">"Disabled"</span> }
+                        .into_any()
+                } else {
+                    view! { <span class="text-green-700 dark:text-green-400">"Active"</span> }
+                        .into_any()
+                }}
+            </td>
+            <td class="p-2 text-right whitespace-nowrap">
+                {(!is_self)
+                    .then(|| {
+                        view! {
+                            <button
+                                type="button"
+                                class="text-primary-600 dark:text-primary-400 hover:underline hover:cursor-pointer mr-3"
+                                on:click=move |_| on_disable.run((id, !disabled))
+                            >
+                                {if disabled { "Enable" } else { "Disable" }}
+                            </button>
+                            <button
+                                type="button"
+                                class="text-red-600 dark:text-red-400 hover:underline hover:cursor-pointer"
+                                on:click=move |_| on_delete.run(deleted.clone())
+                            >
+                                "Delete"
+                            </button>
+                        }
+                    })}
+            </td>
+        </tr>
+    }
+}
@@ -0,0 +1,53 @@
+mod admin_layout;
+mod admin_posts;
+mod admin_roles;
+mod admin_sessions;
+mod admin_users;
+
+pub use admin_layout::AdminLayout;
+pub use admin_posts::AdminPostsPage;
+pub use admin_roles::AdminRolesPage;
+pub use admin_sessions::AdminSessionsPage;
+pub use admin_users::AdminUsersPage;
+
+use leptos::{prelude::*, task::spawn_local};
+use std::future::Future;
+
+/// Run an admin action in the background, then report its outcome
+fn run_action<T: 'static>(
+    action: impl Future<Output = Result<T, ServerFnError>> + 'static,
+    set_error: WriteSignal<Option<String>>,
+    on_success: impl FnOnce(T) + 'static,
+) {
+    spawn_local(async move {
+        match action.await {
+            Ok(value) => {
+                set_error.set(None);
+                on_success(value);
+            }
+            Err(e) => set_error.set(Some(e.to_string())),
+        }
+    });
+}
+
+/// Ask for a confirmation before doing something that cannot be undone
+fn confirm(message: &str) -> bool {
+    window().confirm_with_message(message).unwrap_or(false)
+}
+
+#[component]
+fn ActionError(error: ReadSignal<Option<String>>) -> impl IntoView {
+    move || {
+        error.get().map(|err| {
+            view! {
+                <div
+                    class="bg-red-100 dark:bg-red-900/30 border border-red-300 dark:border-red-700 text-red-700 dark:text-red-300 px-4 py-3 rounded-lg mb-6 flex items-center gap-2"
+                    role="alert"
+                >
+                    <span class="i-mdi-alert-circle text-lg"></span>
+                    <span>{err}</span>
+                </div>
+            }
+        })
+    }
+}
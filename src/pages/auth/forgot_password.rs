@@ -0,0 +1,120 @@
+use crate::server::password_reset::request_password_reset;
+use leptos::{ev, prelude::*, task::spawn_local};
+use leptos_router::components::A;
+
+/// Asks for the email address to send a password reset link to
+#[component]
+pub fn ForgotPasswordPage() -> impl IntoView {
+    let (email, set_email) = signal(String::new());
+    let (error, set_error) = signal(Option::<String>::None);
+    let (sent, set_sent) = signal(false);
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+
+        if email.get_untracked().trim().is_empty() {
+            set_error.set(Some("Email is required".to_string()));
+            return;
+        }
+        set_error.set(None);
+
+        spawn_local(async move {
+            match request_password_reset(email.get_untracked()).await {
+                Ok(()) => set_sent.set(true),
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+        });
+    };
+
+    view! {
+        <div class="max-w-md mx-auto bg-white/90 dark:bg-primary-800/90 p-8 rounded-xl shadow-lg dark:shadow-primary-900/50 backdrop-blur-sm border border-gray-100 dark:border-primary-700 w-full">
+            <div class="text-center mb-8">
+                <div class="inline-flex p-2 bg-gradient-to-br from-primary-400 to-accent-500 rounded-full mb-4">
+                    <span class="i-mdi-lock-reset text-white h-8 w-8"></span>
+                </div>
+                <h1 class="text-3xl font-bold dark:text-white">"Forgot Password"</h1>
+                <p class="text-gray-600 dark:text-gray-300 mt-2">
+                    "We'll email you a link to choose a new one"
+                </p>
+            </div>
+
+            {move || {
+                error
+                    .get()
+                    .map(|err| {
+                        view! {
+                            <div
+                                class="bg-red-100 dark:bg-red-900/30 border border-red-300 dark:border-red-700 text-red-700 dark:text-red-300 px-4 py-3 rounded-lg mb-6 flex items-center gap-2"
+                                role="alert"
+                            >
+                                <span class="i-mdi-alert-circle text-lg"></span>
+                                <span class="block sm:inline">{err}</span>
+                            </div>
+                        }
+                    })
+            }}
+
+            <Show
+                when=move || sent.get()
+                fallback=move || {
+                    view! {
+                        <form on:submit=on_submit class="space-y-6">
+                            <div>
+                                <label
+                                    for="email"
+                                    class="block text-gray-700 dark:text-gray-200 font-medium mb-2 flex items-center gap-1"
+                                >
+                                    <span class="i-mdi-email-outline"></span>
+                                    "Email"
+                                </label>
+                                <div class="relative">
+                                    <span class="absolute left-3 inset-y-0 flex items-center text-gray-400 dark:text-gray-300">
+                                        <span class="i-mdi-email text-lg"></span>
+                                    </span>
+                                    <input
+                                        type="email"
+                                        id="email"
+                                        class="w-full pl-10 pr-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                                        placeholder="Enter the email of your account"
+                                        on:input=move |ev| {
+                                            set_email.set(event_target_value(&ev));
+                                        }
+                                        prop:value=email
+                                    />
+                                </div>
+                            </div>
+
+                            <button
+                                type="submit"
+                                class="w-full bg-gradient-to-r from-primary-500 to-accent-500 text-white py-3 px-4 rounded-lg hover:from-primary-600 hover:to-accent-600 focus:outline-none focus:ring-2 focus:ring-primary-500 focus:ring-offset-2 dark:focus:ring-offset-primary-800 transition-all duration-200 font-medium flex items-center justify-center gap-2"
+                            >
+                                <span class="i-mdi-email-fast"></span>
+                                "Send Reset Link"
+                            </button>
+                        </form>
+                    }
+                }
+            >
+                <div
+                    class="bg-green-100 dark:bg-green-900/30 border border-green-300 dark:border-green-700 text-green-700 dark:text-green-300 px-4 py-3 rounded-lg mb-6 flex items-center gap-2"
+                    role="alert"
+                >
+                    <span class="i-mdi-check-circle text-lg"></span>
+                    <span class="block sm:inline">
+                        "If an account uses this email, a reset link is on its way. It expires in an hour."
+                    </span>
+                </div>
+            </Show>
+
+            <p class="mt-6 text-center dark:text-gray-300 text-sm">
+                "Remembered it? "
+                <A
+                    href="/login"
+                    attr:class="text-primary-600 dark:text-primary-400 hover:underline font-medium"
+                >
+                    "Login"
+                </A>
+            </p>
+        </div>
+    }
+}
@@ -1,6 +1,9 @@
+use super::oidc::OidcProviderButtons;
+use super::passkey::authenticate_with_passkey;
 use crate::app::CurrentUser;
-use crate::models::user::{LoginCredentials, User};
-use crate::server::auth::login;
+use crate::models::user::{LoginCredentials, LoginResponse};
+use crate::server::auth::{finish_passkey_login, login, start_passkey_login};
+use crate::server::two_factor::verify_login_code;
 use leptos::{ev, prelude::*, task::spawn_local};
 use leptos_router::components::A;
 
@@ -9,13 +12,20 @@ use leptos_router::components::A;
 pub fn LoginPage() -> impl IntoView {
     let (username, set_username) = signal(String::new());
     let (password, set_password) = signal(String::new());
+    let (remember, set_remember) = signal(false);
     let (error, set_error) = signal(Option::<String>::None);
+    // Set once the password is verified for users with two-factor authentication, or by a
+    // provider login sending them here
+    let (challenge, set_challenge) = signal(
+        leptos_router::hooks::use_query_map().with_untracked(|query| query.get("challenge")),
+    );
+    let (code, set_code) = signal(String::new());
 
     // Navigate after successful login
     let navigate = leptos_router::hooks::use_navigate();
 
     // Login result storage
-    let (login_result, set_login_result) = signal(None::<Result<User, ServerFnError>>);
+    let (login_result, set_login_result) = signal(None::<Result<LoginResponse, ServerFnError>>);
 
     let on_submit = move |ev: ev::SubmitEvent| {
         ev.prevent_default();
@@ -34,6 +44,7 @@ pub fn LoginPage() -> impl IntoView {
         let credentials = LoginCredentials {
             username: username.get_untracked(),
             password: password.get_untracked(),
+            remember: remember.get_untracked(),
         };
 
         spawn_local(async move {
@@ -42,6 +53,45 @@ pub fn LoginPage() -> impl IntoView {
         });
     };
 
+    let on_passkey = move |_| {
+        // Passkeys are looked up by username
+        if username.get_untracked().trim().is_empty() {
+            set_error.set(Some(
+                "Enter your username to log in with a passkey".to_string(),
+            ));
+            return;
+        }
+        set_error.set(None);
+
+        spawn_local(async move {
+            let result = async {
+                let started = start_passkey_login(username.get_untracked()).await?;
+                let credential = authenticate_with_passkey(started.options).await?;
+                finish_passkey_login(started.ceremony, credential, remember.get_untracked()).await
+            }
+            .await;
+            set_login_result.set(Some(result.map(LoginResponse::LoggedIn)));
+        });
+    };
+
+    let on_submit_code = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+
+        let Some(challenge) = challenge.get_untracked() else {
+            return;
+        };
+        if code.get_untracked().trim().is_empty() {
+            set_error.set(Some("Code is required".to_string()));
+            return;
+        }
+        set_error.set(None);
+
+        spawn_local(async move {
+            let result = verify_login_code(challenge, code.get_untracked()).await;
+            set_login_result.set(Some(result.map(LoginResponse::LoggedIn)));
+        });
+    };
+
     // Get user resource from context to refresh after login
     let user_resource = expect_context::<CurrentUser>();
 
@@ -49,7 +99,10 @@ pub fn LoginPage() -> impl IntoView {
     Effect::new(move |_| {
         if let Some(result) = login_result.get() {
             match result {
-                Ok(_) => {
+                Ok(LoginResponse::TwoFactorRequired { challenge }) => {
+                    set_challenge.set(Some(challenge));
+                }
+                Ok(LoginResponse::LoggedIn(_)) => {
                     // Refresh user resource to update the UI
                     user_resource.refetch();
 
@@ -91,67 +144,150 @@ pub fn LoginPage() -> impl IntoView {
                     })
             }}
 
-            <form on:submit=on_submit class="space-y-6">
-                <div>
-                    <label
-                        for="username"
-                        class="block text-gray-700 dark:text-gray-200 font-medium mb-2 flex items-center gap-1"
-                    >
-                        <span class="i-mdi-account-outline"></span>
-                        "Username"
-                    </label>
-                    <div class="relative">
-                        <span class="absolute left-3 inset-y-0 flex items-center text-gray-400 dark:text-gray-300">
-                            <span class="i-mdi-account text-lg"></span>
-                        </span>
-                        <input
-                            type="text"
-                            id="username"
-                            class="w-full pl-10 pr-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
-                            placeholder="Enter your username"
-                            on:input=move |ev| {
-                                set_username.set(event_target_value(&ev));
-                            }
-                            prop:value=username
-                        />
+            <Show
+                when=move || challenge.get().is_some()
+                fallback=move || {
+                    view! {
+                        <form on:submit=on_submit class="space-y-6">
+                            <div>
+                                <label
+                                    for="username"
+                                    class="block text-gray-700 dark:text-gray-200 font-medium mb-2 flex items-center gap-1"
+                                >
+                                    <span class="i-mdi-account-outline"></span>
+                                    "Username"
+                                </label>
+                                <div class="relative">
+                                    <span class="absolute left-3 inset-y-0 flex items-center text-gray-400 dark:text-gray-300">
+                                        <span class="i-mdi-account text-lg"></span>
+                                    </span>
+                                    <input
+                                        type="text"
+                                        id="username"
+                                        class="w-full pl-10 pr-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                                        placeholder="Enter your username"
+                                        on:input=move |ev| {
+                                            set_username.set(event_target_value(&ev));
+                                        }
+                                        prop:value=username
+                                    />
+                                </div>
+                            </div>
+
+                            <div>
+                                <label
+                                    for="password"
+                                    class="block text-gray-700 dark:text-gray-200 font-medium mb-2 flex items-center gap-1"
+                                >
+                                    <span class="i-mdi-lock-outline"></span>
+                                    "Password"
+                                </label>
+                                <div class="relative">
+                                    <span class="absolute left-3 inset-y-0 flex items-center text-gray-400 dark:text-gray-300">
+                                        <span class="i-mdi-lock text-lg"></span>
+                                    </span>
+                                    <input
+                                        type="password"
+                                        id="password"
+                                        class="w-full pl-10 pr-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                                        placeholder="Enter your password"
+                                        on:input=move |ev| {
+                                            set_password.set(event_target_value(&ev));
+                                        }
+                                        prop:value=password
+                                    />
+                                </div>
+                            </div>
+
+                            <label class="flex items-center gap-2 text-gray-700 dark:text-gray-200 text-sm">
+                                <input
+                                    type="checkbox"
+                                    id="remember"
+                                    class="accent-primary-500"
+                                    on:change=move |ev| set_remember.set(event_target_checked(&ev))
+                                    prop:checked=remember
+                                />
+                                "Remember me"
+                            </label>
+
+                            <button
+                                type="submit"
+                                class="w-full bg-gradient-to-r from-primary-500 to-accent-500 text-white py-3 px-4 rounded-lg hover:from-primary-600 hover:to-accent-600 focus:outline-none focus:ring-2 focus:ring-primary-500 focus:ring-offset-2 dark:focus:ring-offset-primary-800 transition-all duration-200 font-medium flex items-center justify-center gap-2"
+                            >
+                                <span class="i-mdi-login"></span>
+                                "Login"
+                            </button>
+
+                            <div class="flex items-center gap-3 text-sm text-gray-400 dark:text-gray-400">
+                                <span class="flex-grow border-t border-gray-200 dark:border-primary-700"></span>
+                                "or"
+                                <span class="flex-grow border-t border-gray-200 dark:border-primary-700"></span>
+                            </div>
+
+                            <button
+                                type="button"
+                                class="w-full bg-white dark:bg-primary-800 border border-primary-300 dark:border-primary-600 text-primary-700 dark:text-primary-200 py-3 px-4 rounded-lg hover:bg-primary-50 dark:hover:bg-primary-700 focus:outline-none focus:ring-2 focus:ring-primary-500 focus:ring-offset-2 dark:focus:ring-offset-primary-800 transition-all duration-200 font-medium flex items-center justify-center gap-2"
+                                on:click=on_passkey
+                            >
+                                <span class="i-mdi-fingerprint"></span>
+                                "Login with a passkey"
+                            </button>
+
+                            <OidcProviderButtons
+                                remember
+                                on_error=Callback::new(move |e| set_error.set(Some(e)))
+                            />
+                        </form>
+                    }
+                }
+            >
+                <form on:submit=on_submit_code class="space-y-6">
+                    <div>
+                        <label
+                            for="code"
+                            class="block text-gray-700 dark:text-gray-200 font-medium mb-2 flex items-center gap-1"
+                        >
+                            <span class="i-mdi-shield-key-outline"></span>
+                            "Authentication Code"
+                        </label>
+                        <div class="relative">
+                            <span class="absolute left-3 inset-y-0 flex items-center text-gray-400 dark:text-gray-300">
+                                <span class="i-mdi-cellphone-key text-lg"></span>
+                            </span>
+                            <input
+                                type="text"
+                                id="code"
+                                autocomplete="one-time-code"
+                                class="w-full pl-10 pr-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                                placeholder="Code from your app, or a recovery code"
+                                on:input=move |ev| {
+                                    set_code.set(event_target_value(&ev));
+                                }
+                                prop:value=code
+                            />
+                        </div>
                     </div>
-                </div>
 
-                <div>
-                    <label
-                        for="password"
-                        class="block text-gray-700 dark:text-gray-200 font-medium mb-2 flex items-center gap-1"
+                    <button
+                        type="submit"
+                        class="w-full bg-gradient-to-r from-primary-500 to-accent-500 text-white py-3 px-4 rounded-lg hover:from-primary-600 hover:to-accent-600 focus:outline-none focus:ring-2 focus:ring-primary-500 focus:ring-offset-2 dark:focus:ring-offset-primary-800 transition-all duration-200 font-medium flex items-center justify-center gap-2"
                     >
-                        <span class="i-mdi-lock-outline"></span>
-                        "Password"
-                    </label>
-                    <div class="relative">
-                        <span class="absolute left-3 inset-y-0 flex items-center text-gray-400 dark:text-gray-300">
-                            <span class="i-mdi-lock text-lg"></span>
-                        </span>
-                        <input
-                            type="password"
-                            id="password"
-                            class="w-full pl-10 pr-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
-                            placeholder="Enter your password"
-                            on:input=move |ev| {
-                                set_password.set(event_target_value(&ev));
-                            }
-                            prop:value=password
-                        />
-                    </div>
-                </div>
+                        <span class="i-mdi-shield-check"></span>
+                        "Verify"
+                    </button>
+                </form>
+            </Show>
 
-                <button
-                    type="submit"
-                    class="w-full bg-gradient-to-r from-primary-500 to-accent-500 text-white py-3 px-4 rounded-lg hover:from-primary-600 hover:to-accent-600 focus:outline-none focus:ring-2 focus:ring-primary-500 focus:ring-offset-2 dark:focus:ring-offset-primary-800 transition-all duration-200 font-medium flex items-center justify-center gap-2"
+            <p class="mt-6 text-center dark:text-gray-300 text-sm">
+                <A
+                    href="/forgot-password"
+                    attr:class="text-primary-600 dark:text-primary-400 hover:underline font-medium"
                 >
-                    <span class="i-mdi-login"></span>
-                    "Login"
-                </button>
-            </form>
+                    "Forgot your password?"
+                </A>
+            </p>
 
-            <p class="mt-6 text-center dark:text-gray-300 text-sm">
+            <p class="mt-2 text-center dark:text-gray-300 text-sm">
                 "Don't have an account? "
                 <A
                     href="/signup"
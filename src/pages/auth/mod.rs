@@ -1,7 +1,14 @@
+mod forgot_password;
 mod login;
 mod logout;
+pub mod oidc;
+pub mod passkey;
+mod reset_password;
 mod signup;
 
+pub use forgot_password::ForgotPasswordPage;
 pub use login::LoginPage;
 pub use logout::LogoutPage;
-pub use signup::SignupPage;
\ No newline at end of file
+pub use oidc::OidcCallbackPage;
+pub use reset_password::ResetPasswordPage;
+pub use signup::SignupPage;
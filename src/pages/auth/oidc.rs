@@ -0,0 +1,151 @@
+use crate::app::CurrentUser;
+use crate::models::oidc::OidcLoginResponse;
+use crate::server::oidc::{finish_oidc_login, get_oidc_providers, start_oidc_login};
+use leptos::{prelude::*, task::spawn_local};
+use leptos_router::hooks::{use_navigate, use_query_map};
+
+/// Send the browser to the login page of `provider`. Logged in users link their account there.
+pub async fn continue_with_provider(provider: String, remember: bool) -> Result<(), ServerFnError> {
+    let url = start_oidc_login(provider, remember).await?;
+    window()
+        .location()
+        .set_href(&url)
+        .map_err(|_| ServerFnError::new("The login page of the provider could not be opened"))
+}
+
+/// A button per OpenID Connect provider, nothing when none is configured
+#[component]
+pub fn OidcProviderButtons(
+    /// Label of the buttons, given the name of the provider
+    #[prop(default = "Continue with")]
+    action: &'static str,
+    /// Whether the session started by a login is to be remembered
+    #[prop(optional, into)]
+    remember: Signal<bool>,
+    on_error: Callback<String>,
+) -> impl IntoView {
+    let providers_resource = Resource::new(|| (), |_| get_oidc_providers());
+
+    view! {
+        <Suspense>
+            {move || Suspend::new(async move {
+                let providers = providers_resource.await.unwrap_or_default();
+                if providers.is_empty() {
+                    return ().into_any();
+                }
+
+                view! {
+                    <div class="space-y-3">
+                        {providers
+                            .into_iter()
+                            .map(|provider| {
+                                let id = provider.id.clone();
+                                let on_click = move |_| {
+                                    let id = id.clone();
+                                    spawn_local(async move {
+                                        if let Err(e) =
+                                            continue_with_provider(id, remember.get_untracked()).await
+                                        {
+                                            on_error.run(e.to_string());
+                                        }
+                                    });
+                                };
+
+                                view! {
+                                    <button
+                                        type="button"
+                                        class="w-full bg-white dark:bg-primary-800 border border-primary-300 dark:border-primary-600 text-primary-700 dark:text-primary-200 py-3 px-4 rounded-lg hover:bg-primary-50 dark:hover:bg-primary-700 focus:outline-none focus:ring-2 focus:ring-primary-500 focus:ring-offset-2 dark:focus:ring-offset-primary-800 transition-all duration-200 font-medium flex items-center justify-center gap-2"
+                                        on:click=on_click
+                                    >
+                                        <span class="i-mdi-openid"></span>
+                                        {format!("{action} {}", provider.name)}
+                                    </button>
+                                }
+                            })
+                            .collect_view()}
+                    </div>
+                }
+                    .into_any()
+            })}
+        </Suspense>
+    }
+}
+
+/// Page providers send users back to, which finishes their login
+#[component]
+pub fn OidcCallbackPage() -> impl IntoView {
+    let query = use_query_map();
+    let (error, set_error) = signal(Option::<String>::None);
+    let user_resource = expect_context::<CurrentUser>();
+    let navigate = use_navigate();
+
+    // Only runs in the browser, which holds the cookie of the login
+    Effect::new(move |_| {
+        let (state, code, provider_error) = query.with_untracked(|query| {
+            (
+                query.get("state"),
+                query.get("code"),
+                query
+                    .get("error_description")
+                    .or_else(|| query.get("error")),
+            )
+        });
+        let (Some(state), Some(code)) = (state, code) else {
+            set_error.set(Some(
+                provider_error.unwrap_or_else(|| "The login was cancelled".to_string()),
+            ));
+            return;
+        };
+
+        let navigate = navigate.clone();
+        spawn_local(async move {
+            match finish_oidc_login(state, code).await {
+                Ok(OidcLoginResponse::LoggedIn(_)) => {
+                    user_resource.refetch();
+                    navigate("/", Default::default());
+                }
+                Ok(OidcLoginResponse::TwoFactorRequired { challenge }) => {
+                    navigate(&format!("/login?challenge={challenge}"), Default::default());
+                }
+                Ok(OidcLoginResponse::Linked) => navigate("/account", Default::default()),
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+        });
+    });
+
+    view! {
+        <div class="max-w-md mx-auto bg-white/90 dark:bg-primary-800/90 p-8 rounded-xl shadow-lg dark:shadow-primary-900/50 backdrop-blur-sm border border-gray-100 dark:border-primary-700 w-full">
+            {move || match error.get() {
+                Some(err) => {
+                    view! {
+                        <div
+                            class="bg-red-100 dark:bg-red-900/30 border border-red-300 dark:border-red-700 text-red-700 dark:text-red-300 px-4 py-3 rounded-lg mb-6 flex items-center gap-2"
+                            role="alert"
+                        >
+                            <span class="i-mdi-alert-circle text-lg"></span>
+                            <span class="block sm:inline">{err}</span>
+                        </div>
+                        <p class="text-center dark:text-gray-300 text-sm">
+                            <a
+                                href="/login"
+                                class="text-primary-600 dark:text-primary-400 hover:underline font-medium"
+                            >
+                                "Back to login"
+                            </a>
+                        </p>
+                    }
+                        .into_any()
+                }
+                None => {
+                    view! {
+                        <p class="text-center dark:text-gray-300 flex items-center justify-center gap-2">
+                            <span class="i-mdi-loading animate-spin"></span>
+                            "Logging you in..."
+                        </p>
+                    }
+                        .into_any()
+                }
+            }}
+        </div>
+    }
+}
@@ -0,0 +1,78 @@
+use leptos::prelude::*;
+use webauthn_rs_proto::{
+    CreationChallengeResponse, PublicKeyCredential, RegisterPublicKeyCredential,
+    RequestChallengeResponse,
+};
+
+/// Ask the authenticator of the browser to create a passkey with these options
+pub async fn create_passkey(
+    options: CreationChallengeResponse,
+) -> Result<RegisterPublicKeyCredential, ServerFnError> {
+    #[cfg(feature = "hydrate")]
+    {
+        use leptos::wasm_bindgen::JsCast;
+        use leptos::web_sys;
+
+        let promise = window()
+            .navigator()
+            .credentials()
+            .create_with_options(&options.into())
+            .map_err(browser_error)?;
+        let credential = wasm_bindgen_futures::JsFuture::from(promise)
+            .await
+            .map_err(browser_error)?
+            .dyn_into::<web_sys::PublicKeyCredential>()
+            .map_err(|_| ServerFnError::new("No passkey was created"))?;
+
+        Ok(credential.into())
+    }
+    #[cfg(not(feature = "hydrate"))]
+    {
+        let _ = options;
+        Err(ServerFnError::new("Passkeys need a browser"))
+    }
+}
+
+/// Ask the authenticator of the browser to sign the challenge of these options with a passkey
+pub async fn authenticate_with_passkey(
+    options: RequestChallengeResponse,
+) -> Result<PublicKeyCredential, ServerFnError> {
+    #[cfg(feature = "hydrate")]
+    {
+        use leptos::wasm_bindgen::JsCast;
+        use leptos::web_sys;
+
+        let promise = window()
+            .navigator()
+            .credentials()
+            .get_with_options(&options.into())
+            .map_err(browser_error)?;
+        let credential = wasm_bindgen_futures::JsFuture::from(promise)
+            .await
+            .map_err(browser_error)?
+            .dyn_into::<web_sys::PublicKeyCredential>()
+            .map_err(|_| ServerFnError::new("No passkey was used"))?;
+
+        Ok(credential.into())
+    }
+    #[cfg(not(feature = "hydrate"))]
+    {
+        let _ = options;
+        Err(ServerFnError::new("Passkeys need a browser"))
+    }
+}
+
+#[cfg(feature = "hydrate")]
+fn browser_error(error: leptos::wasm_bindgen::JsValue) -> ServerFnError {
+    use leptos::wasm_bindgen::JsCast;
+    use leptos::web_sys::DomException;
+
+    match error.dyn_ref::<DomException>() {
+        // Also what browsers report when the user cancels, without telling so on purpose
+        Some(e) if e.name() == "NotAllowedError" => {
+            ServerFnError::new("The passkey request was cancelled or timed out")
+        }
+        Some(e) => ServerFnError::new(e.message()),
+        None => ServerFnError::new("Passkeys aren't supported by this browser"),
+    }
+}
@@ -0,0 +1,165 @@
+use crate::server::password_reset::reset_password;
+use leptos::{ev, prelude::*, task::spawn_local};
+use leptos_router::{components::A, hooks::use_query_map};
+
+/// Target of the links in password reset emails, sets a new password
+#[component]
+pub fn ResetPasswordPage() -> impl IntoView {
+    let query = use_query_map();
+    let token = move || query.read().get("token").unwrap_or_default();
+
+    let (password, set_password) = signal(String::new());
+    let (confirm_password, set_confirm_password) = signal(String::new());
+    let (error, set_error) = signal(Option::<String>::None);
+    let (done, set_done) = signal(false);
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+
+        if password.get_untracked().len() < 8 {
+            set_error.set(Some("Password must be at least 8 characters".to_string()));
+            return;
+        }
+
+        if password.get_untracked() != confirm_password.get_untracked() {
+            set_error.set(Some("Passwords do not match".to_string()));
+            return;
+        }
+        set_error.set(None);
+
+        let token = query.read_untracked().get("token").unwrap_or_default();
+        spawn_local(async move {
+            match reset_password(token, password.get_untracked()).await {
+                Ok(()) => set_done.set(true),
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+        });
+    };
+
+    view! {
+        <div class="max-w-md mx-auto bg-white/90 dark:bg-primary-800/90 p-8 rounded-xl shadow-lg dark:shadow-primary-900/50 backdrop-blur-sm border border-gray-100 dark:border-primary-700 w-full">
+            <div class="text-center mb-8">
+                <div class="inline-flex p-2 bg-gradient-to-br from-primary-400 to-accent-500 rounded-full mb-4">
+                    <span class="i-mdi-lock-reset text-white h-8 w-8"></span>
+                </div>
+                <h1 class="text-3xl font-bold dark:text-white">"Reset Password"</h1>
+                <p class="text-gray-600 dark:text-gray-300 mt-2">"Choose a new password"</p>
+            </div>
+
+            {move || {
+                error
+                    .get()
+                    .map(|err| {
+                        view! {
+                            <div
+                                class="bg-red-100 dark:bg-red-900/30 border border-red-300 dark:border-red-700 text-red-700 dark:text-red-300 px-4 py-3 rounded-lg mb-6 flex items-center gap-2"
+                                role="alert"
+                            >
+                                <span class="i-mdi-alert-circle text-lg"></span>
+                                <span class="block sm:inline">{err}</span>
+                            </div>
+                        }
+                    })
+            }}
+
+            {move || {
+                if done.get() {
+                    view! {
+                        <div
+                            class="bg-green-100 dark:bg-green-900/30 border border-green-300 dark:border-green-700 text-green-700 dark:text-green-300 px-4 py-3 rounded-lg mb-6 flex items-center gap-2"
+                            role="alert"
+                        >
+                            <span class="i-mdi-check-circle text-lg"></span>
+                            <span class="block sm:inline">
+                                "Your password was changed and you were logged out everywhere. You can now "
+                                <A
+                                    href="/login"
+                                    attr:class="text-primary-600 dark:text-primary-400 hover:underline font-medium"
+                                >
+                                    "login"
+                                </A> " with it."
+                            </span>
+                        </div>
+                    }
+                        .into_any()
+                } else if token().is_empty() {
+                    view! {
+                        <p class="text-center dark:text-gray-300">
+                            "This reset link is incomplete. "
+                            <A
+                                href="/forgot-password"
+                                attr:class="text-primary-600 dark:text-primary-400 hover:underline font-medium"
+                            >
+                                "Request a new one"
+                            </A>
+                        </p>
+                    }
+                        .into_any()
+                } else {
+                    view! {
+                        <form on:submit=on_submit class="space-y-6">
+                            <div>
+                                <label
+                                    for="password"
+                                    class="block text-gray-700 dark:text-gray-200 font-medium mb-2 flex items-center gap-1"
+                                >
+                                    <span class="i-mdi-lock-outline"></span>
+                                    "New Password"
+                                </label>
+                                <div class="relative">
+                                    <span class="absolute left-3 inset-y-0 flex items-center text-gray-400 dark:text-gray-300">
+                                        <span class="i-mdi-lock text-lg"></span>
+                                    </span>
+                                    <input
+                                        type="password"
+                                        id="password"
+                                        class="w-full pl-10 pr-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                                        placeholder="Choose a new password"
+                                        on:input=move |ev| {
+                                            set_password.set(event_target_value(&ev));
+                                        }
+                                        prop:value=password
+                                    />
+                                </div>
+                            </div>
+
+                            <div>
+                                <label
+                                    for="confirm-password"
+                                    class="block text-gray-700 dark:text-gray-200 font-medium mb-2 flex items-center gap-1"
+                                >
+                                    <span class="i-mdi-lock-check-outline"></span>
+                                    "Confirm Password"
+                                </label>
+                                <div class="relative">
+                                    <span class="absolute left-3 inset-y-0 flex items-center text-gray-400 dark:text-gray-300">
+                                        <span class="i-mdi-lock-check text-lg"></span>
+                                    </span>
+                                    <input
+                                        type="password"
+                                        id="confirm-password"
+                                        class="w-full pl-10 pr-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                                        placeholder="Confirm your new password"
+                                        on:input=move |ev| {
+                                            set_confirm_password.set(event_target_value(&ev));
+                                        }
+                                        prop:value=confirm_password
+                                    />
+                                </div>
+                            </div>
+
+                            <button
+                                type="submit"
+                                class="w-full bg-gradient-to-r from-primary-500 to-accent-500 text-white py-3 px-4 rounded-lg hover:from-primary-600 hover:to-accent-600 focus:outline-none focus:ring-2 focus:ring-primary-500 focus:ring-offset-2 dark:focus:ring-offset-primary-800 transition-all duration-200 font-medium flex items-center justify-center gap-2"
+                            >
+                                <span class="i-mdi-lock-reset"></span>
+                                "Reset Password"
+                            </button>
+                        </form>
+                    }
+                        .into_any()
+                }
+            }}
+        </div>
+    }
+}
@@ -1,5 +1,8 @@
+use super::oidc::OidcProviderButtons;
+use super::passkey::create_passkey;
+use crate::app::CurrentUser;
 use crate::models::user::NewUser;
-use crate::server::auth::register;
+use crate::server::auth::{finish_passkey_signup, register, start_passkey_signup};
 use leptos::{ev, prelude::*, task::spawn_local};
 use leptos_router::components::A;
 
@@ -7,6 +10,7 @@ use leptos_router::components::A;
 #[component]
 pub fn SignupPage() -> impl IntoView {
     let (username, set_username) = signal(String::new());
+    let (email, set_email) = signal(String::new());
     let (password, set_password) = signal(String::new());
     let (confirm_password, set_confirm_password) = signal(String::new());
     let (error, set_error) = signal(Option::<String>::None);
@@ -42,6 +46,7 @@ pub fn SignupPage() -> impl IntoView {
         let new_user = NewUser {
             username: username.get_untracked(),
             password: password.get_untracked(),
+            email: Some(email.get_untracked()),
         };
 
         // Use spawn_local instead of create_server_action
@@ -51,6 +56,38 @@ pub fn SignupPage() -> impl IntoView {
         });
     };
 
+    let user_resource = expect_context::<CurrentUser>();
+    let navigate = leptos_router::hooks::use_navigate();
+
+    // No password, the account logs in with the passkey created along with it
+    let on_passkey = move |_| {
+        if username.get_untracked().trim().is_empty() {
+            set_error.set(Some("Username is required".to_string()));
+            return;
+        }
+        set_error.set(None);
+
+        let navigate = navigate.clone();
+        spawn_local(async move {
+            let result = async {
+                let started =
+                    start_passkey_signup(username.get_untracked(), Some(email.get_untracked()))
+                        .await?;
+                let credential = create_passkey(started.options).await?;
+                finish_passkey_signup(started.ceremony, credential).await
+            }
+            .await;
+
+            match result {
+                Ok(_) => {
+                    user_resource.refetch();
+                    navigate("/", Default::default());
+                }
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+        });
+    };
+
     // Handle action response
     Effect::new(move |_| {
         if let Some(result) = register_result.get() {
@@ -59,6 +96,7 @@ pub fn SignupPage() -> impl IntoView {
                     // Show success message
                     set_success.set(true);
                     set_username.set(String::new());
+                    set_email.set(String::new());
                     set_password.set(String::new());
                     set_confirm_password.set(String::new());
                 }
@@ -147,6 +185,34 @@ pub fn SignupPage() -> impl IntoView {
                     </div>
                 </div>
 
+                <div>
+                    <label
+                        for="email"
+                        class="block text-gray-700 dark:text-gray-200 font-medium mb-2 flex items-center gap-1"
+                    >
+                        <span class="i-mdi-email-outline"></span>
+                        "Email"
+                        <span class="text-gray-400 dark:text-gray-400 text-sm font-normal">
+                            "(optional, to reset your password)"
+                        </span>
+                    </label>
+                    <div class="relative">
+                        <span class="absolute left-3 inset-y-0 flex items-center text-gray-400 dark:text-gray-300">
+                            <span class="i-mdi-email text-lg"></span>
+                        </span>
+                        <input
+                            type="email"
+                            id="email"
+                            class="w-full pl-10 pr-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                            placeholder="Enter your email"
+                            on:input=move |ev| {
+                                set_email.set(event_target_value(&ev));
+                            }
+                            prop:value=email
+                        />
+                    </div>
+                </div>
+
                 <div>
                     <label
                         for="password"
@@ -204,6 +270,26 @@ pub fn SignupPage() -> impl IntoView {
                     <span class="i-mdi-account-plus"></span>
                     "Sign Up"
                 </button>
+
+                <div class="flex items-center gap-3 text-sm text-gray-400 dark:text-gray-400">
+                    <span class="flex-grow border-t border-gray-200 dark:border-primary-700"></span>
+                    "or, without a password"
+                    <span class="flex-grow border-t border-gray-200 dark:border-primary-700"></span>
+                </div>
+
+                <button
+                    type="button"
+                    class="w-full bg-white dark:bg-primary-800 border border-primary-300 dark:border-primary-600 text-primary-700 dark:text-primary-200 py-3 px-4 rounded-lg hover:bg-primary-50 dark:hover:bg-primary-700 focus:outline-none focus:ring-2 focus:ring-primary-500 focus:ring-offset-2 dark:focus:ring-offset-primary-800 transition-all duration-200 font-medium flex items-center justify-center gap-2"
+                    on:click=on_passkey
+                >
+                    <span class="i-mdi-fingerprint"></span>
+                    "Sign up with a passkey"
+                </button>
+
+                <OidcProviderButtons
+                    action="Sign up with"
+                    on_error=Callback::new(move |e| set_error.set(Some(e)))
+                />
             </form>
 
             <p class="mt-6 text-center dark:text-gray-300 text-sm">
@@ -0,0 +1,174 @@
+use crate::app::CurrentUser;
+use crate::components::byline::Avatar;
+use crate::components::post_card::PostCard;
+use crate::models::author::{AuthorProfile, ProfileUpdate};
+use crate::server::authors::{get_author, update_profile};
+use leptos::{ev, prelude::*, task::spawn_local};
+use leptos_router::hooks::use_params_map;
+
+/// Public profile of an author, with their published posts
+#[component]
+pub fn AuthorPage() -> impl IntoView {
+    let params = use_params_map();
+    let username_param = move || params.get().get("username").unwrap_or_default();
+
+    let author_resource = Resource::new(username_param, get_author);
+    let user_resource = expect_context::<CurrentUser>();
+
+    view! {
+        <div class="max-w-4xl mx-auto w-full">
+            <Suspense fallback=move || {
+                view! { <p>"Loading..."</p> }
+            }>
+                {move || Suspend::new(async move {
+                    match author_resource.await {
+                        Err(e) => {
+                            view! {
+                                <div class="bg-red-100 dark:bg-red-900/30 border border-red-300 dark:border-red-700 text-red-700 dark:text-red-300 px-4 py-3 rounded-lg mb-6 flex items-center gap-2">
+                                    <span class="i-mdi-alert-circle text-lg"></span>
+                                    <p>"Error loading author: " {e.to_string()}</p>
+                                </div>
+                            }
+                                .into_any()
+                        }
+                        Ok(profile) => {
+                            let is_own_profile = user_resource
+                                .await
+                                .is_some_and(|user| user.username == profile.author.username);
+                            view! {
+                                <AuthorHeader profile=profile.clone() />
+                                {is_own_profile
+                                    .then(|| {
+                                        view! {
+                                            <ProfileForm
+                                                profile=profile.clone()
+                                                on_saved=move || author_resource.refetch()
+                                            />
+                                        }
+                                    })}
+                                {if profile.posts.is_empty() {
+                                    view! {
+                                        <p class="dark:text-gray-300">"No published posts yet."</p>
+                                    }
+                                        .into_any()
+                                } else {
+                                    view! {
+                                        <div class="space-y-8">
+                                            {profile
+                                                .posts
+                                                .into_iter()
+                                                .map(|post| view! { <PostCard post /> })
+                                                .collect_view()}
+                                        </div>
+                                    }
+                                        .into_any()
+                                }}
+                            }
+                                .into_any()
+                        }
+                    }
+                })}
+            </Suspense>
+        </div>
+    }
+}
+
+#[component]
+fn AuthorHeader(profile: AuthorProfile) -> impl IntoView {
+    view! {
+        <section class="flex flex-col sm:flex-row items-center gap-6 mb-8 bg-white/80 dark:bg-primary-800/80 p-6 rounded-xl shadow-lg">
+            <Avatar author=profile.author.clone() class="w-24 h-24 text-4xl shrink-0" />
+            <div class="text-center sm:text-left">
+                <h1 class="text-3xl font-bold dark:text-white">{profile.author.username}</h1>
+                <p class="text-sm text-gray-500 dark:text-gray-400 mb-2">
+                    {format!("Joined {}", profile.joined_at.format("%B %Y"))}
+                </p>
+                <p class="whitespace-pre-line text-gray-700 dark:text-gray-200">{profile.bio}</p>
+            </div>
+        </section>
+    }
+}
+
+/// Edit the bio and avatar of the current user
+#[component]
+fn ProfileForm(profile: AuthorProfile, on_saved: impl Fn() + Send + Sync + 'static) -> impl IntoView {
+    let (bio, set_bio) = signal(profile.bio);
+    let (avatar_url, set_avatar_url) = signal(profile.author.avatar_url.unwrap_or_default());
+    let (error, set_error) = signal(None::<String>);
+    let on_saved = Callback::new(move |()| on_saved());
+
+    view! {
+        <details class="mb-8 bg-white dark:bg-primary-800 p-6 rounded-lg shadow-lg border border-gray-100 dark:border-primary-700">
+            <summary class="cursor-pointer font-medium dark:text-gray-200 flex items-center gap-1">
+                <span class="i-mdi-account-edit"></span>
+                "Edit profile"
+            </summary>
+            <form
+                class="mt-4"
+                on:submit=move |ev: ev::SubmitEvent| {
+                    ev.prevent_default();
+                    let profile = ProfileUpdate {
+                        bio: bio.get_untracked(),
+                        avatar_url: avatar_url.get_untracked(),
+                    };
+                    spawn_local(async move {
+                        match update_profile(profile).await {
+                            Ok(()) => {
+                                set_error.set(None);
+                                on_saved.run(());
+                            }
+                            Err(e) => set_error.set(Some(e.to_string())),
+                        }
+                    });
+                }
+            >
+                {move || {
+                    error
+                        .get()
+                        .map(|err| {
+                            view! {
+                                <p class="text-red-500 dark:text-red-400 mb-4" role="alert">
+                                    {err}
+                                </p>
+                            }
+                        })
+                }}
+                <div class="mb-4">
+                    <label for="bio" class="block text-gray-700 dark:text-gray-200 font-medium mb-2">
+                        "Bio"
+                    </label>
+                    <textarea
+                        id="bio"
+                        class="w-full px-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                        rows="4"
+                        on:input=move |ev| set_bio.set(event_target_value(&ev))
+                        prop:value=bio
+                    ></textarea>
+                </div>
+                <div class="mb-4">
+                    <label
+                        for="avatar_url"
+                        class="block text-gray-700 dark:text-gray-200 font-medium mb-2"
+                    >
+                        "Avatar URL"
+                    </label>
+                    <input
+                        type="url"
+                        id="avatar_url"
+                        class="w-full px-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                        placeholder="https://example.com/me.png"
+                        on:input=move |ev| set_avatar_url.set(event_target_value(&ev))
+                        prop:value=avatar_url
+                    />
+                </div>
+                <button
+                    type="submit"
+                    class="bg-gradient-to-r from-primary-500 to-accent-500 text-white py-2 px-4 rounded-lg hover:from-primary-600 hover:to-accent-600 transition-all duration-200 font-medium flex items-center gap-2 hover:cursor-pointer"
+                >
+                    <span class="i-mdi-content-save"></span>
+                    "Save"
+                </button>
+            </form>
+        </details>
+    }
+}
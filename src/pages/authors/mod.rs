@@ -0,0 +1,3 @@
+mod author_page;
+
+pub use author_page::AuthorPage;
@@ -1,16 +1,34 @@
-use crate::models::post::Post;
+use crate::components::post_card::PostCard;
+use crate::models::post::{PostCursor, PostList, PostSummary};
 use crate::server::blog::get_posts;
 use leptos::prelude::*;
-use leptos_router::components::A;
+use leptos_router::{components::A, hooks::use_query_map};
 
-/// Blog listing page
+/// Number of posts per page of the blog index
+const PAGE_SIZE: u32 = 10;
+
+/// Blog listing page, paginated with the `page` or `before` query parameters
 #[component]
 pub fn BlogPage() -> impl IntoView {
-    // Create resource to fetch posts
-    let posts_resource = OnceResource::new(async move { get_posts(true).await });
+    let query = use_query_map();
+    let page = move || {
+        query
+            .get()
+            .get("page")
+            .and_then(|page| page.parse::<u32>().ok())
+    };
+    let before = move || {
+        query
+            .get()
+            .get("before")
+            .and_then(|cursor| cursor.parse::<PostCursor>().ok())
+    };
 
-    // Format date for display
-    let format_date = |date: chrono::DateTime<chrono::Utc>| date.format("%B %d, %Y").to_string();
+    // Create resource to fetch posts
+    let posts_resource = Resource::new(
+        move || (before(), page()),
+        |(before, page)| get_posts(true, PAGE_SIZE, before, page),
+    );
 
     view! {
         <div class="max-w-4xl mx-auto w-full">
@@ -38,8 +56,8 @@ pub fn BlogPage() -> impl IntoView {
                                         }
                                             .into_any()
                                     }
-                                    Ok(posts) => {
-                                        if posts.is_empty() {
+                                    Ok(list) => {
+                                        if list.posts.is_empty() {
                                             view! {
                                                 <div class="text-center py-12 bg-white/90 dark:bg-primary-800/90 p-8 rounded-xl shadow-lg dark:shadow-primary-900/50 backdrop-blur-sm border border-gray-100 dark:border-primary-700">
                                                     <div class="inline-flex p-3 bg-blue-100 dark:bg-blue-900/30 rounded-full mb-6 text-blue-500 dark:text-blue-400">
@@ -55,45 +73,18 @@ pub fn BlogPage() -> impl IntoView {
                                             }
                                                 .into_any()
                                         } else {
+                                            let posts = list.posts.clone();
                                             view! {
                                                 <div class="space-y-8">
                                                     <For
                                                         each=move || posts.clone()
                                                         key=|post| post.id
-                                                        children=move |post: Post| {
-                                                            view! {
-                                                                <article class="bg-white/80 dark:bg-primary-800/80 backdrop-blur-sm p-6 rounded-xl shadow-lg hover:shadow-xl transition-all duration-300 border-l-4 border-primary-500 dark:border-primary-400 card">
-                                                                    <h2 class="text-2xl font-bold mb-2">
-                                                                        <A
-                                                                            href=format!("/blog/{}", post.id)
-                                                                            attr:class="text-primary-700 dark:text-primary-400 hover:text-primary-500 dark:hover:text-primary-300 transition-colors duration-300"
-                                                                        >
-                                                                            {post.title.clone()}
-                                                                        </A>
-                                                                    </h2>
-                                                                    <div class="text-gray-500 dark:text-gray-300 mb-4 flex items-center text-sm">
-                                                                        <span class="inline-block w-2 h-2 rounded-full bg-accent-500 mr-2"></span>
-                                                                        {format_date(post.created_at)}
-                                                                    </div>
-                                                                    <div class="prose dark:prose-invert text-gray-700 dark:text-gray-200">
-                                                                        // Display a preview of the content
-                                                                        {post.content.chars().take(200).collect::<String>()}
-                                                                        {if post.content.len() > 200 { "..." } else { "" }}
-                                                                    </div>
-                                                                    <div class="mt-4">
-                                                                        <A
-                                                                            href=format!("/blog/{}", post.id)
-                                                                            attr:class="inline-flex items-center px-4 py-2 bg-gradient-to-r from-primary-500 to-accent-500 text-white rounded-lg hover:from-primary-600 hover:to-accent-600 transition-all duration-300 text-sm font-medium gap-1"
-                                                                        >
-                                                                            "Read more"
-                                                                            <span class="i-mdi-arrow-right"></span>
-                                                                        </A>
-                                                                    </div>
-                                                                </article>
-                                                            }
+                                                        children=move |post: PostSummary| {
+                                                            view! { <PostCard post /> }
                                                         }
                                                     />
                                                 </div>
+                                                <Pagination list page=page() />
                                             }
                                                 .into_any()
                                         }
@@ -106,3 +97,57 @@ pub fn BlogPage() -> impl IntoView {
         </div>
     }
 }
+
+/// Links to the newer and older pages of the blog index
+#[component]
+fn Pagination(list: PostList, page: Option<u32>) -> impl IntoView {
+    // Numbered pages stay numbered, cursor pages link to cursors
+    let (previous_href, next_href) = match page {
+        Some(page) => (
+            list.has_previous.then(|| match page - 1 {
+                1 => "/blog".to_string(),
+                previous => format!("/blog?page={previous}"),
+            }),
+            list.next_cursor
+                .map(|_| format!("/blog?page={}", page + 1)),
+        ),
+        None => (
+            list.has_previous.then(|| match list.previous_cursor {
+                Some(cursor) => format!("/blog?before={cursor}"),
+                None => "/blog".to_string(),
+            }),
+            list.next_cursor
+                .map(|cursor| format!("/blog?before={cursor}")),
+        ),
+    };
+
+    view! {
+        <nav class="mt-8 flex justify-between" aria-label="Pagination">
+            {previous_href
+                .map(|href| {
+                    view! {
+                        <A
+                            href=href
+                            attr:class="inline-flex items-center gap-1 px-4 py-2 rounded-lg bg-white/80 dark:bg-primary-800/80 shadow hover:shadow-lg text-primary-700 dark:text-primary-300 transition-all duration-300"
+                        >
+                            <span class="i-mdi-arrow-left"></span>
+                            "Newer posts"
+                        </A>
+                    }
+                })}
+            <span></span>
+            {next_href
+                .map(|href| {
+                    view! {
+                        <A
+                            href=href
+                            attr:class="inline-flex items-center gap-1 px-4 py-2 rounded-lg bg-white/80 dark:bg-primary-800/80 shadow hover:shadow-lg text-primary-700 dark:text-primary-300 transition-all duration-300"
+                        >
+                            "Older posts"
+                            <span class="i-mdi-arrow-right"></span>
+                        </A>
+                    }
+                })}
+        </nav>
+    }
+}
@@ -1,5 +1,8 @@
 use crate::app::CurrentUser;
-use crate::models::post::UpdatePostData;
+use crate::components::schedule_input::{format_schedule, parse_schedule, ScheduleInput};
+use crate::models::permission::Permission;
+use crate::models::post::{post_slug, UpdatePostData};
+use crate::models::tag::parse_tag_list;
 use crate::server::blog::{get_post, update_post};
 use leptos::{ev, prelude::*, task::spawn_local};
 use leptos_router::{components::A, hooks::use_params_map};
@@ -8,29 +11,44 @@ use leptos_router::{components::A, hooks::use_params_map};
 #[component]
 pub fn EditPostPage() -> impl IntoView {
     let params = use_params_map();
-    let post_id = move || {
-        params
-            .get()
-            .get("id")
-            .and_then(|id| id.parse::<i64>().ok())
-            .unwrap_or(0)
-    };
+    let slug_param = move || params.get().get("slug").unwrap_or_default();
 
     let user_resource = expect_context::<CurrentUser>();
-    let post_resource = Resource::new(post_id, async move |post_id| get_post(post_id).await);
+    let post_resource = Resource::new(slug_param, get_post);
 
     // Form state
+    let (post_id, set_post_id) = signal(0);
     let (title, set_title) = signal(String::new());
+    let (slug, set_slug) = signal(String::new());
+    let (tags, set_tags) = signal(String::new());
     let (content, set_content) = signal(String::new());
+    let (excerpt, set_excerpt) = signal(String::new());
     let (published, set_published) = signal(false);
+    let (publish_at, set_publish_at) = signal(String::new());
     let (error, set_error) = signal(Option::<String>::None);
 
     // Initialize form with post data when loaded
     Effect::new(move |_| {
         if let Some(Ok(post)) = post_resource.get() {
+            set_post_id.set(post.id);
+            set_published.set(post.is_visible());
+            set_publish_at.set(
+                post.publish_at
+                    .filter(|_| post.is_scheduled())
+                    .map(format_schedule)
+                    .unwrap_or_default(),
+            );
             set_title.set(post.title);
+            set_slug.set(post.slug);
+            set_tags.set(
+                post.tags
+                    .into_iter()
+                    .map(|tag| tag.name)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            );
             set_content.set(post.content);
-            set_published.set(post.published);
+            set_excerpt.set(post.excerpt.unwrap_or_default());
         }
     });
 
@@ -46,7 +64,9 @@ pub fn EditPostPage() -> impl IntoView {
                     let user = user_resource.get().flatten();
                     let navigate = navigate.clone();
                     match user {
-                        Some(user) if user.is_admin => {
+                        Some(user)
+                            if user.has_permission(Permission::EditOwnPosts)
+                                || user.has_permission(Permission::EditAllPosts) => {
                             view! {
                                 <Suspense fallback=|| {
                                     view! { <div class="dark:text-gray-300">"Loading..."</div> }
@@ -67,6 +87,15 @@ pub fn EditPostPage() -> impl IntoView {
                                                 }
                                                     .into_any()
                                             }
+                                            Some(Ok(post)) if !user.can_edit_post(post.author_id) => {
+                                                view! {
+                                                    <div class="bg-red-100 dark:bg-red-900/30 border border-red-300 dark:border-red-700 text-red-700 dark:text-red-300 px-4 py-3 rounded-lg mb-6 flex items-center gap-2">
+                                                        <span class="i-mdi-shield-alert text-lg"></span>
+                                                        <p>"You may only edit your own posts."</p>
+                                                    </div>
+                                                }
+                                                    .into_any()
+                                            }
                                             Some(Ok(_)) => {
                                                 view! {
                                                     <div>
@@ -102,17 +131,21 @@ pub fn EditPostPage() -> impl IntoView {
                                                                     return;
                                                                 }
                                                                 let update_data = UpdatePostData {
-                                                                    id: post_id(),
+                                                                    id: post_id.get(),
                                                                     title: title.get(),
+                                                                    slug: slug.get(),
+                                                                    tags: parse_tag_list(&tags.get()),
                                                                     content: content.get(),
                                                                     published: published.get(),
+                                                                    publish_at: parse_schedule(&publish_at.get()),
+                                                                    excerpt: Some(excerpt.get()),
                                                                 };
                                                                 let navigate = navigate.clone();
                                                                 spawn_local(async move {
                                                                     let result = update_post(update_data).await;
                                                                     match result {
                                                                         Ok(post) => {
-                                                                            navigate(&format!("/blog/{}", post.id), Default::default());
+                                                                            navigate(&format!("/blog/{}", post.slug), Default::default());
                                                                         }
                                                                         Err(e) => {
                                                                             set_error.set(Some(e.to_string()));
@@ -142,6 +175,49 @@ pub fn EditPostPage() -> impl IntoView {
                                                                 />
                                                             </div>
 
+                                                            <div class="mb-4">
+                                                                <label
+                                                                    for="slug"
+                                                                    class="block text-gray-700 dark:text-gray-200 font-medium mb-2 flex items-center gap-1"
+                                                                >
+                                                                    <span class="i-mdi-link-variant"></span>
+                                                                    "Slug"
+                                                                </label>
+                                                                <input
+                                                                    type="text"
+                                                                    id="slug"
+                                                                    class="w-full px-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                                                                    placeholder=move || post_slug(&title.get())
+                                                                    on:input=move |ev| {
+                                                                        set_slug.set(event_target_value(&ev));
+                                                                    }
+                                                                    prop:value=slug
+                                                                />
+                                                                <p class="mt-1 text-sm text-gray-500 dark:text-gray-400">
+                                                                    "Changing the slug keeps the previous URL as a redirect."
+                                                                </p>
+                                                            </div>
+
+                                                            <div class="mb-4">
+                                                                <label
+                                                                    for="tags"
+                                                                    class="block text-gray-700 dark:text-gray-200 font-medium mb-2 flex items-center gap-1"
+                                                                >
+                                                                    <span class="i-mdi-tag-multiple-outline"></span>
+                                                                    "Tags"
+                                                                </label>
+                                                                <input
+                                                                    type="text"
+                                                                    id="tags"
+                                                                    class="w-full px-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                                                                    placeholder="rust, leptos, web"
+                                                                    on:input=move |ev| {
+                                                                        set_tags.set(event_target_value(&ev));
+                                                                    }
+                                                                    prop:value=tags
+                                                                />
+                                                            </div>
+
                                                             <div class="mb-4">
                                                                 <label
                                                                     for="content"
@@ -162,7 +238,27 @@ pub fn EditPostPage() -> impl IntoView {
                                                                 ></textarea>
                                                             </div>
 
-                                                            <div class="mb-6">
+                                                            <div class="mb-4">
+                                                                <label
+                                                                    for="excerpt"
+                                                                    class="block text-gray-700 dark:text-gray-200 font-medium mb-2 flex items-center gap-1"
+                                                                >
+                                                                    <span class="i-mdi-text-short"></span>
+                                                                    "Excerpt"
+                                                                </label>
+                                                                <textarea
+                                                                    id="excerpt"
+                                                                    class="w-full px-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                                                                    rows="3"
+                                                                    placeholder="Defaults to the first paragraph of the content"
+                                                                    on:input=move |ev| {
+                                                                        set_excerpt.set(event_target_value(&ev));
+                                                                    }
+                                                                    prop:value=excerpt
+                                                                ></textarea>
+                                                            </div>
+
+                                                            <div class="mb-4">
                                                                 <label class="inline-flex items-center">
                                                                     <input
                                                                         type="checkbox"
@@ -179,6 +275,12 @@ pub fn EditPostPage() -> impl IntoView {
                                                                 </label>
                                                             </div>
 
+                                                            <ScheduleInput
+                                                                published=published
+                                                                value=publish_at
+                                                                set_value=set_publish_at
+                                                            />
+
                                                             <div class="flex justify-between">
                                                                 <button
                                                                     type="submit"
@@ -189,7 +291,7 @@ pub fn EditPostPage() -> impl IntoView {
                                                                 </button>
 
                                                                 <A
-                                                                    href=format!("/blog/{}", post_id())
+                                                                    href=format!("/blog/{}", slug_param())
                                                                     attr:class="py-2 px-4 border border-gray-300 dark:border-primary-600 rounded-lg hover:bg-gray-100 dark:hover:bg-primary-700 text-gray-700 dark:text-gray-200 transition-colors flex items-center gap-2"
                                                                 >
                                                                     <span class="i-mdi-close-circle"></span>
@@ -217,7 +319,7 @@ pub fn EditPostPage() -> impl IntoView {
                                         "Access Denied"
                                     </h1>
                                     <p class="mb-6 dark:text-gray-300">
-                                        "You must be an admin to edit posts."
+                                        "You must be an admin or an editor to edit posts."
                                     </p>
                                     <A
                                         href="/login"
@@ -1,9 +1,11 @@
 mod blog_page;
 mod edit_post;
 mod new_post;
+mod post_history;
 mod post_page;
 
 pub use blog_page::BlogPage;
 pub use edit_post::EditPostPage;
 pub use new_post::NewPostPage;
+pub use post_history::PostHistoryPage;
 pub use post_page::PostPage;
\ No newline at end of file
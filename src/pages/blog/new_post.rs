@@ -1,5 +1,8 @@
 use crate::app::CurrentUser;
-use crate::models::post::NewPost;
+use crate::components::schedule_input::{parse_schedule, ScheduleInput};
+use crate::models::permission::Permission;
+use crate::models::post::{post_slug, NewPost};
+use crate::models::tag::parse_tag_list;
 use crate::server::blog::create_post;
 use leptos::{ev, prelude::*, task::spawn_local};
 use leptos_router::components::A;
@@ -11,8 +14,12 @@ pub fn NewPostPage() -> impl IntoView {
     let user_resource = expect_context::<CurrentUser>();
 
     let (title, set_title) = signal(String::new());
+    let (slug, set_slug) = signal(String::new());
+    let (tags, set_tags) = signal(String::new());
     let (content, set_content) = signal(String::new());
+    let (excerpt, set_excerpt) = signal(String::new());
     let (published, set_published) = signal(false);
+    let (publish_at, set_publish_at) = signal(String::new());
     let (error, set_error) = signal(Option::<String>::None);
 
     // Create result storage
@@ -28,7 +35,7 @@ pub fn NewPostPage() -> impl IntoView {
                     let navigate = navigate.clone();
                     let user = user_resource.get().flatten();
                     match user {
-                        Some(user) if user.is_admin => {
+                        Some(user) if user.has_permission(Permission::CreatePosts) => {
                             view! {
                                 <div>
                                     <h1 class="text-3xl font-bold mb-6 dark:text-white flex items-center gap-2">
@@ -64,15 +71,19 @@ pub fn NewPostPage() -> impl IntoView {
                                             }
                                             let new_post = NewPost {
                                                 title: title.get_untracked(),
+                                                slug: slug.get_untracked(),
+                                                tags: parse_tag_list(&tags.get_untracked()),
                                                 content: content.get_untracked(),
                                                 published: published.get_untracked(),
+                                                publish_at: parse_schedule(&publish_at.get_untracked()),
+                                                excerpt: Some(excerpt.get_untracked()),
                                             };
                                             let navigate = navigate.clone();
                                             spawn_local(async move {
                                                 let result = create_post(new_post).await;
                                                 match result {
                                                     Ok(post) => {
-                                                        navigate(&format!("/blog/{}", post.id), Default::default());
+                                                        navigate(&format!("/blog/{}", post.slug), Default::default());
                                                     }
                                                     Err(e) => {
                                                         set_error.set(Some(e.to_string()));
@@ -102,6 +113,46 @@ pub fn NewPostPage() -> impl IntoView {
                                             />
                                         </div>
 
+                                        <div class="mb-4">
+                                            <label
+                                                for="slug"
+                                                class="block text-gray-700 dark:text-gray-200 font-medium mb-2 flex items-center gap-1"
+                                            >
+                                                <span class="i-mdi-link-variant"></span>
+                                                "Slug"
+                                            </label>
+                                            <input
+                                                type="text"
+                                                id="slug"
+                                                class="w-full px-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                                                placeholder=move || post_slug(&title.get())
+                                                on:input=move |ev| {
+                                                    set_slug.set(event_target_value(&ev));
+                                                }
+                                                prop:value=slug
+                                            />
+                                        </div>
+
+                                        <div class="mb-4">
+                                            <label
+                                                for="tags"
+                                                class="block text-gray-700 dark:text-gray-200 font-medium mb-2 flex items-center gap-1"
+                                            >
+                                                <span class="i-mdi-tag-multiple-outline"></span>
+                                                "Tags"
+                                            </label>
+                                            <input
+                                                type="text"
+                                                id="tags"
+                                                class="w-full px-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                                                placeholder="rust, leptos, web"
+                                                on:input=move |ev| {
+                                                    set_tags.set(event_target_value(&ev));
+                                                }
+                                                prop:value=tags
+                                            />
+                                        </div>
+
                                         <div class="mb-4">
                                             <label
                                                 for="content"
@@ -122,7 +173,27 @@ pub fn NewPostPage() -> impl IntoView {
                                             ></textarea>
                                         </div>
 
-                                        <div class="mb-6">
+                                        <div class="mb-4">
+                                            <label
+                                                for="excerpt"
+                                                class="block text-gray-700 dark:text-gray-200 font-medium mb-2 flex items-center gap-1"
+                                            >
+                                                <span class="i-mdi-text-short"></span>
+                                                "Excerpt"
+                                            </label>
+                                            <textarea
+                                                id="excerpt"
+                                                class="w-full px-3 py-2 border border-gray-300 dark:border-primary-600 dark:bg-primary-700/50 dark:text-white rounded-lg focus:outline-none focus:ring-2 focus:ring-primary-500 focus:border-transparent transition-all duration-200"
+                                                rows="3"
+                                                placeholder="Defaults to the first paragraph of the content"
+                                                on:input=move |ev| {
+                                                    set_excerpt.set(event_target_value(&ev));
+                                                }
+                                                prop:value=excerpt
+                                            ></textarea>
+                                        </div>
+
+                                        <div class="mb-4">
                                             <label class="inline-flex items-center">
                                                 <input
                                                     type="checkbox"
@@ -139,6 +210,12 @@ pub fn NewPostPage() -> impl IntoView {
                                             </label>
                                         </div>
 
+                                        <ScheduleInput
+                                            published=published
+                                            value=publish_at
+                                            set_value=set_publish_at
+                                        />
+
                                         <div class="flex justify-between">
                                             <button
                                                 type="submit"
@@ -171,7 +248,7 @@ pub fn NewPostPage() -> impl IntoView {
                                         "Access Denied"
                                     </h1>
                                     <p class="mb-6 dark:text-gray-300">
-                                        "You must be an admin to create posts."
+                                        "You must be an admin or an editor to create posts."
                                     </p>
                                     <A
                                         href="/login"
@@ -0,0 +1,312 @@
+use crate::app::CurrentUser;
+use crate::models::permission::Permission;
+use crate::models::revision::{DiffTag, PostHistory, Revision, RevisionDiff};
+use crate::server::revisions::{get_post_history, get_revision_diff, restore_revision};
+use leptos::{prelude::*, task::spawn_local};
+use leptos_router::{
+    components::{Form, A},
+    hooks::{use_params_map, use_query_map},
+};
+
+/// Revisions to compare: the ones from the query, or the two latest ones
+fn compared_revisions(history: &PostHistory, from: Option<i64>, to: Option<i64>) -> (i64, i64) {
+    let latest = history.revisions.first().map_or(0, |revision| revision.id);
+    let previous = history
+        .revisions
+        .get(1)
+        .map_or(latest, |revision| revision.id);
+
+    (from.unwrap_or(previous), to.unwrap_or(latest))
+}
+
+/// History of a post, with a diff between two of its revisions
+#[component]
+pub fn PostHistoryPage() -> impl IntoView {
+    let params = use_params_map();
+    let slug_param = move || params.get().get("slug").unwrap_or_default();
+    let query = use_query_map();
+    let from = move || {
+        query
+            .get()
+            .get("from")
+            .and_then(|id| id.parse::<i64>().ok())
+    };
+    let to = move || query.get().get("to").and_then(|id| id.parse::<i64>().ok());
+
+    let user_resource = expect_context::<CurrentUser>();
+    let history_resource = Resource::new(slug_param, get_post_history);
+
+    let (restore_error, set_restore_error) = signal(None::<String>);
+    let navigate = leptos_router::hooks::use_navigate();
+
+    let handle_restore = Callback::new(move |id: i64| {
+        let navigate = navigate.clone();
+        spawn_local(async move {
+            match restore_revision(id).await {
+                Ok(post) => {
+                    set_restore_error.set(None);
+                    history_resource.refetch();
+                    navigate(&format!("/blog/{}/history", post.slug), Default::default());
+                }
+                Err(e) => set_restore_error.set(Some(e.to_string())),
+            }
+        });
+    });
+
+    view! {
+        <div class="max-w-4xl mx-auto w-full">
+            <Suspense fallback=|| {
+                view! { <div class="dark:text-gray-300">"Loading..."</div> }
+            }>
+                {move || {
+                    let user = user_resource.get().flatten();
+                    if user
+                        .is_none_or(|user| {
+                            !user.has_permission(Permission::EditOwnPosts)
+                                && !user.has_permission(Permission::EditAllPosts)
+                        })
+                    {
+                        return view! {
+                            <p class="text-red-500 dark:text-red-400">
+                                "You may not see the history of posts."
+                            </p>
+                        }
+                            .into_any();
+                    }
+
+                    match history_resource.get() {
+                        None => view! { <p class="dark:text-gray-300">"Loading..."</p> }.into_any(),
+                        Some(Err(e)) => {
+                            view! {
+                                <p class="text-red-500 dark:text-red-400">
+                                    "Error loading history: " {e.to_string()}
+                                </p>
+                            }
+                                .into_any()
+                        }
+                        Some(Ok(history)) => {
+                            let (compared_from, compared_to) = compared_revisions(
+                                &history,
+                                from(),
+                                to(),
+                            );
+                            let latest = history.revisions.first().map(|revision| revision.id);
+                            let slug = history.post.slug.clone();
+
+                            view! {
+                                <h1 class="text-3xl font-bold mb-2 dark:text-white flex items-center gap-2">
+                                    <span class="i-mdi-history text-primary-500 dark:text-primary-400"></span>
+                                    "History"
+                                </h1>
+                                <p class="mb-6">
+                                    <A
+                                        href=format!("/blog/{slug}")
+                                        attr:class="text-primary-600 dark:text-primary-400 hover:underline"
+                                    >
+                                        {history.post.title.clone()}
+                                    </A>
+                                </p>
+
+                                {move || {
+                                    restore_error
+                                        .get()
+                                        .map(|err| {
+                                            view! {
+                                                <div
+                                                    class="bg-red-100 dark:bg-red-900/30 border border-red-300 dark:border-red-700 text-red-700 dark:text-red-300 px-4 py-3 rounded-lg mb-6 flex items-center gap-2"
+                                                    role="alert"
+                                                >
+                                                    <span class="i-mdi-alert-circle text-lg"></span>
+                                                    <span>{err}</span>
+                                                </div>
+                                            }
+                                        })
+                                }}
+
+                                <Form method="get" action=format!("/blog/{slug}/history")>
+                                    <table class="w-full mb-4 text-sm bg-white dark:bg-primary-800 rounded-lg shadow dark:text-gray-200">
+                                        <thead>
+                                            <tr class="text-left border-b border-gray-200 dark:border-primary-700">
+                                                <th class="p-2">"From"</th>
+                                                <th class="p-2">"To"</th>
+                                                <th class="p-2">"Date"</th>
+                                                <th class="p-2">"Author"</th>
+                                                <th class="p-2">"Title"</th>
+                                                <th class="p-2"></th>
+                                            </tr>
+                                        </thead>
+                                        <tbody>
+                                            {history
+                                                .revisions
+                                                .into_iter()
+                                                .map(|revision| {
+                                                    view! {
+                                                        <RevisionRow
+                                                            revision=revision.clone()
+                                                            is_from=revision.id == compared_from
+                                                            is_to=revision.id == compared_to
+                                                            is_latest=Some(revision.id) == latest
+                                                            on_restore=handle_restore
+                                                        />
+                                                    }
+                                                })
+                                                .collect_view()}
+                                        </tbody>
+                                    </table>
+                                    <button
+                                        type="submit"
+                                        class="mb-8 bg-gradient-to-r from-primary-500 to-accent-500 text-white py-2 px-4 rounded-lg hover:from-primary-600 hover:to-accent-600 transition-all duration-200 font-medium flex items-center gap-2 hover:cursor-pointer"
+                                    >
+                                        <span class="i-mdi-compare-horizontal"></span>
+                                        "Compare"
+                                    </button>
+                                </Form>
+
+                                <DiffView from=compared_from to=compared_to />
+                            }
+                                .into_any()
+                        }
+                    }
+                }}
+            </Suspense>
+        </div>
+    }
+}
+
+#[component]
+fn RevisionRow(
+    revision: Revision,
+    is_from: bool,
+    is_to: bool,
+    is_latest: bool,
+    on_restore: Callback<i64>,
+) -> impl IntoView {
+    let id = revision.id;
+
+    view! {
+        <tr class="border-b border-gray-100 dark:border-primary-700 last:border-0">
+            <td class="p-2">
+                <input type="radio" name="from" value=id.to_string() checked=is_from />
+            </td>
+            <td class="p-2">
+                <input type="radio" name="to" value=id.to_string() checked=is_to />
+            </td>
+            <td class="p-2 whitespace-nowrap">
+                {revision.created_at.format("%B %d, %Y %H:%M").to_string()}
+            </td>
+            <td class="p-2">{revision.author.unwrap_or_else(|| "Unknown".to_string())}</td>
+            <td class="p-2">
+                {revision.title}
+                {(!revision.published)
+                    .then(|| {
+                        view! {
+                            <span class="ml-2 text-xs font-medium px-2 py-0.5 rounded-full bg-yellow-100 dark:bg-yellow-900/40 text-yellow-800 dark:text-yellow-200">
+                                "Draft"
+                            </span>
+                        }
+                    })}
+            </td>
+            <td class="p-2 text-right">
+                {if is_latest {
+                    view! { <span class="text-gray-500 dark:text-gray-400">"Current"</span> }
+                        .into_any()
+                } else {
+                    view! {
+                        <button
+                            type="button"
+                            class="text-primary-600 dark:text-primary-400 hover:underline hover:cursor-pointer"
+                            on:click=move |_| on_restore.run(id)
+                        >
+                            "Restore"
+                        </button>
+                    }
+                        .into_any()
+                }}
+            </td>
+        </tr>
+    }
+}
+
+/// Line-level diff between the contents of two revisions
+#[component]
+fn DiffView(from: i64, to: i64) -> impl IntoView {
+    let diff_resource = Resource::new(move || (from, to), |(from, to)| get_revision_diff(from, to));
+
+    view! {
+        <Suspense fallback=|| {
+            view! { <p class="dark:text-gray-300">"Loading diff..."</p> }
+        }>
+            {move || match diff_resource.get() {
+                None => ().into_any(),
+                Some(Err(e)) => {
+                    view! {
+                        <p class="text-red-500 dark:text-red-400">
+                            "Error loading diff: " {e.to_string()}
+                        </p>
+                    }
+                        .into_any()
+                }
+                Some(Ok(diff)) => view! { <DiffHunks diff /> }.into_any(),
+            }}
+        </Suspense>
+    }
+}
+
+#[component]
+fn DiffHunks(diff: RevisionDiff) -> impl IntoView {
+    let title_change = (diff.from.title != diff.to.title).then(|| {
+        view! {
+            <p class="mb-4 dark:text-gray-200">
+                "Title: " <del class="diff-delete">{diff.from.title.clone()}</del> " → "
+                <ins class="diff-insert">{diff.to.title.clone()}</ins>
+            </p>
+        }
+    });
+
+    if diff.hunks.is_empty() {
+        return view! {
+            {title_change}
+            <p class="text-gray-500 dark:text-gray-400">"The contents are identical."</p>
+        }
+        .into_any();
+    }
+
+    view! {
+        {title_change}
+        <div class="revision-diff overflow-x-auto rounded-lg border border-gray-200 dark:border-primary-700 bg-white dark:bg-primary-900">
+            {diff
+                .hunks
+                .into_iter()
+                .map(|hunk| {
+                    view! {
+                        <table class="w-full font-mono text-sm border-b border-gray-200 dark:border-primary-700 last:border-0">
+                            <tbody>
+                                {hunk
+                                    .into_iter()
+                                    .map(|line| {
+                                        let (class, marker) = match line.tag {
+                                            DiffTag::Equal => ("", " "),
+                                            DiffTag::Delete => ("diff-delete", "-"),
+                                            DiffTag::Insert => ("diff-insert", "+"),
+                                        };
+                                        view! {
+                                            <tr class=class>
+                                                <td class="diff-number">{line.old_number}</td>
+                                                <td class="diff-number">{line.new_number}</td>
+                                                <td class="px-2 select-none">{marker}</td>
+                                                <td class="pr-2 whitespace-pre dark:text-gray-200">
+                                                    {line.text}
+                                                </td>
+                                            </tr>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </tbody>
+                        </table>
+                    }
+                })
+                .collect_view()}
+        </div>
+    }
+        .into_any()
+}
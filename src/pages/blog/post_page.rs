@@ -1,22 +1,53 @@
 use crate::app::CurrentUser;
-use crate::server::blog::{delete_post, get_post};
-use leptos::{prelude::*, task::spawn_local};
+use crate::components::byline::Byline;
+use crate::components::reading_time::ReadingTime;
+use crate::components::redirect::PermanentRedirect;
+use crate::components::tag_list::TagList;
+use crate::server::blog::{delete_post, render_post};
+use leptos::{ev, prelude::*, task::spawn_local, wasm_bindgen::JsCast, web_sys};
+use leptos_meta::Meta;
 use leptos_router::{components::A, hooks::use_params_map};
+use leptos_use::{use_clipboard, UseClipboardReturn};
+use std::time::Duration;
+
+/// Copy the code of a highlighted block when its copy button is clicked.
+///
+/// Code blocks are server-rendered HTML, so the click is handled by delegation on the content.
+fn copy_code_on_click(ev: ev::MouseEvent, copy: impl Fn(&str)) {
+    let Some(button) = ev
+        .target()
+        .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+        .and_then(|target| target.closest(".copy-code").ok().flatten())
+    else {
+        return;
+    };
+
+    let Some(code) = button
+        .closest(".code-block")
+        .ok()
+        .flatten()
+        .and_then(|block| block.query_selector("code").ok().flatten())
+        .and_then(|code| code.dyn_into::<web_sys::HtmlElement>().ok())
+    else {
+        return;
+    };
+
+    copy(&code.inner_text());
+    button.set_text_content(Some("Copied!"));
+    set_timeout(
+        move || button.set_text_content(Some("Copy")),
+        Duration::from_secs(2),
+    );
+}
 
 /// Single post page
 #[component]
 pub fn PostPage() -> impl IntoView {
     let params = use_params_map();
-    let post_id = move || {
-        params
-            .get()
-            .get("id")
-            .and_then(|id| id.parse::<i64>().ok())
-            .unwrap_or(0)
-    };
+    let slug_param = move || params.get().get("slug").unwrap_or_default();
 
     // Create resource to fetch post
-    let post_resource = Resource::new(post_id, get_post);
+    let post_resource = Resource::new(slug_param, render_post);
 
     // Create resource to fetch current user
     let user_resource = expect_context::<CurrentUser>();
@@ -40,6 +71,8 @@ pub fn PostPage() -> impl IntoView {
         }
     });
 
+    let UseClipboardReturn { copy, .. } = use_clipboard();
+
     // Format date for display
     let format_date = |date: chrono::DateTime<chrono::Utc>| date.format("%B %d, %Y").to_string();
 
@@ -56,25 +89,43 @@ pub fn PostPage() -> impl IntoView {
                         }
                             .into_any()
                     }
-                    Some(Ok(post)) => {
+                    // Old slugs and numeric ids redirect to the current slug
+                    Some(Ok(rendered)) if rendered.post.slug != slug_param() => {
+                        view! {
+                            <PermanentRedirect path=format!("/blog/{}", rendered.post.slug) />
+                        }
+                            .into_any()
+                    }
+                    Some(Ok(rendered)) => {
+                        let post = rendered.post;
+                        let copy = copy.clone();
                         view! {
                             <article class="bg-white dark:bg-primary-800 p-8 rounded-xl shadow-lg dark:shadow-primary-900/50 border border-gray-100 dark:border-primary-700">
                                 <h1 class="text-3xl font-bold mb-2 dark:text-white">
                                     {post.title.clone()}
                                 </h1>
-                                <div class="text-gray-500 dark:text-gray-300 mb-6">
-                                    {format_date(post.created_at)}
+                                <Meta name="description" content=post.summary().to_string() />
+                                <div class="text-gray-500 dark:text-gray-300 mb-6 flex flex-wrap items-center gap-x-4">
+                                    <span>{format_date(post.created_at)}</span>
+                                    <Byline author=post.author.clone() />
+                                    <ReadingTime
+                                        word_count=post.word_count
+                                        reading_time=post.reading_time
+                                    />
+                                </div>
+                                <div class="mb-6">
+                                    <TagList tags=post.tags.clone() />
                                 </div>
 
                                 // Show admin actions if user is admin
                                 <Await future=user_resource.into_future() let:_server_user>
                                     {move || {
                                         let user = user_resource.get().flatten();
-                                        if user.is_some_and(|user| user.is_admin) {
+                                        if user.is_some_and(|user| user.can_edit_post(post.author_id)) {
                                             view! {
                                                 <div class="flex gap-4 mb-6">
                                                     <A
-                                                        href=format!("/blog/{}/edit", post.id)
+                                                        href=format!("/blog/{}/edit", post.slug)
                                                         attr:class="px-4 py-2 bg-blue-500 dark:bg-blue-600 text-white rounded-lg hover:bg-blue-600 dark:hover:bg-blue-700 transition-colors"
                                                     >
                                                         <span class="flex items-center gap-2">
@@ -82,6 +133,15 @@ pub fn PostPage() -> impl IntoView {
                                                             "Edit"
                                                         </span>
                                                     </A>
+                                                    <A
+                                                        href=format!("/blog/{}/history", post.slug)
+                                                        attr:class="px-4 py-2 bg-gray-500 dark:bg-gray-600 text-white rounded-lg hover:bg-gray-600 dark:hover:bg-gray-700 transition-colors"
+                                                    >
+                                                        <span class="flex items-center gap-2">
+                                                            <span class="i-mdi-history"></span>
+                                                            "History"
+                                                        </span>
+                                                    </A>
                                                     <button
                                                         class="px-4 py-2 bg-red-500 dark:bg-red-600 text-white rounded-lg hover:bg-red-600 dark:hover:bg-red-700 transition-colors flex items-center gap-2"
                                                         on:click=move |_| {
@@ -100,18 +160,12 @@ pub fn PostPage() -> impl IntoView {
                                     }}
                                 </Await>
 
-                                <div class="prose dark:prose-invert max-w-none">
-                                    // Display content - in a real app, you might want to render markdown
-                                    {post
-                                        .content
-                                        .split("\n")
-                                        .map(|p| {
-                                            view! {
-                                                <p class="mb-4 dark:text-gray-200">{p.to_string()}</p>
-                                            }
-                                        })
-                                        .collect::<Vec<_>>()}
-                                </div>
+                                // Content is rendered and sanitized on the server
+                                <div
+                                    class="prose dark:prose-invert max-w-none"
+                                    inner_html=rendered.html
+                                    on:click=move |ev| copy_code_on_click(ev, &copy)
+                                ></div>
                             </article>
 
                             <div class="mt-8">
@@ -1,8 +1,18 @@
+pub mod account;
+pub mod admin;
 pub mod auth;
+pub mod authors;
 pub mod blog;
 pub mod home;
+pub mod search;
+pub mod tags;
 
 // Re-export all page components for easier imports
+pub use account::*;
+pub use admin::*;
 pub use auth::*;
+pub use authors::*;
 pub use blog::*;
-pub use home::*;
\ No newline at end of file
+pub use home::*;
+pub use search::*;
+pub use tags::*;
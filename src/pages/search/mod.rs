@@ -0,0 +1,3 @@
+mod search_page;
+
+pub use search_page::SearchPage;
@@ -0,0 +1,150 @@
+use crate::components::search_box::SearchBox;
+use crate::components::tag_list::TagList;
+use crate::models::search::SearchResult;
+use crate::server::search::search_posts;
+use leptos::prelude::*;
+use leptos_router::{components::A, hooks::use_query_map, location::Url};
+
+/// Search results page, driven by the `q` and `page` query parameters
+#[component]
+pub fn SearchPage() -> impl IntoView {
+    let query = use_query_map();
+    let search_query = move || query.get().get("q").unwrap_or_default();
+    let page = move || {
+        query
+            .get()
+            .get("page")
+            .and_then(|page| page.parse::<u32>().ok())
+            .unwrap_or(1)
+    };
+
+    let results_resource = Resource::new(
+        move || (search_query(), page()),
+        |(query, page)| search_posts(query, page),
+    );
+
+    let page_href =
+        move |page: u32| format!("/search?q={}&page={page}", Url::escape(&search_query()));
+
+    view! {
+        <div class="max-w-4xl mx-auto w-full">
+            <h1 class="text-3xl font-bold mb-8 bg-gradient-to-r from-primary-600 to-accent-600 text-transparent bg-clip-text flex items-center justify-center gap-2">
+                <span class="i-mdi-magnify text-primary-500 dark:text-primary-400"></span>
+                "Search"
+            </h1>
+
+            <div class="mb-8">
+                {move || {
+                    view! {
+                        <SearchBox
+                            value=search_query()
+                            input_class="bg-white dark:bg-primary-800 border border-gray-300 dark:border-primary-600 dark:text-white focus:ring-primary-500"
+                        />
+                    }
+                }}
+            </div>
+
+            <Suspense fallback=move || {
+                view! { <p>"Loading..."</p> }
+            }>
+                {move || Suspend::new(async move {
+                    match results_resource.await {
+                        Err(e) => {
+                            view! {
+                                <div class="bg-red-100 dark:bg-red-900/30 border border-red-300 dark:border-red-700 text-red-700 dark:text-red-300 px-4 py-3 rounded-lg mb-6 flex items-center gap-2">
+                                    <span class="i-mdi-alert-circle text-lg"></span>
+                                    <p>"Error searching posts: " {e.to_string()}</p>
+                                </div>
+                            }
+                                .into_any()
+                        }
+                        Ok(_) if search_query().trim().is_empty() => ().into_any(),
+                        Ok(search) if search.results.is_empty() && search.page == 1 => {
+                            view! {
+                                <p class="dark:text-gray-300">
+                                    "No posts match “" {search_query()} "”."
+                                </p>
+                            }
+                                .into_any()
+                        }
+                        Ok(search) => {
+                            view! {
+                                <div class="space-y-6">
+                                    {search
+                                        .results
+                                        .into_iter()
+                                        .map(|result| view! { <SearchResultCard result /> })
+                                        .collect_view()}
+                                </div>
+                                <nav class="mt-8 flex justify-between">
+                                    {(search.page > 1)
+                                        .then(|| {
+                                            view! {
+                                                <A
+                                                    href=page_href(search.page - 1)
+                                                    attr:class="text-primary-600 dark:text-primary-400 hover:underline inline-flex items-center gap-1"
+                                                >
+                                                    <span class="i-mdi-arrow-left"></span>
+                                                    "Previous"
+                                                </A>
+                                            }
+                                        })}
+                                    <span></span>
+                                    {search
+                                        .has_more
+                                        .then(|| {
+                                            view! {
+                                                <A
+                                                    href=page_href(search.page + 1)
+                                                    attr:class="text-primary-600 dark:text-primary-400 hover:underline inline-flex items-center gap-1"
+                                                >
+                                                    "Next"
+                                                    <span class="i-mdi-arrow-right"></span>
+                                                </A>
+                                            }
+                                        })}
+                                </nav>
+                            }
+                                .into_any()
+                        }
+                    }
+                })}
+            </Suspense>
+        </div>
+    }
+}
+
+/// A matching post, with the matched terms highlighted
+#[component]
+fn SearchResultCard(result: SearchResult) -> impl IntoView {
+    let SearchResult {
+        post,
+        title_html,
+        snippet_html,
+    } = result;
+
+    view! {
+        <article class="bg-white/80 dark:bg-primary-800/80 backdrop-blur-sm p-6 rounded-xl shadow-lg border-l-4 border-primary-500 dark:border-primary-400 text-left search-result">
+            <h2 class="text-xl font-bold mb-2 flex items-center gap-2">
+                // Highlights are escaped on the server
+                <A
+                    href=format!("/blog/{}", post.slug)
+                    attr:class="text-primary-700 dark:text-primary-400 hover:text-primary-500 dark:hover:text-primary-300 transition-colors duration-300"
+                >
+                    <span inner_html=title_html></span>
+                </A>
+                {(!post.is_visible())
+                    .then(|| {
+                        let status = if post.is_scheduled() { "Scheduled" } else { "Draft" };
+                        view! {
+                            <span class="text-xs font-medium px-2 py-0.5 rounded-full bg-yellow-100 dark:bg-yellow-900/40 text-yellow-800 dark:text-yellow-200">
+                                {status}
+                            </span>
+                        }
+                    })}
+            </h2>
+            <p class="text-gray-700 dark:text-gray-200 mb-4" inner_html=snippet_html></p>
+            <TagList tags=post.tags />
+        </article>
+    }
+}
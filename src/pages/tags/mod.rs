@@ -0,0 +1,5 @@
+mod tag_page;
+mod tags_page;
+
+pub use tag_page::TagPage;
+pub use tags_page::TagsPage;
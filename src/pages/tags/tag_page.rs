@@ -0,0 +1,103 @@
+use crate::components::post_card::PostCard;
+use crate::models::post::PostSummary;
+use crate::server::tags::get_posts_by_tag;
+use leptos::prelude::*;
+use leptos_meta::Link;
+use leptos_router::{components::A, hooks::use_params_map};
+
+/// Published posts with a given tag
+#[component]
+pub fn TagPage() -> impl IntoView {
+    let params = use_params_map();
+    let tag_param = move || params.get().get("tag").unwrap_or_default();
+
+    let posts_resource = Resource::new(tag_param, |tag| get_posts_by_tag(tag, true));
+
+    // Posts carry the tag's display name, the slug is only a fallback
+    let tag_name = move |posts: &[PostSummary]| {
+        let slug = tag_param();
+        posts
+            .iter()
+            .flat_map(|post| post.tags.iter())
+            .find(|tag| tag.slug == slug)
+            .map(|tag| tag.name.clone())
+            .unwrap_or(slug)
+    };
+
+    view! {
+        <div class="max-w-4xl mx-auto w-full">
+            <Suspense fallback=move || {
+                view! { <p>"Loading..."</p> }
+            }>
+                {move || Suspend::new(async move {
+                    match posts_resource.await {
+                        Err(e) => {
+                            view! {
+                                <div class="bg-red-100 dark:bg-red-900/30 border border-red-300 dark:border-red-700 text-red-700 dark:text-red-300 px-4 py-3 rounded-lg mb-6 flex items-center gap-2">
+                                    <span class="i-mdi-alert-circle text-lg"></span>
+                                    <p>"Error loading posts: " {e.to_string()}</p>
+                                </div>
+                            }
+                                .into_any()
+                        }
+                        Ok(posts) => {
+                            let tag = tag_param();
+                            view! {
+                                <Link
+                                    rel="alternate"
+                                    type_="application/rss+xml"
+                                    title=format!("RSS - {}", tag_name(&posts))
+                                    href=format!("/tags/{tag}/feed.xml")
+                                />
+                                <Link
+                                    rel="alternate"
+                                    type_="application/atom+xml"
+                                    title=format!("Atom - {}", tag_name(&posts))
+                                    href=format!("/tags/{tag}/atom.xml")
+                                />
+                                <h1 class="text-3xl font-bold mb-8 bg-gradient-to-r from-primary-600 to-accent-600 text-transparent bg-clip-text flex items-center justify-center gap-2">
+                                    <span class="i-mdi-tag text-primary-500 dark:text-primary-400"></span>
+                                    {format!("Posts tagged “{}”", tag_name(&posts))}
+                                </h1>
+                                {if posts.is_empty() {
+                                    view! {
+                                        <p class="dark:text-gray-300">"No posts with this tag."</p>
+                                    }
+                                        .into_any()
+                                } else {
+                                    view! {
+                                        <div class="space-y-8">
+                                            {posts
+                                                .into_iter()
+                                                .map(|post| view! { <PostCard post /> })
+                                                .collect_view()}
+                                        </div>
+                                    }
+                                        .into_any()
+                                }}
+                                <div class="mt-8 flex justify-between">
+                                    <A
+                                        href="/tags"
+                                        attr:class="text-primary-600 dark:text-primary-400 hover:underline inline-flex items-center gap-1"
+                                    >
+                                        <span class="i-mdi-arrow-left"></span>
+                                        "All tags"
+                                    </A>
+                                    <a
+                                        href=format!("/tags/{tag}/feed.xml")
+                                        rel="external"
+                                        class="text-primary-600 dark:text-primary-400 hover:underline inline-flex items-center gap-1"
+                                    >
+                                        <span class="i-mdi-rss"></span>
+                                        "RSS feed"
+                                    </a>
+                                </div>
+                            }
+                                .into_any()
+                        }
+                    }
+                })}
+            </Suspense>
+        </div>
+    }
+}
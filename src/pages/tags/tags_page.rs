@@ -0,0 +1,66 @@
+use crate::models::tag::TagCount;
+use crate::server::tags::get_tags;
+use leptos::prelude::*;
+use leptos_router::components::A;
+
+/// Index of all tags with their post counts
+#[component]
+pub fn TagsPage() -> impl IntoView {
+    let tags_resource = OnceResource::new(async move { get_tags().await });
+
+    view! {
+        <div class="max-w-4xl mx-auto w-full">
+            <h1 class="text-3xl font-bold mb-8 bg-gradient-to-r from-primary-600 to-accent-600 text-transparent bg-clip-text flex items-center justify-center gap-2">
+                <span class="i-mdi-tag-multiple text-primary-500 dark:text-primary-400"></span>
+                "Tags"
+            </h1>
+
+            <Suspense fallback=move || {
+                view! { <p>"Loading..."</p> }
+            }>
+                {move || Suspend::new(async move {
+                    match tags_resource.await {
+                        Err(e) => {
+                            view! {
+                                <div class="bg-red-100 dark:bg-red-900/30 border border-red-300 dark:border-red-700 text-red-700 dark:text-red-300 px-4 py-3 rounded-lg mb-6 flex items-center gap-2">
+                                    <span class="i-mdi-alert-circle text-lg"></span>
+                                    <p>"Error loading tags: " {e.to_string()}</p>
+                                </div>
+                            }
+                                .into_any()
+                        }
+                        Ok(tags) if tags.is_empty() => {
+                            view! { <p class="dark:text-gray-300">"No tags yet."</p> }.into_any()
+                        }
+                        Ok(tags) => {
+                            view! {
+                                <ul class="flex flex-wrap justify-center gap-3">
+                                    {tags
+                                        .into_iter()
+                                        .map(|TagCount { tag, post_count }| {
+                                            view! {
+                                                <li>
+                                                    <A
+                                                        href=format!("/tags/{}", tag.slug)
+                                                        attr:class="inline-flex items-center gap-2 px-4 py-2 rounded-full bg-white/80 dark:bg-primary-800/80 shadow hover:shadow-lg border border-gray-100 dark:border-primary-700 text-primary-700 dark:text-primary-200 transition-all duration-300"
+                                                    >
+                                                        <span class="i-mdi-tag-outline"></span>
+                                                        {tag.name}
+                                                        <span class="text-xs px-2 py-0.5 rounded-full bg-primary-100 dark:bg-primary-700">
+                                                            {post_count}
+                                                        </span>
+                                                    </A>
+                                                </li>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </ul>
+                            }
+                                .into_any()
+                        }
+                    }
+                })}
+            </Suspense>
+        </div>
+    }
+}
@@ -0,0 +1,314 @@
+use crate::models::admin::{AdminPost, AdminUser, BulkPostAction, SessionList};
+use crate::server::csrf::CsrfClient;
+use leptos::prelude::*;
+
+#[cfg(feature = "ssr")]
+use crate::models::permission::Permission;
+#[cfg(feature = "ssr")]
+use crate::server::utils::db::parse_sqlite_datetime;
+#[cfg(feature = "ssr")]
+use crate::server::utils::session::require_permission;
+
+type Result<T> = std::result::Result<T, ServerFnError>;
+
+/// Admins may not disable or delete themselves
+#[cfg(feature = "ssr")]
+fn ensure_not_self(user: &crate::models::session::SessionUser, id: i64) -> Result<()> {
+    if user.id == Some(id) {
+        return Err(ServerFnError::new(
+            "You may not do this to your own account",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Users whose username contains `query`, every user when it is empty
+#[server(GetAdminUsers, "/api/admin", client = CsrfClient)]
+pub async fn get_admin_users(query: String) -> Result<Vec<AdminUser>> {
+    use super::roles::user_roles;
+
+    require_permission(Permission::AccessAdmin).await?;
+
+    let pattern = format!(
+        "%{}%",
+        query
+            .trim()
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    );
+
+    let conn = crate::server::utils::db::get_db();
+    let mut rows = conn
+        .query(
+            "SELECT id, username, disabled, created_at,
+                (SELECT COUNT(*) FROM posts WHERE posts.author_id = users.id)
+             FROM users
+             WHERE username LIKE ? ESCAPE '\\'
+             ORDER BY id",
+            libsql::params![pattern],
+        )
+        .await?;
+
+    let mut users = Vec::new();
+    while let Some(row) = rows.next().await? {
+        let id: i64 = row.get(0)?;
+        users.push(AdminUser {
+            id,
+            username: row.get(1)?,
+            roles: user_roles(conn, id).await?,
+            disabled: row.get(2)?,
+            created_at: parse_sqlite_datetime(&row.get::<String>(3)?)?,
+            post_count: row.get(4)?,
+        });
+    }
+
+    Ok(users)
+}
+
+/// Disable or enable a user. Disabled users are logged out and may not log in again.
+#[server(SetUserDisabled, "/api/admin", endpoint = "set_user_disabled", client = CsrfClient)]
+pub async fn set_user_disabled(id: i64, disabled: bool) -> Result<()> {
+    use super::roles::ensure_user_manager_remains;
+
+    let user = require_permission(Permission::ManageUsers).await?;
+    ensure_not_self(&user, id)?;
+
+    let conn = crate::server::utils::db::get_db();
+    let tx = conn.transaction().await?;
+
+    let updated = tx
+        .execute(
+            "UPDATE users SET disabled = ? WHERE id = ?",
+            libsql::params![disabled, id],
+        )
+        .await?;
+    if updated == 0 {
+        tx.rollback().await?;
+        return Err(ServerFnError::new("Not found"));
+    }
+
+    if disabled {
+        tx.execute(
+            "DELETE FROM sessions WHERE user_id = ?",
+            libsql::params![id],
+        )
+        .await?;
+    }
+
+    if let Err(e) = ensure_user_manager_remains(&tx).await {
+        tx.rollback().await?;
+        return Err(e);
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Delete a user. Their posts and revisions are kept, without an author.
+#[server(DeleteUser, "/api/admin", endpoint = "delete_user", client = CsrfClient)]
+pub async fn delete_user(id: i64) -> Result<()> {
+    use super::roles::ensure_user_manager_remains;
+
+    let user = require_permission(Permission::ManageUsers).await?;
+    ensure_not_self(&user, id)?;
+
+    let conn = crate::server::utils::db::get_db();
+    let tx = conn.transaction().await?;
+
+    for query in [
+        "DELETE FROM sessions WHERE user_id = ?",
+        "DELETE FROM user_roles WHERE user_id = ?",
+        "DELETE FROM password_reset_tokens WHERE user_id = ?",
+        "DELETE FROM recovery_codes WHERE user_id = ?",
+        "DELETE FROM login_challenges WHERE user_id = ?",
+        "DELETE FROM credentials WHERE user_id = ?",
+        "DELETE FROM identities WHERE user_id = ?",
+        "DELETE FROM oidc_logins WHERE link_user_id = ?",
+        "UPDATE posts SET author_id = NULL WHERE author_id = ?",
+        "UPDATE post_revisions SET author_id = NULL WHERE author_id = ?",
+    ] {
+        tx.execute(query, libsql::params![id]).await?;
+    }
+
+    let deleted = tx
+        .execute("DELETE FROM users WHERE id = ?", libsql::params![id])
+        .await?;
+    if deleted == 0 {
+        tx.rollback().await?;
+        return Err(ServerFnError::new("Not found"));
+    }
+
+    if let Err(e) = ensure_user_manager_remains(&tx).await {
+        tx.rollback().await?;
+        return Err(e);
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Every post, drafts and scheduled posts included, most recently updated first
+#[server(GetAdminPosts, "/api/admin", client = CsrfClient)]
+pub async fn get_admin_posts() -> Result<Vec<AdminPost>> {
+    require_permission(Permission::AccessAdmin).await?;
+
+    let conn = crate::server::utils::db::get_db();
+    let mut rows = conn
+        .query(
+            "SELECT posts.id, posts.slug, posts.title, users.username, posts.published,
+                posts.publish_at, posts.updated_at
+             FROM posts
+             LEFT JOIN users ON users.id = posts.author_id
+             ORDER BY posts.updated_at DESC",
+            (),
+        )
+        .await?;
+
+    let mut posts = Vec::new();
+    while let Some(row) = rows.next().await? {
+        posts.push(AdminPost {
+            id: row.get(0)?,
+            slug: row.get(1)?,
+            title: row.get(2)?,
+            author: row.get(3)?,
+            published: row.get(4)?,
+            publish_at: row
+                .get::<Option<String>>(5)?
+                .map(|publish_at| publish_at.parse())
+                .transpose()?,
+            updated_at: row.get::<String>(6)?.parse()?,
+        });
+    }
+
+    Ok(posts)
+}
+
+/// Publish, unpublish or delete several posts at once. Returns the number of posts changed.
+#[server(BulkUpdatePosts, "/api/admin", endpoint = "bulk_update_posts", client = CsrfClient)]
+pub async fn bulk_update_posts(ids: Vec<i64>, action: BulkPostAction) -> Result<usize> {
+    use super::blog::{format_publish_at, remove_post};
+    use super::revisions::record_revision;
+    use super::scheduler::{publish_due_posts, schedule_changed};
+
+    // The same as editing or deleting the posts one by one
+    let user = require_permission(Permission::EditAllPosts).await?;
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    let conn = crate::server::utils::db::get_db();
+    let now = chrono::Utc::now();
+    let placeholders = (3..ids.len() + 3)
+        .map(|n| format!("?{n}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let changed = match action {
+        BulkPostAction::Delete => {
+            let mut deleted = Vec::new();
+            for &id in &ids {
+                if remove_post(conn, id).await? {
+                    deleted.push(id);
+                }
+            }
+            deleted
+        }
+        BulkPostAction::Publish | BulkPostAction::Unpublish => {
+            // Publishing makes the posts due right away, the scheduler code does the rest
+            let query = if action == BulkPostAction::Publish {
+                format!(
+                    "UPDATE posts SET publish_at = ?2, updated_at = ?1
+                     WHERE id IN ({placeholders}) AND published = FALSE
+                     RETURNING id"
+                )
+            } else {
+                format!(
+                    "UPDATE posts SET published = FALSE, publish_at = NULL, updated_at = ?1
+                     WHERE id IN ({placeholders}) AND (published OR publish_at IS NOT NULL)
+                     RETURNING id"
+                )
+            };
+
+            let params: Vec<libsql::Value> = [now.to_string(), format_publish_at(now)]
+                .into_iter()
+                .map(libsql::Value::from)
+                .chain(ids.iter().copied().map(libsql::Value::from))
+                .collect();
+            let mut rows = conn.query(&query, libsql::params_from_iter(params)).await?;
+            let mut updated = Vec::new();
+            while let Some(row) = rows.next().await? {
+                updated.push(row.get::<i64>(0)?);
+            }
+            updated
+        }
+    };
+
+    if action == BulkPostAction::Publish {
+        publish_due_posts(conn).await?;
+    }
+    if action != BulkPostAction::Delete {
+        schedule_changed();
+        for &id in &changed {
+            record_revision(conn, id, user.id).await?;
+        }
+    }
+
+    Ok(changed.len())
+}
+
+/// Active sessions of logged in users, soonest to expire last
+#[server(GetAdminSessions, "/api/admin", endpoint = "sessions", client = CsrfClient)]
+pub async fn get_admin_sessions() -> Result<SessionList> {
+    use crate::models::admin::AdminSession;
+    use crate::server::utils::session::{get_session_id, hash_session_id};
+
+    require_permission(Permission::AccessAdmin).await?;
+
+    let current_session_hash = get_session_id().await.ok().map(|id| hash_session_id(&id));
+    let now = chrono::Utc::now().timestamp();
+
+    let conn = crate::server::utils::db::get_db();
+    let mut rows = conn
+        .query(
+            "SELECT sessions.public_id, users.username, sessions.expires, sessions.id_hash IS ?1
+             FROM sessions
+             LEFT JOIN users ON users.id = sessions.user_id
+             WHERE sessions.user_id IS NOT NULL AND sessions.expires > datetime(?2, 'unixepoch')
+             ORDER BY sessions.expires DESC",
+            libsql::params![current_session_hash, now],
+        )
+        .await?;
+
+    let mut sessions = Vec::new();
+    while let Some(row) = rows.next().await? {
+        sessions.push(AdminSession {
+            id: row.get(0)?,
+            username: row.get(1)?,
+            expires: parse_sqlite_datetime(&row.get::<String>(2)?)?,
+            current: row.get(3)?,
+        });
+    }
+
+    Ok(SessionList { sessions })
+}
+
+/// Revoke a session, logging its user out
+#[server(RevokeSession, "/api/admin", endpoint = "revoke_session", client = CsrfClient)]
+pub async fn revoke_session(id: String) -> Result<()> {
+    require_permission(Permission::ManageUsers).await?;
+
+    let conn = crate::server::utils::db::get_db();
+    let deleted = conn
+        .execute(
+            "DELETE FROM sessions WHERE public_id = ?",
+            libsql::params![id],
+        )
+        .await?;
+    if deleted == 0 {
+        return Err(ServerFnError::new("Not found"));
+    }
+
+    Ok(())
+}
@@ -1,53 +1,176 @@
 use leptos::prelude::*;
+use webauthn_rs_proto::{
+    CreationChallengeResponse, PublicKeyCredential, RegisterPublicKeyCredential,
+    RequestChallengeResponse,
+};
 
-use crate::models::user::{LoginCredentials, NewUser, User};
+use crate::models::passkey::{PasskeyCeremony, PasskeyInfo};
+use crate::models::user::{LoginCredentials, LoginResponse, NewUser, User};
+use crate::server::csrf::CsrfClient;
 
 type Result<T> = std::result::Result<T, ServerFnError>;
 
-#[server(Register, "/api/auth")]
-pub async fn register(new_user: NewUser) -> Result<()> {
+/// Shortest password accepted, in characters
+#[cfg(feature = "ssr")]
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Hash a password with the argon2 defaults. Fails when it is too short, every password set
+/// goes through here.
+#[cfg(feature = "ssr")]
+pub fn hash_password(password: &str) -> Result<String> {
     use argon2::{
         password_hash::{rand_core::OsRng, PasswordHasher, SaltString},
         Argon2,
     };
-    use leptos::prelude::*;
-    use libsql::params;
 
-    use super::utils::db::get_db;
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(ServerFnError::new(format!(
+            "Password must be at least {MIN_PASSWORD_LENGTH} characters"
+        )));
+    }
 
-    // Hash the password
     let salt = SaltString::generate(&mut OsRng);
     let argon2_config = Argon2::default();
-    let password_hash = argon2_config
-        .hash_password(new_user.password.as_bytes(), &salt)
+    Ok(argon2_config
+        .hash_password(password.as_bytes(), &salt)
         .map_err(|e| ServerFnError::new(e.to_string()))?
-        .to_string();
+        .to_string())
+}
 
-    let conn = get_db();
+/// Trim an email address, `None` when it is empty. Fails when it isn't a valid address.
+#[cfg(feature = "ssr")]
+pub fn normalize_email(email: Option<String>) -> Result<Option<String>> {
+    let Some(email) = email
+        .map(|email| email.trim().to_string())
+        .filter(|email| !email.is_empty())
+    else {
+        return Ok(None);
+    };
+
+    if email.parse::<lettre::Address>().is_err() {
+        return Err(ServerFnError::new("Invalid email address"));
+    }
+
+    Ok(Some(email))
+}
+
+/// Message for a failed insert or update of a user
+#[cfg(feature = "ssr")]
+fn user_conflict_error(e: libsql::Error) -> ServerFnError {
+    let message = e.to_string();
+    if message.contains("UNIQUE constraint failed: users.email") {
+        ServerFnError::new("Email already in use")
+    } else if message.contains("UNIQUE constraint failed") {
+        ServerFnError::new("Username already exists")
+    } else {
+        ServerFnError::new("Database error")
+    }
+}
+
+/// Insert a user with the given role. Returns their id.
+#[cfg(feature = "ssr")]
+pub async fn create_user(
+    conn: &libsql::Connection,
+    username: String,
+    password: String,
+    email: Option<String>,
+    role: &str,
+) -> Result<i64> {
+    use libsql::params;
+
+    let email = normalize_email(email)?;
+    let password_hash = hash_password(&password)?;
 
     // Insert the new user
-    let result = conn
-        .execute(
-            "INSERT INTO users (username, password_hash, is_admin) VALUES (?, ?, FALSE)",
-            params![new_user.username, password_hash],
+    let mut rows = conn
+        .query(
+            "INSERT INTO users (username, password_hash, email) VALUES (?, ?, ?) RETURNING id",
+            params![username, password_hash, email],
         )
-        .await;
-
-    match result {
-        Ok(_) => Ok(()),
-        Err(e) => {
-            if e.to_string().contains("UNIQUE constraint failed") {
-                Err(ServerFnError::new("Username already exists"))
-            } else {
-                Err(ServerFnError::new("Database error"))
-            }
-        }
-    }
+        .await
+        .map_err(user_conflict_error)?;
+
+    let id: i64 = match rows.next().await? {
+        Some(row) => row.get(0)?,
+        None => return Err(ServerFnError::new("Database error")),
+    };
+
+    conn.execute(
+        "INSERT INTO user_roles (user_id, role_id) SELECT ?, id FROM roles WHERE name = ?",
+        params![id, role],
+    )
+    .await?;
+
+    Ok(id)
+}
+
+#[server(Register, "/api/auth", client = CsrfClient)]
+pub async fn register(new_user: NewUser) -> Result<()> {
+    use super::roles::DEFAULT_ROLE;
+    use super::utils::db::get_db;
+
+    create_user(
+        get_db(),
+        new_user.username,
+        new_user.password,
+        new_user.email,
+        DEFAULT_ROLE,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Load a user with their roles and effective permissions
+#[cfg(feature = "ssr")]
+pub(crate) async fn load_user(conn: &libsql::Connection, id: i64) -> Result<Option<User>> {
+    use super::roles::{user_permissions, user_roles};
+    use super::utils::db::parse_sqlite_datetime;
+
+    let mut rows = conn
+        .query(
+            "SELECT id, username, password_hash, theme_preference, created_at, email, totp_enabled,
+                EXISTS (
+                    SELECT 1 FROM user_roles JOIN roles ON roles.id = user_roles.role_id
+                    WHERE user_roles.user_id = users.id AND roles.requires_two_factor
+                )
+             FROM users WHERE id = ?",
+            libsql::params![id],
+        )
+        .await?;
+
+    let Some(row) = rows.next().await? else {
+        return Ok(None);
+    };
+
+    Ok(Some(User {
+        id,
+        username: row.get(1)?,
+        email: row.get(5)?,
+        password_hash: row.get(2)?,
+        roles: user_roles(conn, id).await?,
+        permissions: user_permissions(conn, id).await?,
+        theme_preference: crate::models::session::ThemePreference::from_libsql_value(row.get(3)?),
+        created_at: parse_sqlite_datetime(&row.get::<String>(4)?)?,
+        two_factor_enabled: row.get(6)?,
+        two_factor_required: row.get(7)?,
+    }))
 }
 
-#[server(Login, "/api/auth")]
-pub async fn login(credentials: LoginCredentials) -> Result<User> {
-    use crate::models::user::User;
+/// Hash checked against the password of unknown usernames, so that they take as long to
+/// refuse as wrong passwords and can't be told apart by timing
+#[cfg(feature = "ssr")]
+fn dummy_password_hash() -> &'static str {
+    static HASH: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+    HASH.get_or_init(|| hash_password("not the password of anyone").unwrap_or_default())
+}
+
+/// Check the password, then either log the user in or, when they have two-factor
+/// authentication, ask for a code with [`verify_login_code`](super::two_factor::verify_login_code).
+/// Failures slow down then block further logins of the username and from the address, see
+/// [`throttle`](super::utils::throttle).
+#[server(Login, "/api/auth", endpoint = "login", client = CsrfClient)]
+pub async fn login(credentials: LoginCredentials) -> Result<LoginResponse> {
     use argon2::{
         password_hash::{PasswordHash, PasswordVerifier},
         Argon2,
@@ -55,122 +178,479 @@ pub async fn login(credentials: LoginCredentials) -> Result<User> {
     use leptos::prelude::*;
     use libsql::params;
 
+    use super::two_factor::start_login_challenge;
     use super::utils::db::get_db;
+    use super::utils::request::client_ip;
     use super::utils::session;
+    use super::utils::throttle::{
+        login_failed, login_succeeded, password_accepted, reserve_login_attempt,
+    };
 
     tracing::debug!("login 1");
     let conn = get_db();
+    let ip = client_ip().await;
+    reserve_login_attempt(conn, &credentials.username, ip).await?;
 
     // Fetch the user
-    let mut rows = conn.query(
-        "SELECT id, username, password_hash, is_admin, theme_preference, created_at FROM users WHERE username = ?",
-        params![credentials.username.clone()],
-    ).await?;
+    let mut rows = conn
+        .query(
+            "SELECT id, password_hash, disabled, totp_enabled FROM users WHERE username = ?",
+            params![credentials.username.clone()],
+        )
+        .await?;
 
     let first_row = rows.next().await?;
+    drop(rows);
 
     tracing::debug!("login 2 {:?}", first_row);
 
-    let Some(row) = first_row else {
-        return Err(ServerFnError::new("Invalid username or password"));
+    let password_hash = match &first_row {
+        Some(row) => row.get::<String>(1)?,
+        None => dummy_password_hash().to_string(),
     };
 
-    let password_hash: String = row.get(2)?;
-
     // Verify the password
     let parsed_hash =
         PasswordHash::new(&password_hash).map_err(|e| ServerFnError::new(e.to_string()))?;
 
     let argon2 = Argon2::default();
-    if argon2
+    let verified = argon2
         .verify_password(credentials.password.as_bytes(), &parsed_hash)
-        .is_err()
-    {
+        .is_ok();
+    // Disabled accounts look like wrong passwords, so that guessing them tells nothing
+    let Some(row) = first_row.filter(|row| verified && matches!(row.get::<bool>(2), Ok(false)))
+    else {
+        login_failed(conn, &credentials.username, ip).await?;
         return Err(ServerFnError::new("Invalid username or password"));
+    };
+
+    let id: i64 = row.get(0)?;
+    if row.get::<bool>(3)? {
+        password_accepted(conn, ip).await?;
+        let challenge = start_login_challenge(conn, id, credentials.remember).await?;
+        return Ok(LoginResponse::TwoFactorRequired { challenge });
     }
 
-    let theme_preference = crate::models::session::ThemePreference::from_libsql_value(row.get(4)?);
-    let created_at = row.get::<String>(5)?;
-    tracing::debug!("login 3 {:?} {:?}", theme_preference, created_at);
+    login_succeeded(conn, &credentials.username, ip).await?;
 
     // Get user data
-    let user = User {
-        id: row.get(0)?,
-        username: row.get(1)?,
-        password_hash,
-        is_admin: row.get(3)?,
-        theme_preference,
-        created_at: chrono::NaiveDateTime::parse_from_str(
-            created_at.as_str(),
-            "%Y-%m-%d %H:%M:%S",
-        )?
-        .and_utc(),
+    let Some(user) = load_user(conn, id).await? else {
+        return Err(ServerFnError::new("Invalid username or password"));
     };
 
-    tracing::debug!("login 4 {:?}", user);
+    tracing::debug!("login 3 {:?}", user);
 
     // Set the user in session
-    session::set_user_session(&user.get_session_user()).await?;
+    session::start_session(&user.get_session_user(), credentials.remember).await?;
 
-    tracing::debug!("login 5 {:?}", user);
-
-    Ok(user)
+    Ok(LoginResponse::LoggedIn(user))
 }
 
-#[server(Logout, "/api/auth")]
+#[server(Logout, "/api/auth", endpoint = "logout", client = CsrfClient)]
 pub async fn logout() -> Result<()> {
     use super::utils::session;
     session::clear_user().await?;
     Ok(())
 }
 
-#[server(GetCurrentUser, "/api/auth")]
+#[server(GetCurrentUser, "/api/auth", endpoint = "current_user", client = CsrfClient)]
 pub async fn get_current_user() -> Result<Option<User>> {
-    use crate::models::user::User;
+    use super::utils::db::get_db;
+    use super::utils::session;
 
-    use libsql::params;
+    let session_user = session::get_user_session().await?;
+
+    let Some(session_user) = session_user else {
+        return Ok(None);
+    };
+    let Some(id) = session_user.id else {
+        return Ok(None);
+    };
+    let Some(user) = load_user(get_db(), id).await? else {
+        return Ok(None);
+    };
 
+    let should_be_session_user = user.get_session_user();
+    if should_be_session_user != session_user {
+        session::set_user_session(&should_be_session_user).await?;
+    }
+
+    Ok(Some(user))
+}
+
+/// Change the email address of the current user, removing it when empty
+#[server(UpdateEmail, "/api/auth", client = CsrfClient)]
+pub async fn update_email(email: Option<String>) -> Result<()> {
     use super::utils::db::get_db;
     use super::utils::session;
 
-    let session_user = session::get_user_session().await?;
+    let Some(user_id) = session::get_user_session().await?.and_then(|user| user.id) else {
+        return Err(ServerFnError::new("Unauthorized"));
+    };
 
-    if let Some(session_user) = session_user {
-        let conn = get_db();
+    let email = normalize_email(email)?;
+    let conn = get_db();
+    conn.execute(
+        "UPDATE users SET email = ? WHERE id = ?",
+        libsql::params![email, user_id],
+    )
+    .await
+    .map_err(user_conflict_error)?;
+    // Links sent to the previous address must not work anymore
+    conn.execute(
+        "DELETE FROM password_reset_tokens WHERE user_id = ?",
+        libsql::params![user_id],
+    )
+    .await?;
 
-        let mut rows = conn
-            .query(
-                "SELECT id, username, password_hash, is_admin, theme_preference, created_at FROM users WHERE id = ?",
-                params![session_user.id],
-            )
-            .await?;
+    Ok(())
+}
 
-        let Some(row) = rows.next().await? else {
-            return Ok(None);
-        };
+/// Name of a passkey when the user didn't choose one
+#[cfg(feature = "ssr")]
+const DEFAULT_PASSKEY_NAME: &str = "Passkey";
 
-        let user = User {
-            id: row.get(0)?,
-            username: row.get(1)?,
-            password_hash: row.get(2)?,
-            is_admin: row.get(3)?,
-            theme_preference: crate::models::session::ThemePreference::from_libsql_value(
-                row.get(4)?,
-            ),
-            created_at: chrono::NaiveDateTime::parse_from_str(
-                &row.get::<String>(5)?,
-                "%Y-%m-%d %H:%M:%S",
-            )?
-            .and_utc(),
-        };
-
-        let should_be_session_user = user.get_session_user();
-        if should_be_session_user != session_user {
-            session::set_user_session(&should_be_session_user).await?;
+#[cfg(feature = "ssr")]
+fn passkey_name(name: String) -> String {
+    let name = name.trim();
+    if name.is_empty() {
+        DEFAULT_PASSKEY_NAME.to_string()
+    } else {
+        name.chars().take(64).collect()
+    }
+}
+
+/// Start creating an account that logs in with a passkey instead of a password
+#[server(StartPasskeySignup, "/api/auth", client = CsrfClient)]
+pub async fn start_passkey_signup(
+    username: String,
+    email: Option<String>,
+) -> Result<PasskeyCeremony<CreationChallengeResponse>> {
+    use super::utils::db::get_db;
+    use super::utils::webauthn::{start_ceremony, webauthn, webauthn_error, CeremonyState};
+
+    let username = username.trim().to_string();
+    if username.is_empty() {
+        return Err(ServerFnError::new("Username is required"));
+    }
+    let email = normalize_email(email)?;
+
+    // Checked again when the account is created, this spares asking the authenticator for nothing
+    let conn = get_db();
+    let mut rows = conn
+        .query(
+            "SELECT email = ? COLLATE NOCASE FROM users WHERE username = ? OR email = ? COLLATE NOCASE",
+            libsql::params![email.clone(), username.clone(), email.clone()],
+        )
+        .await?;
+    if let Some(row) = rows.next().await? {
+        return Err(if row.get::<Option<bool>>(0)?.unwrap_or(false) {
+            ServerFnError::new("Email already in use")
+        } else {
+            ServerFnError::new("Username already exists")
+        });
+    }
+
+    let user_handle = webauthn_rs::prelude::Uuid::new_v4();
+    let (options, registration) = webauthn()
+        .start_passkey_registration(user_handle, &username, &username, None)
+        .map_err(webauthn_error)?;
+    let ceremony = start_ceremony(
+        conn,
+        &CeremonyState::Signup {
+            username,
+            email,
+            user_handle,
+            registration,
+        },
+    )
+    .await?;
+
+    Ok(PasskeyCeremony { ceremony, options })
+}
+
+/// Create the account with the passkey the authenticator made, and log the user in. The
+/// account gets a random password nobody knows, a password reset can set a real one.
+#[server(FinishPasskeySignup, "/api/auth", client = CsrfClient)]
+pub async fn finish_passkey_signup(
+    ceremony: String,
+    credential: RegisterPublicKeyCredential,
+) -> Result<User> {
+    use super::roles::DEFAULT_ROLE;
+    use super::utils::crypto::random_token;
+    use super::utils::db::get_db;
+    use super::utils::session;
+    use super::utils::webauthn::{
+        insert_passkey, take_ceremony, webauthn, webauthn_error, CeremonyState,
+    };
+
+    let conn = get_db();
+    let CeremonyState::Signup {
+        username,
+        email,
+        user_handle,
+        registration,
+    } = take_ceremony(conn, &ceremony).await?
+    else {
+        return Err(ServerFnError::new("Invalid passkey request"));
+    };
+    let passkey = webauthn()
+        .finish_passkey_registration(&credential, &registration)
+        .map_err(webauthn_error)?;
+
+    let tx = conn.transaction().await?;
+    let user_id = create_user(&tx, username, random_token(), email, DEFAULT_ROLE).await?;
+    tx.execute(
+        "UPDATE users SET webauthn_id = ? WHERE id = ?",
+        libsql::params![user_handle.to_string(), user_id],
+    )
+    .await?;
+    insert_passkey(&tx, user_id, DEFAULT_PASSKEY_NAME, &passkey).await?;
+    tx.commit().await?;
+
+    let Some(user) = load_user(conn, user_id).await? else {
+        return Err(ServerFnError::new("Not found"));
+    };
+    session::start_session(&user.get_session_user(), false).await?;
+
+    Ok(user)
+}
+
+/// Start adding a passkey to the account of the current user
+#[server(StartPasskeyRegistration, "/api/auth", client = CsrfClient)]
+pub async fn start_passkey_registration() -> Result<PasskeyCeremony<CreationChallengeResponse>> {
+    use super::utils::db::get_db;
+    use super::utils::session;
+    use super::utils::webauthn::{
+        start_ceremony, user_handle, user_passkeys, webauthn, webauthn_error, CeremonyState,
+    };
+
+    let Some(session_user) = session::get_user_session().await? else {
+        return Err(ServerFnError::new("Unauthorized"));
+    };
+    let (Some(user_id), Some(username)) = (session_user.id, session_user.username) else {
+        return Err(ServerFnError::new("Unauthorized"));
+    };
+
+    let conn = get_db();
+    // The authenticator refuses to register a second passkey for the same account
+    let existing = user_passkeys(conn, user_id)
+        .await?
+        .iter()
+        .map(|passkey| passkey.cred_id().clone())
+        .collect();
+    let (options, registration) = webauthn()
+        .start_passkey_registration(
+            user_handle(conn, user_id).await?,
+            &username,
+            &username,
+            Some(existing),
+        )
+        .map_err(webauthn_error)?;
+    let ceremony = start_ceremony(
+        conn,
+        &CeremonyState::Registration {
+            user_id,
+            registration,
+        },
+    )
+    .await?;
+
+    Ok(PasskeyCeremony { ceremony, options })
+}
+
+/// Store the passkey the authenticator made for the current user
+#[server(FinishPasskeyRegistration, "/api/auth", client = CsrfClient)]
+pub async fn finish_passkey_registration(
+    ceremony: String,
+    credential: RegisterPublicKeyCredential,
+    name: String,
+) -> Result<()> {
+    use super::utils::db::get_db;
+    use super::utils::session;
+    use super::utils::webauthn::{
+        insert_passkey, take_ceremony, webauthn, webauthn_error, CeremonyState,
+    };
+
+    let Some(current_user_id) = session::get_user_session().await?.and_then(|user| user.id) else {
+        return Err(ServerFnError::new("Unauthorized"));
+    };
+
+    let conn = get_db();
+    let CeremonyState::Registration {
+        user_id,
+        registration,
+    } = take_ceremony(conn, &ceremony).await?
+    else {
+        return Err(ServerFnError::new("Invalid passkey request"));
+    };
+    if user_id != current_user_id {
+        return Err(ServerFnError::new("Invalid passkey request"));
+    }
+
+    let passkey = webauthn()
+        .finish_passkey_registration(&credential, &registration)
+        .map_err(webauthn_error)?;
+    insert_passkey(conn, user_id, &passkey_name(name), &passkey).await
+}
+
+/// Start logging in with one of the passkeys of a user
+#[server(StartPasskeyLogin, "/api/auth", client = CsrfClient)]
+pub async fn start_passkey_login(
+    username: String,
+) -> Result<PasskeyCeremony<RequestChallengeResponse>> {
+    use super::utils::db::get_db;
+    use super::utils::webauthn::{
+        start_ceremony, user_passkeys, webauthn, webauthn_error, CeremonyState,
+    };
+
+    let conn = get_db();
+    let mut rows = conn
+        .query(
+            "SELECT id FROM users WHERE username = ? AND NOT disabled",
+            libsql::params![username.trim()],
+        )
+        .await?;
+    let (user_id, passkeys) = match rows.next().await? {
+        Some(row) => {
+            let user_id: i64 = row.get(0)?;
+            (user_id, user_passkeys(conn, user_id).await?)
         }
+        None => (0, Vec::new()),
+    };
+    // The same as a wrong password, so that it doesn't tell which usernames exist
+    if passkeys.is_empty() {
+        return Err(ServerFnError::new("Invalid username or password"));
+    }
 
-        Ok(Some(user))
-    } else {
-        Ok(None)
+    let (options, authentication) = webauthn()
+        .start_passkey_authentication(&passkeys)
+        .map_err(webauthn_error)?;
+    let ceremony = start_ceremony(
+        conn,
+        &CeremonyState::Login {
+            user_id,
+            authentication,
+        },
+    )
+    .await?;
+
+    Ok(PasskeyCeremony { ceremony, options })
+}
+
+/// Check the answer of the authenticator and log the user in. Passkeys verify the user
+/// themselves, so this doesn't ask for a two-factor authentication code.
+#[server(FinishPasskeyLogin, "/api/auth", client = CsrfClient)]
+pub async fn finish_passkey_login(
+    ceremony: String,
+    credential: PublicKeyCredential,
+    #[server(default)] remember: bool,
+) -> Result<User> {
+    use super::utils::db::get_db;
+    use super::utils::session;
+    use super::utils::webauthn::{take_ceremony, webauthn, webauthn_error, CeremonyState};
+
+    let conn = get_db();
+    let CeremonyState::Login {
+        user_id,
+        authentication,
+    } = take_ceremony(conn, &ceremony).await?
+    else {
+        return Err(ServerFnError::new("Invalid passkey request"));
+    };
+    let result = webauthn()
+        .finish_passkey_authentication(&credential, &authentication)
+        .map_err(webauthn_error)?;
+
+    let credential_id = hex::encode(result.cred_id());
+    let mut rows = conn
+        .query(
+            "SELECT credentials.public_key FROM credentials
+             JOIN users ON users.id = credentials.user_id
+             WHERE credentials.id = ? AND credentials.user_id = ? AND NOT users.disabled",
+            libsql::params![credential_id.clone(), user_id],
+        )
+        .await?;
+    let Some(row) = rows.next().await? else {
+        return Err(ServerFnError::new("The passkey could not be verified"));
+    };
+    let mut passkey: webauthn_rs::prelude::Passkey = serde_json::from_str(&row.get::<String>(0)?)?;
+
+    // Keep the signature counter, so that a cloned authenticator gets noticed
+    passkey.update_credential(&result);
+    conn.execute(
+        "UPDATE credentials SET public_key = ?, sign_count = ?, last_used_at = CURRENT_TIMESTAMP
+         WHERE id = ?",
+        libsql::params![
+            serde_json::to_string(&passkey)?,
+            result.counter(),
+            credential_id
+        ],
+    )
+    .await?;
+
+    let Some(user) = load_user(conn, user_id).await? else {
+        return Err(ServerFnError::new("Not found"));
+    };
+    session::start_session(&user.get_session_user(), remember).await?;
+
+    Ok(user)
+}
+
+/// Passkeys of the current user
+#[server(GetPasskeys, "/api/auth", client = CsrfClient)]
+pub async fn get_passkeys() -> Result<Vec<PasskeyInfo>> {
+    use super::utils::db::{get_db, parse_sqlite_datetime};
+    use super::utils::session;
+
+    let Some(user_id) = session::get_user_session().await?.and_then(|user| user.id) else {
+        return Err(ServerFnError::new("Unauthorized"));
+    };
+
+    let mut rows = get_db()
+        .query(
+            "SELECT id, name, created_at, last_used_at FROM credentials
+             WHERE user_id = ? ORDER BY created_at",
+            libsql::params![user_id],
+        )
+        .await?;
+
+    let mut passkeys = Vec::new();
+    while let Some(row) = rows.next().await? {
+        passkeys.push(PasskeyInfo {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            created_at: parse_sqlite_datetime(&row.get::<String>(2)?)?,
+            last_used_at: row
+                .get::<Option<String>>(3)?
+                .as_deref()
+                .map(parse_sqlite_datetime)
+                .transpose()?,
+        });
+    }
+
+    Ok(passkeys)
+}
+
+/// Remove a passkey of the current user
+#[server(DeletePasskey, "/api/auth", client = CsrfClient)]
+pub async fn delete_passkey(id: String) -> Result<()> {
+    use super::utils::db::get_db;
+    use super::utils::session;
+
+    let Some(user_id) = session::get_user_session().await?.and_then(|user| user.id) else {
+        return Err(ServerFnError::new("Unauthorized"));
+    };
+
+    let deleted = get_db()
+        .execute(
+            "DELETE FROM credentials WHERE id = ? AND user_id = ?",
+            libsql::params![id, user_id],
+        )
+        .await?;
+    if deleted == 0 {
+        return Err(ServerFnError::new("Not found"));
     }
+
+    Ok(())
 }
@@ -0,0 +1,117 @@
+use crate::models::author::{AuthorProfile, ProfileUpdate};
+use crate::server::csrf::CsrfClient;
+use leptos::prelude::*;
+
+#[cfg(feature = "ssr")]
+use crate::models::author::Author;
+
+type Result<T> = std::result::Result<T, ServerFnError>;
+
+/// Longest accepted bio, in characters
+#[cfg(feature = "ssr")]
+const MAX_BIO_LENGTH: usize = 2000;
+
+/// Longest accepted avatar URL
+#[cfg(feature = "ssr")]
+const MAX_AVATAR_URL_LENGTH: usize = 2048;
+
+/// Public profile of an author. Users who may not write posts and never did have none.
+#[server(GetAuthor, "/api/authors", client = CsrfClient)]
+pub async fn get_author(username: String) -> Result<AuthorProfile> {
+    use super::blog::{post_from_row, visible_filter, POST_COLUMNS};
+    use super::tags::load_tags;
+    use super::utils::db::parse_sqlite_datetime;
+    use crate::models::permission::Permission;
+    use crate::models::post::PostSummary;
+
+    let conn = crate::server::utils::db::get_db();
+
+    let mut rows = conn
+        .query(
+            "SELECT id, username, avatar_url, bio, created_at, EXISTS (
+                SELECT 1 FROM user_roles
+                JOIN role_permissions ON role_permissions.role_id = user_roles.role_id
+                JOIN permissions ON permissions.id = role_permissions.permission_id
+                WHERE user_roles.user_id = users.id AND permissions.name = ?
+             )
+             FROM users WHERE username = ?",
+            libsql::params![Permission::CreatePosts.name(), username],
+        )
+        .await?;
+    let Some(row) = rows.next().await? else {
+        return Err(ServerFnError::new("Not found"));
+    };
+
+    let id: i64 = row.get(0)?;
+    let author = Author {
+        username: row.get(1)?,
+        avatar_url: row.get(2)?,
+    };
+    let bio: String = row.get(3)?;
+    let joined_at = parse_sqlite_datetime(&row.get::<String>(4)?)?;
+    let can_write_posts: bool = row.get(5)?;
+
+    let mut rows = conn
+        .query(
+            &format!(
+                "SELECT {POST_COLUMNS} FROM posts
+                 WHERE posts.author_id = ?1 AND {}
+                 ORDER BY posts.created_at DESC",
+                visible_filter(2)
+            ),
+            libsql::params![id, chrono::Utc::now().timestamp()],
+        )
+        .await?;
+
+    let mut posts = Vec::new();
+    while let Some(row) = rows.next().await? {
+        posts.push(post_from_row(&row)?);
+    }
+    load_tags(conn, &mut posts).await?;
+
+    if posts.is_empty() && !can_write_posts {
+        return Err(ServerFnError::new("Not found"));
+    }
+
+    Ok(AuthorProfile {
+        author,
+        bio,
+        joined_at,
+        posts: posts.into_iter().map(PostSummary::from).collect(),
+    })
+}
+
+/// Update the bio and avatar of the current user
+#[server(UpdateProfile, "/api/authors", client = CsrfClient)]
+pub async fn update_profile(profile: ProfileUpdate) -> Result<()> {
+    let Some(user_id) = crate::server::utils::session::get_user_session()
+        .await?
+        .and_then(|user| user.id)
+    else {
+        return Err(ServerFnError::new("Unauthorized"));
+    };
+
+    let bio = profile.bio.trim().to_string();
+    if bio.chars().count() > MAX_BIO_LENGTH {
+        return Err(ServerFnError::new(format!(
+            "The bio may not be longer than {MAX_BIO_LENGTH} characters"
+        )));
+    }
+
+    let avatar_url = Some(profile.avatar_url.trim().to_string()).filter(|url| !url.is_empty());
+    if avatar_url.as_ref().is_some_and(|url| {
+        !(url.starts_with("https://") || url.starts_with("http://"))
+            || url.len() > MAX_AVATAR_URL_LENGTH
+    }) {
+        return Err(ServerFnError::new("The avatar must be an http(s) URL"));
+    }
+
+    let conn = crate::server::utils::db::get_db();
+    conn.execute(
+        "UPDATE users SET bio = ?, avatar_url = ? WHERE id = ?",
+        libsql::params![bio, avatar_url, user_id],
+    )
+    .await?;
+
+    Ok(())
+}
@@ -1,63 +1,418 @@
-use crate::models::post::{NewPost, Post, UpdatePostData};
+use crate::models::post::{NewPost, Post, PostCursor, PostList, RenderedPost, UpdatePostData};
+use crate::server::csrf::CsrfClient;
 use leptos::prelude::*;
 
+#[cfg(feature = "ssr")]
+use super::revisions::record_revision;
+#[cfg(feature = "ssr")]
+use super::scheduler::{publish_due_posts, schedule_changed};
+#[cfg(feature = "ssr")]
+use super::tags::{load_tags, set_post_tags};
+#[cfg(feature = "ssr")]
+use crate::models::author::Author;
+
 type Result<T> = std::result::Result<T, ServerFnError>;
 
-#[server(GetPosts, "/api/blog")]
-pub async fn get_posts(only_published: bool) -> Result<Vec<Post>> {
-    let conn = crate::server::utils::db::get_db();
+/// Columns of a post, qualified so that they can be selected from joins
+#[cfg(feature = "ssr")]
+pub(crate) const POST_COLUMNS: &str = "posts.id, posts.slug, posts.title, posts.content, \
+    posts.created_at, posts.updated_at, posts.published, posts.excerpt, posts.auto_excerpt, \
+    posts.word_count, posts.reading_time, posts.publish_at, posts.author_id, \
+    (SELECT username FROM users WHERE users.id = posts.author_id), \
+    (SELECT avatar_url FROM users WHERE users.id = posts.author_id)";
+
+/// Number of columns in [`POST_COLUMNS`], i.e. index of the first column selected after them
+#[cfg(feature = "ssr")]
+pub(crate) const POST_COLUMN_COUNT: i32 = 15;
+
+/// SQL condition matching the posts readers can see, those whose publication time has come.
+/// The current time is bound to the parameter numbered `now_param`, as a Unix timestamp.
+#[cfg(feature = "ssr")]
+pub(crate) fn visible_filter(now_param: usize) -> String {
+    format!("datetime(posts.publish_at) <= datetime(?{now_param}, 'unixepoch')")
+}
+
+/// `at` as stored in `posts.publish_at`: RFC 3339 in UTC, which SQLite's date functions read
+/// as well as chrono
+#[cfg(feature = "ssr")]
+pub(crate) fn format_publish_at(at: chrono::DateTime<chrono::Utc>) -> String {
+    at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+/// Build a post from a row selected with [`POST_COLUMNS`], without its tags
+#[cfg(feature = "ssr")]
+pub(crate) fn post_from_row(row: &libsql::Row) -> Result<Post> {
+    Ok(Post {
+        id: row.get(0)?,
+        slug: row.get(1)?,
+        title: row.get(2)?,
+        content: row.get(3)?,
+        created_at: row.get::<String>(4)?.parse()?,
+        updated_at: row.get::<String>(5)?.parse()?,
+        published: row.get(6)?,
+        tags: Vec::new(),
+        excerpt: row.get(7)?,
+        auto_excerpt: row.get::<Option<String>>(8)?.unwrap_or_default(),
+        word_count: row.get::<Option<i64>>(9)?.unwrap_or_default(),
+        reading_time: row.get::<Option<i64>>(10)?.unwrap_or_default(),
+        publish_at: row
+            .get::<Option<String>>(11)?
+            .map(|publish_at| publish_at.parse())
+            .transpose()?,
+        author_id: row.get(12)?,
+        author: author_from_row(row, 13)?,
+    })
+}
+
+/// Author selected as username and avatar columns starting at `index`, if any
+#[cfg(feature = "ssr")]
+fn author_from_row(row: &libsql::Row, index: i32) -> Result<Option<Author>> {
+    let Some(username) = row.get::<Option<String>>(index)? else {
+        return Ok(None);
+    };
+
+    Ok(Some(Author {
+        username,
+        avatar_url: row.get(index + 1)?,
+    }))
+}
+
+/// When a saved post goes live: now if published, unless it is already live, at the
+/// scheduled time otherwise, and never for drafts
+#[cfg(feature = "ssr")]
+fn publish_time(
+    published: bool,
+    scheduled: Option<chrono::DateTime<chrono::Utc>>,
+    existing: Option<&Post>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    if published {
+        let live_since = existing
+            .and_then(|post| post.publish_at)
+            .filter(|publish_at| *publish_at <= now);
+        Some(live_since.unwrap_or(now))
+    } else {
+        scheduled
+    }
+}
+
+/// Excerpt written by the author, `None` when left empty
+#[cfg(feature = "ssr")]
+fn author_excerpt(excerpt: Option<String>) -> Option<String> {
+    excerpt
+        .map(|excerpt| excerpt.trim().to_string())
+        .filter(|excerpt| !excerpt.is_empty())
+}
+
+/// Users may edit posts of their own, or any post, depending on their permissions
+#[cfg(feature = "ssr")]
+pub(crate) async fn ensure_can_edit_post(
+    post: &Post,
+) -> Result<crate::models::session::SessionUser> {
+    crate::server::utils::session::get_user_session()
+        .await?
+        .filter(|user| user.can_edit_post(post.author_id))
+        .ok_or_else(|| ServerFnError::new("Forbidden"))
+}
+
+/// Unpublished posts may only be listed by those who may view drafts
+#[cfg(feature = "ssr")]
+pub(crate) async fn ensure_can_list_drafts(only_published: bool) -> Result<()> {
+    use crate::models::permission::Permission;
+    use crate::server::utils::session::require_permission;
+
+    if !only_published {
+        require_permission(Permission::ViewDrafts).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "ssr")]
+pub(crate) async fn find_post_by_id(conn: &libsql::Connection, id: i64) -> Result<Option<Post>> {
+    let mut rows = conn
+        .query(
+            &format!("SELECT {POST_COLUMNS} FROM posts WHERE id = ?"),
+            libsql::params![id],
+        )
+        .await?;
+
+    let Some(row) = rows.next().await? else {
+        return Ok(None);
+    };
+
+    let mut post = post_from_row(&row)?;
+    load_tags(conn, std::slice::from_mut(&mut post)).await?;
+
+    Ok(Some(post))
+}
+
+/// Find a post by its current slug, a slug it had in the past, or its legacy numeric id
+#[cfg(feature = "ssr")]
+pub(crate) async fn find_post_by_slug(
+    conn: &libsql::Connection,
+    slug: &str,
+) -> Result<Option<Post>> {
+    let mut rows = conn
+        .query(
+            &format!("SELECT {POST_COLUMNS} FROM posts WHERE slug = ?"),
+            libsql::params![slug],
+        )
+        .await?;
+    if let Some(row) = rows.next().await? {
+        let mut post = post_from_row(&row)?;
+        load_tags(conn, std::slice::from_mut(&mut post)).await?;
+        return Ok(Some(post));
+    }
+
+    let mut rows = conn
+        .query(
+            "SELECT post_id FROM post_slugs WHERE slug = ?",
+            libsql::params![slug],
+        )
+        .await?;
+    if let Some(row) = rows.next().await? {
+        return find_post_by_id(conn, row.get(0)?).await;
+    }
 
-    let query = if only_published {
-        "SELECT id, title, content, created_at, updated_at, published FROM posts WHERE published = TRUE ORDER BY created_at DESC"
+    match slug.parse::<i64>() {
+        Ok(id) => find_post_by_id(conn, id).await,
+        Err(_) => Ok(None),
+    }
+}
+
+/// Build a slug from the requested one (or the title) that no other post uses or used
+#[cfg(feature = "ssr")]
+async fn unique_slug(
+    conn: &libsql::Connection,
+    requested: &str,
+    title: &str,
+    post_id: Option<i64>,
+) -> Result<String> {
+    use crate::models::post::post_slug;
+
+    let base = if requested.trim().is_empty() {
+        post_slug(title)
+    } else {
+        post_slug(requested)
+    };
+
+    let mut candidate = base.clone();
+    let mut suffix = 1;
+    loop {
+        let mut rows = conn
+            .query(
+                "SELECT 1 FROM posts WHERE slug = ?1 AND id IS NOT ?2
+                 UNION ALL
+                 SELECT 1 FROM post_slugs WHERE slug = ?1 AND post_id IS NOT ?2",
+                libsql::params![candidate.clone(), post_id],
+            )
+            .await?;
+
+        // `new` would be shadowed by the new post route
+        if candidate != "new" && rows.next().await?.is_none() {
+            return Ok(candidate);
+        }
+
+        suffix += 1;
+        candidate = format!("{base}-{suffix}");
+    }
+}
+
+/// Posts from newest to oldest, optionally only those with the given tag slug
+#[cfg(feature = "ssr")]
+pub(crate) async fn list_posts(
+    conn: &libsql::Connection,
+    only_published: bool,
+    tag: Option<String>,
+) -> Result<Vec<Post>> {
+    let mut filters = Vec::new();
+    let mut params = Vec::new();
+    if only_published {
+        params.push(libsql::Value::from(chrono::Utc::now().timestamp()));
+        filters.push(visible_filter(params.len()));
+    }
+    if let Some(tag) = tag {
+        params.push(libsql::Value::from(tag));
+        filters.push(format!(
+            "id IN (
+                SELECT post_tags.post_id FROM post_tags
+                JOIN tags ON tags.id = post_tags.tag_id
+                WHERE tags.slug = ?{}
+            )",
+            params.len()
+        ));
+    }
+    let where_clause = if filters.is_empty() {
+        String::new()
     } else {
-        "SELECT id, title, content, created_at, updated_at, published FROM posts ORDER BY created_at DESC"
+        format!("WHERE {}", filters.join(" AND "))
     };
 
-    let mut rows = conn.query(query, ()).await?;
+    let query = format!("SELECT {POST_COLUMNS} FROM posts {where_clause} ORDER BY created_at DESC");
+    let mut rows = conn.query(&query, libsql::params_from_iter(params)).await?;
 
     let mut posts = Vec::new();
     while let Some(row) = rows.next().await? {
-        posts.push(Post {
-            id: row.get(0)?,
-            title: row.get(1)?,
-            content: row.get(2)?,
-            created_at: row.get::<String>(3)?.parse()?,
-            updated_at: row.get::<String>(4)?.parse()?,
-            published: row.get(5)?,
-        });
+        posts.push(post_from_row(&row)?);
     }
+    load_tags(conn, &mut posts).await?;
 
     Ok(posts)
 }
 
-#[server(GetPost, "/api/blog")]
-pub async fn get_post(id: i64) -> Result<Post> {
+/// Largest page accepted by [`get_posts`]
+#[cfg(feature = "ssr")]
+const MAX_PAGE_SIZE: u32 = 50;
+
+/// List posts from newest to oldest, one page at a time, without their full content.
+///
+/// Pages start right after the `before` cursor when given, or at the 1-based `page` number.
+#[server(GetPosts, "/api/blog", endpoint = "get_posts", client = CsrfClient)]
+pub async fn get_posts(
+    only_published: bool,
+    page_size: u32,
+    before: Option<PostCursor>,
+    page: Option<u32>,
+) -> Result<PostList> {
+    use crate::models::post::PostSummary;
+
+    ensure_can_list_drafts(only_published).await?;
+
     let conn = crate::server::utils::db::get_db();
+    let page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+
+    // Posts are ordered by creation date, ties broken by id, so that cursors are unambiguous
+    let (position_filter, offset, mut params) = match (before, page) {
+        (Some(cursor), _) => (
+            "AND (created_at < ?1 OR (created_at = ?1 AND id < ?2))",
+            0,
+            vec![
+                libsql::Value::from(cursor.created_at.to_string()),
+                libsql::Value::from(cursor.id),
+            ],
+        ),
+        (None, page) => ("", (page.unwrap_or(1).max(1) - 1) * page_size, Vec::new()),
+    };
+    let now = chrono::Utc::now().timestamp();
+    let published_filter = if only_published {
+        params.push(libsql::Value::from(now));
+        format!("AND {}", visible_filter(params.len()))
+    } else {
+        String::new()
+    };
 
+    // Fetch one extra row to know whether there is a next page
     let mut rows = conn
         .query(
-            "SELECT id, title, content, created_at, updated_at, published FROM posts WHERE id = ?",
-            libsql::params![id],
+            &format!(
+                "SELECT id, slug, title, coalesce(excerpt, auto_excerpt, ''), created_at,
+                    updated_at, published, coalesce(word_count, 0), coalesce(reading_time, 0),
+                    (SELECT username FROM users WHERE users.id = posts.author_id),
+                    (SELECT avatar_url FROM users WHERE users.id = posts.author_id)
+                 FROM posts
+                 WHERE TRUE {published_filter} {position_filter}
+                 ORDER BY created_at DESC, id DESC
+                 LIMIT {} OFFSET {offset}",
+                page_size + 1,
+            ),
+            libsql::params_from_iter(params),
         )
         .await?;
 
-    let Some(row) = rows.next().await? else {
-        return Err(ServerFnError::new("Not found"));
+    let mut posts = Vec::new();
+    while let Some(row) = rows.next().await? {
+        posts.push(PostSummary {
+            id: row.get(0)?,
+            slug: row.get(1)?,
+            title: row.get(2)?,
+            excerpt: row.get(3)?,
+            created_at: row.get::<String>(4)?.parse()?,
+            updated_at: row.get::<String>(5)?.parse()?,
+            published: row.get(6)?,
+            tags: Vec::new(),
+            word_count: row.get(7)?,
+            reading_time: row.get(8)?,
+            author: author_from_row(&row, 9)?,
+        });
+    }
+
+    let next_cursor = if posts.len() > page_size as usize {
+        posts.truncate(page_size as usize);
+        posts.last().map(|post| PostCursor {
+            created_at: post.created_at,
+            id: post.id,
+        })
+    } else {
+        None
+    };
+    load_tags(conn, &mut posts).await?;
+
+    let (has_previous, previous_cursor) = match (before, posts.first()) {
+        (None, _) => (offset > 0, None),
+        (Some(_), None) => (false, None),
+        // The previous page holds the posts right after the first one of this page. It starts
+        // after the post following them, and is the first page when there is no such post.
+        (Some(_), Some(first)) => {
+            // Same placeholders as the cursor of the main query, so that the filter fits
+            let mut params = vec![
+                libsql::Value::from(first.created_at.to_string()),
+                libsql::Value::from(first.id),
+            ];
+            if only_published {
+                params.push(libsql::Value::from(now));
+            }
+            let mut rows = conn
+                .query(
+                    &format!(
+                        "SELECT created_at, id FROM posts
+                         WHERE (created_at > ?1 OR (created_at = ?1 AND id > ?2)) {published_filter}
+                         ORDER BY created_at ASC, id ASC
+                         LIMIT {}",
+                        page_size + 1
+                    ),
+                    libsql::params_from_iter(params),
+                )
+                .await?;
+
+            let mut newer = Vec::new();
+            while let Some(row) = rows.next().await? {
+                newer.push(PostCursor {
+                    created_at: row.get::<String>(0)?.parse()?,
+                    id: row.get(1)?,
+                });
+            }
+
+            (!newer.is_empty(), newer.get(page_size as usize).copied())
+        }
     };
 
-    let post = Post {
-        id: row.get(0)?,
-        title: row.get(1)?,
-        content: row.get(2)?,
-        created_at: row.get::<String>(3)?.parse()?,
-        updated_at: row.get::<String>(4)?.parse()?,
-        published: row.get(5)?,
+    Ok(PostList {
+        posts,
+        next_cursor,
+        has_previous,
+        previous_cursor,
+    })
+}
+
+/// Get a post by slug. The returned post's slug differs from the requested one
+/// when it was resolved through the slug history or a legacy numeric id.
+#[server(GetPost, "/api/blog", client = CsrfClient)]
+pub async fn get_post(slug: String) -> Result<Post> {
+    let conn = crate::server::utils::db::get_db();
+
+    let Some(post) = find_post_by_slug(conn, &slug).await? else {
+        return Err(ServerFnError::new("Not found"));
     };
 
-    // Check if the post is published or the user is admin
-    if !post.published {
+    // Unpublished posts are only shown to those who may edit them or view drafts
+    if !post.is_visible() {
+        use crate::models::permission::Permission;
+
         let user = crate::server::utils::session::get_user_session().await?;
-        if user.is_none_or(|u| !u.is_admin) {
+        if user.is_none_or(|u| {
+            !u.can_edit_post(post.author_id) && !u.has_permission(Permission::ViewDrafts)
+        }) {
             return Err(ServerFnError::new("Not found"));
         }
     }
@@ -65,26 +420,38 @@ pub async fn get_post(id: i64) -> Result<Post> {
     Ok(post)
 }
 
-#[server(CreatePost, "/api/blog")]
+#[server(RenderPost, "/api/blog", client = CsrfClient)]
+pub async fn render_post(slug: String) -> Result<RenderedPost> {
+    use crate::server::utils::markdown::render_markdown;
+
+    let post = get_post(slug).await?;
+    let html = render_markdown(&post.content);
+
+    Ok(RenderedPost { post, html })
+}
+
+#[server(CreatePost, "/api/blog", endpoint = "create_post", client = CsrfClient)]
 pub async fn create_post(new_post: NewPost) -> Result<Post> {
-    use tracing::debug;
+    use crate::models::permission::Permission;
+    use crate::models::post::reading_time;
+    use crate::server::utils::markdown::summarize_markdown;
+    use crate::server::utils::session::require_permission;
 
-    // Check if user is admin
-    let user = crate::server::utils::session::get_user_session().await?;
-    if user.as_ref().is_none_or(|u| !u.is_admin) {
-        debug!("Forbidden for user: {:?}", &user);
-        return Err(ServerFnError::new("Forbidden"));
-    }
+    let user = require_permission(Permission::CreatePosts).await?;
 
     let conn = crate::server::utils::db::get_db();
 
     // Get current timestamp
     let now = chrono::Utc::now();
 
-    // Insert the new post
+    let slug = unique_slug(conn, &new_post.slug, &new_post.title, None).await?;
+    let summary = summarize_markdown(&new_post.content);
+    let publish_at = publish_time(new_post.published, new_post.publish_at, None, now);
+
+    // Insert the new post, the scheduler publishes it when its time has come
     let mut rows = conn.query(
-        "INSERT INTO posts (title, content, created_at, updated_at, published) VALUES (?, ?, ?, ?, ?) RETURNING id",
-        libsql::params![new_post.title.clone(), new_post.content.clone(), now.to_string(), now.to_string(), new_post.published]
+        "INSERT INTO posts (slug, title, content, created_at, updated_at, published, publish_at, excerpt, auto_excerpt, word_count, reading_time, author_id) VALUES (?, ?, ?, ?, ?, FALSE, ?, ?, ?, ?, ?, ?) RETURNING id",
+        libsql::params![slug.clone(), new_post.title.clone(), new_post.content.clone(), now.to_string(), now.to_string(), publish_at.map(format_publish_at), author_excerpt(new_post.excerpt.clone()), summary.excerpt, summary.word_count, reading_time(summary.word_count), user.id]
     ).await?;
 
     let Some(row) = rows.next().await? else {
@@ -93,70 +460,122 @@ pub async fn create_post(new_post: NewPost) -> Result<Post> {
 
     let id = row.get(0)?;
 
-    // Return the created post
-    let post = Post {
-        id,
-        title: new_post.title,
-        content: new_post.content,
-        created_at: now,
-        updated_at: now,
-        published: new_post.published,
-    };
+    set_post_tags(conn, id, &new_post.tags).await?;
+    publish_due_posts(conn).await?;
+    schedule_changed();
+    record_revision(conn, id, user.id).await?;
 
-    Ok(post)
+    // Return the created post
+    find_post_by_id(conn, id)
+        .await?
+        .ok_or_else(|| ServerFnError::new("Failed to insert post"))
 }
 
-#[server(UpdatePost, "/api/blog")]
+#[server(UpdatePost, "/api/blog", endpoint = "update_post", client = CsrfClient)]
 pub async fn update_post(update: UpdatePostData) -> Result<Post> {
-    // Check if user is admin
-    let user = crate::server::utils::session::get_user_session().await?;
-    if user.is_none_or(|u| !u.is_admin) {
-        return Err(ServerFnError::new("Forbidden"));
-    }
+    use crate::models::post::reading_time;
+    use crate::server::utils::markdown::summarize_markdown;
 
     let conn = crate::server::utils::db::get_db();
 
+    let Some(existing) = find_post_by_id(conn, update.id).await? else {
+        return Err(ServerFnError::new("Not found"));
+    };
+    let user = ensure_can_edit_post(&existing).await?;
+
     // Get current timestamp
     let now = chrono::Utc::now();
 
-    // Update the post
-    let result = conn
-        .execute(
-            "UPDATE posts SET title = ?, content = ?, updated_at = ?, published = ? WHERE id = ?",
-            libsql::params![
-                update.title,
-                update.content,
-                now.to_string(),
-                update.published,
-                update.id
-            ],
+    let slug = unique_slug(conn, &update.slug, &update.title, Some(update.id)).await?;
+    let summary = summarize_markdown(&update.content);
+    let publish_at = publish_time(update.published, update.publish_at, Some(&existing), now);
+    // Posts going live again, e.g. after being rescheduled, run the publish hooks again
+    let still_published = existing.published && publish_at == existing.publish_at;
+
+    // Keep the previous slug so that links to it redirect to the new one
+    if slug != existing.slug {
+        conn.execute(
+            "INSERT OR REPLACE INTO post_slugs (slug, post_id) VALUES (?, ?)",
+            libsql::params![existing.slug, update.id],
+        )
+        .await?;
+        conn.execute(
+            "DELETE FROM post_slugs WHERE slug = ?",
+            libsql::params![slug.clone()],
         )
         .await?;
-
-    if result == 0 {
-        return Err(ServerFnError::new("Not found"));
     }
 
+    // Update the post
+    conn.execute(
+        "UPDATE posts SET slug = ?, title = ?, content = ?, updated_at = ?, published = ?, publish_at = ?, excerpt = ?, auto_excerpt = ?, word_count = ?, reading_time = ? WHERE id = ?",
+        libsql::params![
+            slug,
+            update.title,
+            update.content,
+            now.to_string(),
+            still_published,
+            publish_at.map(format_publish_at),
+            author_excerpt(update.excerpt),
+            summary.excerpt,
+            summary.word_count,
+            reading_time(summary.word_count),
+            update.id
+        ],
+    )
+    .await?;
+
+    set_post_tags(conn, update.id, &update.tags).await?;
+    publish_due_posts(conn).await?;
+    schedule_changed();
+    record_revision(conn, update.id, user.id).await?;
+
     // Get the updated post
-    get_post(update.id).await
+    find_post_by_id(conn, update.id)
+        .await?
+        .ok_or_else(|| ServerFnError::new("Not found"))
 }
 
-#[server(DeletePost, "/api/blog")]
-pub async fn delete_post(id: i64) -> Result<()> {
-    // Check if user is admin
-    let user = crate::server::utils::session::get_user_session().await?;
-    if user.is_none_or(|u| !u.is_admin) {
-        return Err(ServerFnError::new("Forbidden"));
-    }
-
-    let conn = crate::server::utils::db::get_db();
+/// Delete a post along with its slug history, revisions and tags.
+/// Returns whether the post existed.
+#[cfg(feature = "ssr")]
+pub(crate) async fn remove_post(conn: &libsql::Connection, id: i64) -> Result<bool> {
+    use super::tags::set_post_tags;
 
-    // Delete the post
     let result = conn
         .execute("DELETE FROM posts WHERE id = ?", libsql::params![id])
         .await?;
 
     if result == 0 {
+        return Ok(false);
+    }
+
+    conn.execute(
+        "DELETE FROM post_slugs WHERE post_id = ?",
+        libsql::params![id],
+    )
+    .await?;
+    conn.execute(
+        "DELETE FROM post_revisions WHERE post_id = ?",
+        libsql::params![id],
+    )
+    .await?;
+    set_post_tags(conn, id, &[]).await?;
+
+    Ok(true)
+}
+
+#[server(DeletePost, "/api/blog", endpoint = "delete_post", client = CsrfClient)]
+pub async fn delete_post(id: i64) -> Result<()> {
+    let conn = crate::server::utils::db::get_db();
+
+    let Some(post) = find_post_by_id(conn, id).await? else {
+        return Err(ServerFnError::new("Not found"));
+    };
+    ensure_can_edit_post(&post).await?;
+
+    // Delete the post
+    if !remove_post(conn, id).await? {
         return Err(ServerFnError::new("Not found"));
     }
 
@@ -0,0 +1,60 @@
+//! Calls to server functions carry the CSRF token the shell puts in the page, or the one the
+//! server renewed in a cookie since, which the server checks to refuse calls made by other sites
+
+use leptos::server_fn::client::{browser::BrowserClient, Client};
+use leptos::server_fn::error::ServerFnError;
+use leptos::server_fn::request::browser::BrowserRequest;
+use leptos::server_fn::response::browser::BrowserResponse;
+use std::future::Future;
+
+/// Header server function calls send the CSRF token in
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// Name of the meta tag holding the CSRF token of the page
+pub const CSRF_META: &str = "csrf-token";
+
+/// Name of the cookie holding the CSRF token, renewed when users log in or out
+pub const CSRF_COOKIE: &str = "csrf-token";
+
+/// Sends server function calls from the browser with the CSRF token of the page
+pub struct CsrfClient;
+
+impl<E> Client<E> for CsrfClient {
+    type Request = BrowserRequest;
+    type Response = BrowserResponse;
+
+    fn send(
+        req: BrowserRequest,
+    ) -> impl Future<Output = Result<BrowserResponse, ServerFnError<E>>> + Send {
+        #[cfg(feature = "hydrate")]
+        if let Some(token) = page_token() {
+            req.headers().set(CSRF_HEADER, &token);
+        }
+
+        <BrowserClient as Client<E>>::send(req)
+    }
+}
+
+/// The token of the cookie, which is the latest one, or else the one the page was rendered with
+#[cfg(feature = "hydrate")]
+fn page_token() -> Option<String> {
+    use wasm_bindgen::JsCast;
+
+    let document = leptos::prelude::document();
+    document
+        .dyn_ref::<web_sys::HtmlDocument>()
+        .and_then(|document| document.cookie().ok())
+        .and_then(|cookies| {
+            cookies.split("; ").find_map(|cookie| {
+                let (name, value) = cookie.split_once('=')?;
+                (name.trim_start_matches("__Host-") == CSRF_COOKIE).then(|| value.to_string())
+            })
+        })
+        .or_else(|| {
+            document
+                .query_selector(&format!("meta[name=\"{CSRF_META}\"]"))
+                .ok()
+                .flatten()
+                .and_then(|meta| meta.get_attribute("content"))
+        })
+}
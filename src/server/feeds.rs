@@ -0,0 +1,464 @@
+use atom_syndication as atom;
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use chrono::{DateTime, Utc};
+use leptos::prelude::*;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::env;
+use tracing::error;
+
+use crate::models::post::Post;
+use crate::server::blog::list_posts;
+use crate::server::utils::db::get_db;
+use crate::server::utils::markdown::render_markdown;
+
+type Result<T> = std::result::Result<T, ServerFnError>;
+
+const SITE_TITLE: &str = "Léo Coletta";
+const SITE_DESCRIPTION: &str = "Articles by Léo Coletta, software engineer";
+const SITE_AUTHOR: &str = "Léo Coletta";
+
+/// Number of most recent posts included in a feed
+const FEED_LENGTH: usize = 20;
+
+#[derive(Debug, Clone, Copy)]
+enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
+
+impl FeedFormat {
+    const ALL: [FeedFormat; 3] = [FeedFormat::Rss, FeedFormat::Atom, FeedFormat::Json];
+
+    fn file_name(self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "feed.xml",
+            FeedFormat::Atom => "atom.xml",
+            FeedFormat::Json => "feed.json",
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "application/rss+xml; charset=utf-8",
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+            FeedFormat::Json => "application/feed+json; charset=utf-8",
+        }
+    }
+}
+
+struct FeedEntry {
+    post: Post,
+    url: String,
+    html: String,
+}
+
+struct Feed {
+    title: String,
+    home_url: String,
+    feed_url: String,
+    updated: DateTime<Utc>,
+    entries: Vec<FeedEntry>,
+}
+
+/// Routes serving the site wide feeds and the per-tag feeds, in RSS 2.0, Atom and JSON Feed 1.1
+pub fn feed_routes() -> Router<LeptosOptions> {
+    let mut router = Router::new();
+
+    for format in FeedFormat::ALL {
+        router = router
+            .route(
+                &format!("/{}", format.file_name()),
+                get(
+                    move |State(options): State<LeptosOptions>, headers: HeaderMap| {
+                        serve_feed(options, headers, format, None)
+                    },
+                ),
+            )
+            .route(
+                &format!("/tags/:tag/{}", format.file_name()),
+                get(
+                    move |State(options): State<LeptosOptions>,
+                          Path(tag): Path<String>,
+                          headers: HeaderMap| {
+                        serve_feed(options, headers, format, Some(tag))
+                    },
+                ),
+            );
+    }
+
+    router
+}
+
+/// Public base URL of the site, used for absolute links in feeds and emails
+pub fn site_url(options: &LeptosOptions) -> String {
+    env::var("SITE_URL")
+        .map(|url| url.trim_end_matches('/').to_string())
+        .unwrap_or_else(|_| format!("http://{}", options.site_addr))
+}
+
+async fn serve_feed(
+    options: LeptosOptions,
+    headers: HeaderMap,
+    format: FeedFormat,
+    tag: Option<String>,
+) -> Response {
+    let feed = match load_feed(&site_url(&options), format, tag).await {
+        Ok(Some(feed)) => feed,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            error!("Failed to build {} feed: {e}", format.file_name());
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let body = match format {
+        FeedFormat::Rss => render_rss(&feed),
+        FeedFormat::Atom => render_atom(&feed),
+        FeedFormat::Json => render_json(&feed),
+    };
+
+    let etag = format!("\"{:x}\"", Sha256::digest(body.as_bytes()));
+    let last_modified = feed.updated.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+    let validators = [
+        (header::ETAG, etag.clone()),
+        (header::LAST_MODIFIED, last_modified),
+    ];
+
+    if is_not_modified(&headers, &etag, feed.updated) {
+        return (StatusCode::NOT_MODIFIED, validators).into_response();
+    }
+
+    (
+        validators,
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(format.content_type()),
+        )],
+        body,
+    )
+        .into_response()
+}
+
+/// Check the conditional request headers against the feed validators.
+/// `If-None-Match` takes precedence over `If-Modified-Since`, as per RFC 9110.
+fn is_not_modified(headers: &HeaderMap, etag: &str, updated: DateTime<Utc>) -> bool {
+    if let Some(if_none_match) = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+    {
+        return if_none_match
+            .split(',')
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == "*" || tag == etag);
+    }
+
+    headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+        .is_some_and(|since| updated.timestamp() <= since.timestamp())
+}
+
+/// Load the published posts of a feed, or `None` when the tag doesn't exist
+async fn load_feed(
+    site_url: &str,
+    format: FeedFormat,
+    tag: Option<String>,
+) -> Result<Option<Feed>> {
+    let conn = get_db();
+
+    let (title, home_url, feed_url) = match &tag {
+        Some(tag) => {
+            let mut rows = conn
+                .query(
+                    "SELECT name FROM tags WHERE slug = ?",
+                    libsql::params![tag.clone()],
+                )
+                .await?;
+            let Some(row) = rows.next().await? else {
+                return Ok(None);
+            };
+            let name: String = row.get(0)?;
+
+            (
+                format!("{SITE_TITLE} - {name}"),
+                format!("{site_url}/tags/{tag}"),
+                format!("{site_url}/tags/{tag}/{}", format.file_name()),
+            )
+        }
+        None => (
+            SITE_TITLE.to_string(),
+            format!("{site_url}/blog"),
+            format!("{site_url}/{}", format.file_name()),
+        ),
+    };
+
+    let mut posts = list_posts(conn, true, tag).await?;
+    posts.truncate(FEED_LENGTH);
+
+    let updated = posts
+        .iter()
+        .map(|post| post.updated_at)
+        .max()
+        .unwrap_or(DateTime::UNIX_EPOCH);
+
+    let entries = posts
+        .into_iter()
+        .map(|post| FeedEntry {
+            url: format!("{site_url}/blog/{}", post.slug),
+            html: render_markdown(&post.content),
+            post,
+        })
+        .collect();
+
+    Ok(Some(Feed {
+        title,
+        home_url,
+        feed_url,
+        updated,
+        entries,
+    }))
+}
+
+fn render_rss(feed: &Feed) -> String {
+    let items = feed
+        .entries
+        .iter()
+        .map(|entry| {
+            rss::ItemBuilder::default()
+                .title(entry.post.title.clone())
+                .link(entry.url.clone())
+                .guid(
+                    rss::GuidBuilder::default()
+                        .value(entry.url.clone())
+                        .permalink(true)
+                        .build(),
+                )
+                .description(entry.post.summary().to_string())
+                .pub_date(entry.post.created_at.to_rfc2822())
+                .categories(
+                    entry
+                        .post
+                        .tags
+                        .iter()
+                        .map(|tag| {
+                            rss::CategoryBuilder::default()
+                                .name(tag.name.clone())
+                                .build()
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                // Emitted as `content:encoded`
+                .content(entry.html.clone())
+                .build()
+        })
+        .collect::<Vec<_>>();
+
+    rss::ChannelBuilder::default()
+        .namespaces([(
+            "content".to_string(),
+            "http://purl.org/rss/1.0/modules/content/".to_string(),
+        )])
+        .title(feed.title.clone())
+        .link(feed.home_url.clone())
+        .description(SITE_DESCRIPTION)
+        .last_build_date(feed.updated.to_rfc2822())
+        .items(items)
+        .build()
+        .to_string()
+}
+
+fn render_atom(feed: &Feed) -> String {
+    let entries = feed
+        .entries
+        .iter()
+        .map(|entry| {
+            atom::EntryBuilder::default()
+                .id(entry.url.clone())
+                .title(entry.post.title.clone())
+                .published(Some(entry.post.created_at.fixed_offset()))
+                .updated(entry.post.updated_at.fixed_offset())
+                .summary(Some(atom::Text::plain(entry.post.summary())))
+                .link(
+                    atom::LinkBuilder::default()
+                        .href(entry.url.clone())
+                        .rel("alternate")
+                        .build(),
+                )
+                .categories(
+                    entry
+                        .post
+                        .tags
+                        .iter()
+                        .map(|tag| {
+                            atom::CategoryBuilder::default()
+                                .term(tag.slug.clone())
+                                .label(Some(tag.name.clone()))
+                                .build()
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .content(Some(
+                    atom::ContentBuilder::default()
+                        .content_type(Some("html".to_string()))
+                        .value(Some(entry.html.clone()))
+                        .build(),
+                ))
+                .build()
+        })
+        .collect::<Vec<_>>();
+
+    atom::FeedBuilder::default()
+        .id(feed.feed_url.clone())
+        .title(feed.title.clone())
+        .subtitle(Some(SITE_DESCRIPTION.into()))
+        .updated(feed.updated.fixed_offset())
+        .author(atom::PersonBuilder::default().name(SITE_AUTHOR).build())
+        .links(vec![
+            atom::LinkBuilder::default()
+                .href(feed.feed_url.clone())
+                .rel("self")
+                .build(),
+            atom::LinkBuilder::default()
+                .href(feed.home_url.clone())
+                .rel("alternate")
+                .build(),
+        ])
+        .entries(entries)
+        .build()
+        .to_string()
+}
+
+/// JSON Feed 1.1, see <https://www.jsonfeed.org/version/1.1/>
+#[derive(Serialize)]
+struct JsonFeed<'a> {
+    version: &'static str,
+    title: &'a str,
+    home_page_url: &'a str,
+    feed_url: &'a str,
+    description: &'static str,
+    authors: [JsonFeedAuthor; 1],
+    items: Vec<JsonFeedItem<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonFeedAuthor {
+    name: &'static str,
+}
+
+#[derive(Serialize)]
+struct JsonFeedItem<'a> {
+    id: &'a str,
+    url: &'a str,
+    title: &'a str,
+    content_html: &'a str,
+    summary: &'a str,
+    date_published: String,
+    date_modified: String,
+    tags: Vec<&'a str>,
+}
+
+fn render_json(feed: &Feed) -> String {
+    let json_feed = JsonFeed {
+        version: "https://jsonfeed.org/version/1.1",
+        title: &feed.title,
+        home_page_url: &feed.home_url,
+        feed_url: &feed.feed_url,
+        description: SITE_DESCRIPTION,
+        authors: [JsonFeedAuthor { name: SITE_AUTHOR }],
+        items: feed
+            .entries
+            .iter()
+            .map(|entry| JsonFeedItem {
+                id: &entry.url,
+                url: &entry.url,
+                title: &entry.post.title,
+                content_html: &entry.html,
+                summary: entry.post.summary(),
+                date_published: entry.post.created_at.to_rfc3339(),
+                date_modified: entry.post.updated_at.to_rfc3339(),
+                tags: entry
+                    .post
+                    .tags
+                    .iter()
+                    .map(|tag| tag.name.as_str())
+                    .collect(),
+            })
+            .collect(),
+    };
+
+    serde_json::to_string(&json_feed).expect("JSON feed serialization cannot fail")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ETAG: &str = "\"abc\"";
+
+    fn updated() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z")
+            .unwrap()
+            .to_utc()
+    }
+
+    fn headers(pairs: &[(header::HeaderName, &str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.clone(), HeaderValue::from_str(value).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn matching_etags_are_not_modified() {
+        for if_none_match in ["\"abc\"", "W/\"abc\"", "\"other\", \"abc\"", "*"] {
+            let headers = headers(&[(header::IF_NONE_MATCH, if_none_match)]);
+            assert!(
+                is_not_modified(&headers, ETAG, updated()),
+                "{if_none_match}"
+            );
+        }
+
+        let headers = headers(&[(header::IF_NONE_MATCH, "\"other\"")]);
+        assert!(!is_not_modified(&headers, ETAG, updated()));
+    }
+
+    #[test]
+    fn feeds_are_not_modified_since_their_update() {
+        for (since, not_modified) in [
+            ("Wed, 01 May 2024 12:00:00 GMT", true),
+            ("Thu, 02 May 2024 00:00:00 GMT", true),
+            ("Wed, 01 May 2024 11:59:59 GMT", false),
+            ("yesterday", false),
+        ] {
+            let headers = headers(&[(header::IF_MODIFIED_SINCE, since)]);
+            assert_eq!(
+                is_not_modified(&headers, ETAG, updated()),
+                not_modified,
+                "{since}"
+            );
+        }
+    }
+
+    #[test]
+    fn etags_take_precedence_over_dates() {
+        let headers = headers(&[
+            (header::IF_NONE_MATCH, "\"other\""),
+            (header::IF_MODIFIED_SINCE, "Thu, 02 May 2024 00:00:00 GMT"),
+        ]);
+        assert!(!is_not_modified(&headers, ETAG, updated()));
+    }
+
+    #[test]
+    fn unconditional_requests_are_modified() {
+        assert!(!is_not_modified(&HeaderMap::new(), ETAG, updated()));
+    }
+}
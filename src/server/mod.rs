@@ -1,5 +1,21 @@
+pub mod admin;
 pub mod auth;
+pub mod authors;
 pub mod blog;
+pub mod csrf;
+#[cfg(feature = "ssr")]
+pub mod feeds;
+pub mod oidc;
+pub mod password_reset;
+pub mod revisions;
+pub mod roles;
+#[cfg(feature = "ssr")]
+pub mod scheduler;
+pub mod search;
 pub mod session;
 #[cfg(feature = "ssr")]
+pub mod session_gc;
+pub mod tags;
+pub mod two_factor;
+#[cfg(feature = "ssr")]
 pub mod utils;
@@ -0,0 +1,340 @@
+use leptos::prelude::*;
+
+use crate::models::oidc::{IdentityInfo, OidcLoginResponse, OidcProviderInfo};
+use crate::server::csrf::CsrfClient;
+
+type Result<T> = std::result::Result<T, ServerFnError>;
+
+/// Cookie tying a login to the browser that started it, so that nobody can make a visitor
+/// finish a login they started themselves
+#[cfg(feature = "ssr")]
+const STATE_COOKIE: &str = "oidc_state";
+
+/// Providers users can log in with
+#[server(GetOidcProviders, "/api/oidc", client = CsrfClient)]
+pub async fn get_oidc_providers() -> Result<Vec<OidcProviderInfo>> {
+    use super::utils::oidc::providers;
+
+    Ok(providers()
+        .iter()
+        .map(|provider| OidcProviderInfo {
+            id: provider.id.clone(),
+            name: provider.name.clone(),
+        })
+        .collect())
+}
+
+/// Start a login with `provider`, or linking an account there when the user is logged in.
+/// Returns the URL of the login page of the provider to send the user to.
+#[server(StartOidcLogin, "/api/oidc", endpoint = "start", client = CsrfClient)]
+pub async fn start_oidc_login(
+    provider: String,
+    #[server(default)] remember: bool,
+) -> Result<String> {
+    use axum::http::header::SET_COOKIE;
+    use leptos_axum::ResponseOptions;
+    use openidconnect::core::CoreAuthenticationFlow;
+    use openidconnect::{CsrfToken, Nonce, PkceCodeChallenge, Scope};
+
+    use super::utils::db::get_db;
+    use super::utils::oidc::{
+        client, provider as find_provider, save_login, PendingLogin, LOGIN_LIFETIME_SECS,
+    };
+    use super::utils::session;
+
+    let provider = find_provider(&provider)?;
+    let link_user_id = session::get_user_session().await?.and_then(|user| user.id);
+
+    let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+    let (url, state, nonce) = client(provider)
+        .await?
+        .authorize_url(
+            CoreAuthenticationFlow::AuthorizationCode,
+            CsrfToken::new_random,
+            Nonce::new_random,
+        )
+        .add_scope(Scope::new("email".to_string()))
+        .add_scope(Scope::new("profile".to_string()))
+        .set_pkce_challenge(pkce_challenge)
+        .url();
+
+    save_login(
+        get_db(),
+        state.secret(),
+        &PendingLogin {
+            provider: provider.id.clone(),
+            nonce: nonce.secret().clone(),
+            pkce_verifier: pkce_verifier.secret().clone(),
+            link_user_id,
+            remember,
+        },
+    )
+    .await?;
+
+    // Lax, the user comes back from the provider through a cross-site navigation
+    let cookie = session::lax_cookie(
+        STATE_COOKIE,
+        state.secret(),
+        Some(LOGIN_LIFETIME_SECS),
+        "/api/oidc",
+    )?;
+    expect_context::<ResponseOptions>().append_header(SET_COOKIE, cookie);
+
+    Ok(url.to_string())
+}
+
+/// Username for an account created by a login with a provider: the one the provider suggests,
+/// with a number added when it is taken
+#[cfg(feature = "ssr")]
+async fn available_username(conn: &libsql::Connection, suggested: &str) -> Result<String> {
+    let base: String = suggested.trim().chars().take(32).collect();
+    let base = if base.is_empty() {
+        "user".to_string()
+    } else {
+        base
+    };
+
+    let mut username = base.clone();
+    for n in 2.. {
+        let mut rows = conn
+            .query(
+                "SELECT 1 FROM users WHERE username = ?",
+                libsql::params![username.clone()],
+            )
+            .await?;
+        if rows.next().await?.is_none() {
+            break;
+        }
+        username = format!("{base}-{n}");
+    }
+
+    Ok(username)
+}
+
+/// Finish a login with the `state` and `code` the provider sent the user back with.
+///
+/// An account at a provider seen for the first time gets a new user with a random password,
+/// unless a logged in user started the login to link it. Users are never matched by email
+/// address, providers don't all make sure that it belongs to the account.
+#[server(FinishOidcLogin, "/api/oidc", endpoint = "finish", client = CsrfClient)]
+pub async fn finish_oidc_login(state: String, code: String) -> Result<OidcLoginResponse> {
+    use axum_extra::extract::CookieJar;
+    use openidconnect::{AuthorizationCode, Nonce, PkceCodeVerifier, TokenResponse};
+
+    use super::auth::{create_user, load_user, normalize_email};
+    use super::roles::DEFAULT_ROLE;
+    use super::two_factor::start_login_challenge;
+    use super::utils::crypto::random_token;
+    use super::utils::db::get_db;
+    use super::utils::oidc::{client, http_client, oidc_error, provider, take_login};
+    use super::utils::session;
+
+    // The cookie is left to expire, the login it points to can only be finished once
+    let cookies: CookieJar = leptos_axum::extract().await?;
+    if cookies
+        .get(&session::cookie_name(STATE_COOKIE))
+        .is_none_or(|cookie| cookie.value() != state)
+    {
+        return Err(ServerFnError::new(
+            "This login was started in another browser, please try again",
+        ));
+    }
+
+    let conn = get_db();
+    let login = take_login(conn, &state).await?;
+    let provider = provider(&login.provider)?;
+    let client = client(provider).await?;
+
+    let token = client
+        .exchange_code(AuthorizationCode::new(code))
+        .map_err(|e| oidc_error(provider, e))?
+        .set_pkce_verifier(PkceCodeVerifier::new(login.pkce_verifier))
+        .request_async(http_client())
+        .await
+        .map_err(|e| oidc_error(provider, e))?;
+    let Some(id_token) = token.id_token() else {
+        return Err(oidc_error(provider, "no ID token in the token response"));
+    };
+    let claims = id_token
+        .claims(&client.id_token_verifier(), &Nonce::new(login.nonce))
+        .map_err(|e| oidc_error(provider, e))?;
+
+    let subject = claims.subject().to_string();
+    // Only kept when the provider checked that it belongs to the account
+    let email = claims
+        .email()
+        .filter(|_| claims.email_verified() == Some(true))
+        .and_then(|email| normalize_email(Some(email.to_string())).ok().flatten());
+
+    let mut rows = conn
+        .query(
+            "SELECT user_id FROM identities WHERE provider = ? AND subject = ?",
+            libsql::params![provider.id.clone(), subject.clone()],
+        )
+        .await?;
+    let linked_user_id = match rows.next().await? {
+        Some(row) => Some(row.get::<i64>(0)?),
+        None => None,
+    };
+
+    if let Some(user_id) = login.link_user_id {
+        match linked_user_id {
+            Some(linked) if linked != user_id => {
+                return Err(ServerFnError::new(format!(
+                    "This {} account is already linked to another user",
+                    provider.name
+                )));
+            }
+            Some(_) => {}
+            None => {
+                conn.execute(
+                    "INSERT INTO identities (provider, subject, user_id, email) VALUES (?, ?, ?, ?)",
+                    libsql::params![provider.id.clone(), subject, user_id, email],
+                )
+                .await?;
+            }
+        }
+
+        return Ok(OidcLoginResponse::Linked);
+    }
+
+    let user_id = match linked_user_id {
+        Some(user_id) => {
+            conn.execute(
+                "UPDATE identities SET email = ?, last_used_at = CURRENT_TIMESTAMP
+                 WHERE provider = ? AND subject = ?",
+                libsql::params![email, provider.id.clone(), subject],
+            )
+            .await?;
+            user_id
+        }
+        None => {
+            let suggested = claims
+                .preferred_username()
+                .map(|username| username.to_string())
+                .or_else(|| {
+                    claims
+                        .name()
+                        .and_then(|name| name.get(None))
+                        .map(|name| name.to_string())
+                })
+                .or_else(|| {
+                    email
+                        .as_ref()
+                        .and_then(|email| email.split('@').next().map(str::to_string))
+                })
+                .unwrap_or_default();
+            let username = available_username(conn, &suggested).await?;
+
+            // Another user may already have the address
+            let mut rows = conn
+                .query(
+                    "SELECT 1 FROM users WHERE email = ?",
+                    libsql::params![email.clone()],
+                )
+                .await?;
+            let user_email = match rows.next().await? {
+                Some(_) => None,
+                None => email.clone(),
+            };
+
+            let tx = conn.transaction().await?;
+            let user_id =
+                create_user(&tx, username, random_token(), user_email, DEFAULT_ROLE).await?;
+            tx.execute(
+                "INSERT INTO identities (provider, subject, user_id, email, last_used_at)
+                 VALUES (?, ?, ?, ?, CURRENT_TIMESTAMP)",
+                libsql::params![provider.id.clone(), subject, user_id, email],
+            )
+            .await?;
+            tx.commit().await?;
+            user_id
+        }
+    };
+
+    let mut rows = conn
+        .query(
+            "SELECT disabled, totp_enabled FROM users WHERE id = ?",
+            libsql::params![user_id],
+        )
+        .await?;
+    let Some(row) = rows.next().await? else {
+        return Err(ServerFnError::new("Not found"));
+    };
+    if row.get::<bool>(0)? {
+        return Err(ServerFnError::new("This account is disabled"));
+    }
+    if row.get::<bool>(1)? {
+        let challenge = start_login_challenge(conn, user_id, login.remember).await?;
+        return Ok(OidcLoginResponse::TwoFactorRequired { challenge });
+    }
+
+    let Some(user) = load_user(conn, user_id).await? else {
+        return Err(ServerFnError::new("Not found"));
+    };
+    session::start_session(&user.get_session_user(), login.remember).await?;
+
+    Ok(OidcLoginResponse::LoggedIn(user))
+}
+
+/// Accounts at providers linked to the current user
+#[server(GetIdentities, "/api/oidc", client = CsrfClient)]
+pub async fn get_identities() -> Result<Vec<IdentityInfo>> {
+    use super::utils::db::{get_db, parse_sqlite_datetime};
+    use super::utils::oidc::provider;
+    use super::utils::session;
+
+    let Some(user_id) = session::get_user_session().await?.and_then(|user| user.id) else {
+        return Err(ServerFnError::new("Unauthorized"));
+    };
+
+    let mut rows = get_db()
+        .query(
+            "SELECT provider, subject, email, created_at, last_used_at FROM identities
+             WHERE user_id = ? ORDER BY created_at",
+            libsql::params![user_id],
+        )
+        .await?;
+
+    let mut identities = Vec::new();
+    while let Some(row) = rows.next().await? {
+        let id: String = row.get(0)?;
+        identities.push(IdentityInfo {
+            provider_name: provider(&id).map_or_else(|_| id.clone(), |p| p.name.clone()),
+            provider: id,
+            subject: row.get(1)?,
+            email: row.get(2)?,
+            created_at: parse_sqlite_datetime(&row.get::<String>(3)?)?,
+            last_used_at: row
+                .get::<Option<String>>(4)?
+                .as_deref()
+                .map(parse_sqlite_datetime)
+                .transpose()?,
+        });
+    }
+
+    Ok(identities)
+}
+
+/// Remove an account at a provider from the current user
+#[server(UnlinkIdentity, "/api/oidc", client = CsrfClient)]
+pub async fn unlink_identity(provider: String, subject: String) -> Result<()> {
+    use super::utils::db::get_db;
+    use super::utils::session;
+
+    let Some(user_id) = session::get_user_session().await?.and_then(|user| user.id) else {
+        return Err(ServerFnError::new("Unauthorized"));
+    };
+
+    let deleted = get_db()
+        .execute(
+            "DELETE FROM identities WHERE provider = ? AND subject = ? AND user_id = ?",
+            libsql::params![provider, subject, user_id],
+        )
+        .await?;
+    if deleted == 0 {
+        return Err(ServerFnError::new("Not found"));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use crate::server::csrf::CsrfClient;
+use leptos::prelude::*;
+
+type Result<T> = std::result::Result<T, ServerFnError>;
+
+/// How long a password reset link stays valid
+#[cfg(feature = "ssr")]
+const TOKEN_LIFETIME_SECS: i64 = 60 * 60;
+
+/// Email a password reset link to the user with this email address. Succeeds whether or
+/// not such a user exists, so that it can't be used to find out who has an account.
+#[server(RequestPasswordReset, "/api/password-reset", client = CsrfClient)]
+pub async fn request_password_reset(email: String) -> Result<()> {
+    use super::utils::crypto::{random_token, sha256_hex};
+    use super::utils::db::get_db;
+    use super::utils::mail::{send_mail, site_link, Mail};
+
+    let email = email.trim().to_string();
+    if email.is_empty() {
+        return Err(ServerFnError::new("Email is required"));
+    }
+
+    let conn = get_db();
+    let mut rows = conn
+        .query(
+            "SELECT id, username, email FROM users
+             WHERE email = ? COLLATE NOCASE AND disabled = FALSE",
+            libsql::params![email],
+        )
+        .await?;
+    let Some(row) = rows.next().await? else {
+        return Ok(());
+    };
+    let user_id: i64 = row.get(0)?;
+    let username: String = row.get(1)?;
+    let email: String = row.get(2)?;
+
+    let token = random_token();
+    let expires = chrono::Utc::now().timestamp() + TOKEN_LIFETIME_SECS;
+
+    // Only the latest link works
+    conn.execute(
+        "DELETE FROM password_reset_tokens WHERE user_id = ?",
+        libsql::params![user_id],
+    )
+    .await?;
+    conn.execute(
+        "INSERT INTO password_reset_tokens (token_hash, user_id, expires)
+         VALUES (?, ?, datetime(?, 'unixepoch'))",
+        libsql::params![sha256_hex(&token), user_id, expires],
+    )
+    .await?;
+
+    let mail = Mail {
+        to: email,
+        subject: "Reset your password".to_string(),
+        body: format!(
+            "Hi {username},\n\n\
+             Someone asked to reset the password of your account. If it was you, follow this \
+             link within the hour to choose a new one:\n\n{}\n\n\
+             Otherwise you can ignore this email.",
+            site_link(&format!("/reset-password?token={token}"))
+        ),
+    };
+    // Sent in the background, so that the response time doesn't tell whether the user exists
+    tokio::spawn(async move {
+        if let Err(e) = send_mail(&mail).await {
+            tracing::error!("Failed to send password reset email: {e}");
+        }
+    });
+
+    Ok(())
+}
+
+/// Set a new password using the token of a reset link. The token can only be used once,
+/// and every session of the user is revoked.
+#[server(ResetPassword, "/api/password-reset", client = CsrfClient)]
+pub async fn reset_password(token: String, password: String) -> Result<()> {
+    use super::auth::hash_password;
+    use super::utils::crypto::sha256_hex;
+    use super::utils::db::get_db;
+
+    let password_hash = hash_password(&password)?;
+    let now = chrono::Utc::now().timestamp();
+
+    let conn = get_db();
+    let tx = conn.transaction().await?;
+
+    // Deleting the token as it is checked makes it single-use
+    let mut rows = tx
+        .query(
+            "DELETE FROM password_reset_tokens
+             WHERE token_hash = ? AND expires > datetime(?, 'unixepoch')
+             RETURNING user_id",
+            libsql::params![sha256_hex(&token), now],
+        )
+        .await?;
+    // Read until the end, the statement must be done before committing
+    let mut user_ids = Vec::new();
+    while let Some(row) = rows.next().await? {
+        user_ids.push(row.get::<i64>(0)?);
+    }
+    let Some(&user_id) = user_ids.first() else {
+        tx.rollback().await?;
+        return Err(ServerFnError::new(
+            "This reset link is invalid or has expired",
+        ));
+    };
+
+    tx.execute(
+        "UPDATE users SET password_hash = ? WHERE id = ?",
+        libsql::params![password_hash, user_id],
+    )
+    .await?;
+    for query in [
+        "DELETE FROM password_reset_tokens WHERE user_id = ?",
+        "DELETE FROM sessions WHERE user_id = ?",
+    ] {
+        tx.execute(query, libsql::params![user_id]).await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use crate::models::post::Post;
+use crate::models::revision::{PostHistory, RevisionDiff};
+use crate::server::csrf::CsrfClient;
+use leptos::prelude::*;
+
+#[cfg(feature = "ssr")]
+use crate::models::revision::Revision;
+
+type Result<T> = std::result::Result<T, ServerFnError>;
+
+/// Number of unchanged lines shown around changes in diffs
+#[cfg(feature = "ssr")]
+const DIFF_CONTEXT: usize = 3;
+
+#[cfg(feature = "ssr")]
+const REVISION_COLUMNS: &str = "post_revisions.id, post_revisions.post_id, users.username, \
+    post_revisions.created_at, post_revisions.title, post_revisions.published";
+
+#[cfg(feature = "ssr")]
+fn revision_from_row(row: &libsql::Row) -> Result<Revision> {
+    Ok(Revision {
+        id: row.get(0)?,
+        post_id: row.get(1)?,
+        author: row.get(2)?,
+        created_at: row.get::<String>(3)?.parse()?,
+        title: row.get(4)?,
+        published: row.get(5)?,
+    })
+}
+
+/// Revisions may be seen and restored by those who may edit their post
+#[cfg(feature = "ssr")]
+async fn ensure_can_edit_revisions(
+    conn: &libsql::Connection,
+    post_id: i64,
+) -> Result<crate::models::session::SessionUser> {
+    use super::blog::{ensure_can_edit_post, find_post_by_id};
+
+    let Some(post) = find_post_by_id(conn, post_id).await? else {
+        return Err(ServerFnError::new("Not found"));
+    };
+
+    ensure_can_edit_post(&post).await
+}
+
+/// Record the current version of a post as a new revision
+#[cfg(feature = "ssr")]
+pub(crate) async fn record_revision(
+    conn: &libsql::Connection,
+    post_id: i64,
+    author_id: Option<i64>,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO post_revisions (post_id, author_id, created_at, title, content, published)
+         SELECT id, ?, updated_at, title, content, published FROM posts WHERE id = ?",
+        libsql::params![author_id, post_id],
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// A revision along with its content
+#[cfg(feature = "ssr")]
+async fn find_revision(conn: &libsql::Connection, id: i64) -> Result<Option<(Revision, String)>> {
+    let mut rows = conn
+        .query(
+            &format!(
+                "SELECT {REVISION_COLUMNS}, post_revisions.content FROM post_revisions
+                 LEFT JOIN users ON users.id = post_revisions.author_id
+                 WHERE post_revisions.id = ?"
+            ),
+            libsql::params![id],
+        )
+        .await?;
+
+    let Some(row) = rows.next().await? else {
+        return Ok(None);
+    };
+    Ok(Some((revision_from_row(&row)?, row.get(6)?)))
+}
+
+/// Get a post and its revisions, newest first
+#[server(GetPostHistory, "/api/revisions", client = CsrfClient)]
+pub async fn get_post_history(slug: String) -> Result<PostHistory> {
+    use super::blog::{ensure_can_edit_post, find_post_by_slug};
+
+    let conn = crate::server::utils::db::get_db();
+    let Some(post) = find_post_by_slug(conn, &slug).await? else {
+        return Err(ServerFnError::new("Not found"));
+    };
+    ensure_can_edit_post(&post).await?;
+
+    let mut rows = conn
+        .query(
+            &format!(
+                "SELECT {REVISION_COLUMNS} FROM post_revisions
+                 LEFT JOIN users ON users.id = post_revisions.author_id
+                 WHERE post_revisions.post_id = ?
+                 ORDER BY post_revisions.id DESC"
+            ),
+            libsql::params![post.id],
+        )
+        .await?;
+
+    let mut revisions = Vec::new();
+    while let Some(row) = rows.next().await? {
+        revisions.push(revision_from_row(&row)?);
+    }
+
+    Ok(PostHistory { post, revisions })
+}
+
+/// Line-level diff of the contents of two revisions of the same post
+#[server(GetRevisionDiff, "/api/revisions", client = CsrfClient)]
+pub async fn get_revision_diff(from: i64, to: i64) -> Result<RevisionDiff> {
+    use crate::models::revision::{DiffLine, DiffTag};
+    use similar::{ChangeTag, TextDiff};
+
+    let conn = crate::server::utils::db::get_db();
+    let (Some((from, old)), Some((to, new))) = (
+        find_revision(conn, from).await?,
+        find_revision(conn, to).await?,
+    ) else {
+        return Err(ServerFnError::new("Not found"));
+    };
+    if from.post_id != to.post_id {
+        return Err(ServerFnError::new("Revisions of different posts"));
+    }
+    ensure_can_edit_revisions(conn, from.post_id).await?;
+
+    let diff = TextDiff::from_lines(&old, &new);
+    let hunks = diff
+        .grouped_ops(DIFF_CONTEXT)
+        .iter()
+        .map(|group| {
+            group
+                .iter()
+                .flat_map(|op| diff.iter_changes(op))
+                .map(|change| DiffLine {
+                    tag: match change.tag() {
+                        ChangeTag::Equal => DiffTag::Equal,
+                        ChangeTag::Delete => DiffTag::Delete,
+                        ChangeTag::Insert => DiffTag::Insert,
+                    },
+                    old_number: change.old_index().map(|index| index + 1),
+                    new_number: change.new_index().map(|index| index + 1),
+                    text: change.value().trim_end_matches(['\r', '\n']).to_string(),
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(RevisionDiff { from, to, hunks })
+}
+
+/// Bring back the title and content of a revision. The post is saved as a new revision,
+/// so that the restore itself can be undone.
+#[server(RestoreRevision, "/api/revisions", client = CsrfClient)]
+pub async fn restore_revision(id: i64) -> Result<Post> {
+    use super::blog::find_post_by_id;
+    use crate::models::post::reading_time;
+    use crate::server::utils::markdown::summarize_markdown;
+
+    let conn = crate::server::utils::db::get_db();
+    let Some((revision, content)) = find_revision(conn, id).await? else {
+        return Err(ServerFnError::new("Not found"));
+    };
+    let user = ensure_can_edit_revisions(conn, revision.post_id).await?;
+
+    let summary = summarize_markdown(&content);
+    conn.execute(
+        "UPDATE posts SET title = ?, content = ?, updated_at = ?, auto_excerpt = ?, word_count = ?, reading_time = ? WHERE id = ?",
+        libsql::params![
+            revision.title.clone(),
+            content,
+            chrono::Utc::now().to_string(),
+            summary.excerpt,
+            summary.word_count,
+            reading_time(summary.word_count),
+            revision.post_id
+        ],
+    )
+    .await?;
+
+    record_revision(conn, revision.post_id, user.id).await?;
+
+    find_post_by_id(conn, revision.post_id)
+        .await?
+        .ok_or_else(|| ServerFnError::new("Not found"))
+}
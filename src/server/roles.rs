@@ -0,0 +1,227 @@
+use crate::models::permission::Role;
+use crate::server::csrf::CsrfClient;
+use leptos::prelude::*;
+
+#[cfg(feature = "ssr")]
+use crate::models::permission::Permission;
+#[cfg(feature = "ssr")]
+use std::collections::BTreeSet;
+
+type Result<T> = std::result::Result<T, ServerFnError>;
+
+/// Role granted to newly registered users
+#[cfg(feature = "ssr")]
+pub(crate) const DEFAULT_ROLE: &str = "reader";
+
+/// Names of the roles granted to a user
+#[cfg(feature = "ssr")]
+pub(crate) async fn user_roles(conn: &libsql::Connection, user_id: i64) -> Result<Vec<String>> {
+    let mut rows = conn
+        .query(
+            "SELECT roles.name FROM user_roles
+             JOIN roles ON roles.id = user_roles.role_id
+             WHERE user_roles.user_id = ?
+             ORDER BY roles.id",
+            libsql::params![user_id],
+        )
+        .await?;
+
+    let mut roles = Vec::new();
+    while let Some(row) = rows.next().await? {
+        roles.push(row.get(0)?);
+    }
+
+    Ok(roles)
+}
+
+/// Every permission granted to a user through their roles. Permissions unknown to this
+/// version of the blog are ignored, and so are those of roles requiring two-factor
+/// authentication when the user hasn't enabled it.
+#[cfg(feature = "ssr")]
+pub(crate) async fn user_permissions(
+    conn: &libsql::Connection,
+    user_id: i64,
+) -> Result<BTreeSet<Permission>> {
+    let mut rows = conn
+        .query(
+            "SELECT DISTINCT permissions.name FROM user_roles
+             JOIN users ON users.id = user_roles.user_id
+             JOIN roles ON roles.id = user_roles.role_id
+             JOIN role_permissions ON role_permissions.role_id = user_roles.role_id
+             JOIN permissions ON permissions.id = role_permissions.permission_id
+             WHERE user_roles.user_id = ? AND (NOT roles.requires_two_factor OR users.totp_enabled)",
+            libsql::params![user_id],
+        )
+        .await?;
+
+    let mut permissions = BTreeSet::new();
+    while let Some(row) = rows.next().await? {
+        if let Ok(permission) = row.get::<String>(0)?.parse() {
+            permissions.insert(permission);
+        }
+    }
+
+    Ok(permissions)
+}
+
+/// Id of a user and of a role, looked up by name
+#[cfg(feature = "ssr")]
+async fn find_user_and_role(
+    conn: &libsql::Connection,
+    username: String,
+    role: String,
+) -> Result<(i64, i64)> {
+    let mut rows = conn
+        .query(
+            "SELECT users.id, roles.id FROM users, roles
+             WHERE users.username = ? AND roles.name = ?",
+            libsql::params![username, role],
+        )
+        .await?;
+
+    let Some(row) = rows.next().await? else {
+        return Err(ServerFnError::new("Not found"));
+    };
+    Ok((row.get(0)?, row.get(1)?))
+}
+
+/// Fail when no enabled user may manage users anymore, so that nobody is locked out of
+/// the administration. Meant to be checked inside the transaction making the change.
+#[cfg(feature = "ssr")]
+pub(crate) async fn ensure_user_manager_remains(conn: &libsql::Connection) -> Result<()> {
+    let mut managers = conn
+        .query(
+            "SELECT 1 FROM user_roles
+             JOIN users ON users.id = user_roles.user_id
+             JOIN roles ON roles.id = user_roles.role_id
+             JOIN role_permissions ON role_permissions.role_id = user_roles.role_id
+             JOIN permissions ON permissions.id = role_permissions.permission_id
+             WHERE permissions.name = ? AND NOT users.disabled
+                AND (NOT roles.requires_two_factor OR users.totp_enabled)
+             LIMIT 1",
+            libsql::params![Permission::ManageUsers.name()],
+        )
+        .await?;
+
+    if managers.next().await?.is_none() {
+        return Err(ServerFnError::new(
+            "At least one user must keep the permission to manage users",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Every role with its permissions
+#[server(GetRoles, "/api/roles", client = CsrfClient)]
+pub async fn get_roles() -> Result<Vec<Role>> {
+    use crate::server::utils::session::require_permission;
+
+    require_permission(Permission::ManageUsers).await?;
+
+    let conn = crate::server::utils::db::get_db();
+    let mut rows = conn
+        .query(
+            "SELECT roles.name, permissions.name, roles.requires_two_factor FROM roles
+             LEFT JOIN role_permissions ON role_permissions.role_id = roles.id
+             LEFT JOIN permissions ON permissions.id = role_permissions.permission_id
+             ORDER BY roles.id, permissions.id",
+            (),
+        )
+        .await?;
+
+    let mut roles: Vec<Role> = Vec::new();
+    while let Some(row) = rows.next().await? {
+        let name: String = row.get(0)?;
+        if roles.last().is_none_or(|role| role.name != name) {
+            roles.push(Role {
+                name,
+                permissions: Vec::new(),
+                requires_two_factor: row.get(2)?,
+            });
+        }
+
+        let permission = row
+            .get::<Option<String>>(1)?
+            .and_then(|permission| permission.parse().ok());
+        if let (Some(role), Some(permission)) = (roles.last_mut(), permission) {
+            role.permissions.push(permission);
+        }
+    }
+
+    Ok(roles)
+}
+
+/// Grant a role to a user
+#[server(GrantRole, "/api/roles", client = CsrfClient)]
+pub async fn grant_role(username: String, role: String) -> Result<()> {
+    use crate::server::utils::session::require_permission;
+
+    require_permission(Permission::ManageUsers).await?;
+
+    let conn = crate::server::utils::db::get_db();
+    let (user_id, role_id) = find_user_and_role(conn, username, role).await?;
+    conn.execute(
+        "INSERT OR IGNORE INTO user_roles (user_id, role_id) VALUES (?, ?)",
+        libsql::params![user_id, role_id],
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Revoke a role from a user, unless it would leave nobody able to manage users
+#[server(RevokeRole, "/api/roles", client = CsrfClient)]
+pub async fn revoke_role(username: String, role: String) -> Result<()> {
+    use crate::server::utils::session::require_permission;
+
+    require_permission(Permission::ManageUsers).await?;
+
+    let conn = crate::server::utils::db::get_db();
+    let (user_id, role_id) = find_user_and_role(conn, username, role).await?;
+
+    let tx = conn.transaction().await?;
+    tx.execute(
+        "DELETE FROM user_roles WHERE user_id = ? AND role_id = ?",
+        libsql::params![user_id, role_id],
+    )
+    .await?;
+
+    if let Err(e) = ensure_user_manager_remains(&tx).await {
+        tx.rollback().await?;
+        return Err(e);
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Require, or stop requiring, two-factor authentication for the permissions of a role
+#[server(SetRoleRequiresTwoFactor, "/api/roles", client = CsrfClient)]
+pub async fn set_role_requires_two_factor(role: String, required: bool) -> Result<()> {
+    use crate::server::utils::session::require_permission;
+
+    require_permission(Permission::ManageUsers).await?;
+
+    let conn = crate::server::utils::db::get_db();
+    let tx = conn.transaction().await?;
+    let updated = tx
+        .execute(
+            "UPDATE roles SET requires_two_factor = ? WHERE name = ?",
+            libsql::params![required, role],
+        )
+        .await?;
+    if updated == 0 {
+        tx.rollback().await?;
+        return Err(ServerFnError::new("Not found"));
+    }
+
+    // Somebody able to manage users must already have two-factor authentication
+    if let Err(e) = ensure_user_manager_remains(&tx).await {
+        tx.rollback().await?;
+        return Err(e);
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
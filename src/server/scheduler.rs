@@ -0,0 +1,111 @@
+use chrono::{DateTime, Utc};
+use leptos::prelude::*;
+use libsql::Connection;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{error, info};
+
+use crate::models::post::Post;
+use crate::server::blog::find_post_by_id;
+use crate::server::utils::db::get_db;
+
+type Result<T> = std::result::Result<T, ServerFnError>;
+
+/// Called once for every post that goes live, right after it is marked as published
+pub type PublishHook = fn(&Post);
+
+/// Every publish hook, run in order
+const PUBLISH_HOOKS: &[PublishHook] = &[log_publication];
+
+/// Longest time between two checks for posts to publish
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+/// Wakes the scheduler up when a post publication time was set or changed
+static SCHEDULE_CHANGED: Notify = Notify::const_new();
+
+fn log_publication(post: &Post) {
+    info!("Published post {} ({})", post.id, post.slug);
+}
+
+/// Let the scheduler know that a publication time was set or changed
+pub fn schedule_changed() {
+    SCHEDULE_CHANGED.notify_one();
+}
+
+/// Mark the posts whose publication time has come as published and run the publish hooks
+/// for them. Returns the number of published posts.
+pub async fn publish_due_posts(conn: &Connection) -> Result<usize> {
+    let mut rows = conn
+        .query(
+            "UPDATE posts SET published = TRUE
+             WHERE published = FALSE AND datetime(publish_at) <= datetime(?, 'unixepoch')
+             RETURNING id",
+            libsql::params![Utc::now().timestamp()],
+        )
+        .await?;
+
+    let mut ids = Vec::new();
+    while let Some(row) = rows.next().await? {
+        ids.push(row.get::<i64>(0)?);
+    }
+
+    for &id in &ids {
+        let Some(post) = find_post_by_id(conn, id).await? else {
+            continue;
+        };
+        for hook in PUBLISH_HOOKS {
+            hook(&post);
+        }
+    }
+
+    Ok(ids.len())
+}
+
+/// Publication time of the next scheduled post, if any
+async fn next_publication(conn: &Connection) -> Result<Option<DateTime<Utc>>> {
+    let mut rows = conn
+        .query(
+            "SELECT publish_at FROM posts WHERE published = FALSE AND publish_at IS NOT NULL
+             ORDER BY datetime(publish_at) LIMIT 1",
+            (),
+        )
+        .await?;
+
+    match rows.next().await? {
+        Some(row) => Ok(row
+            .get::<Option<String>>(0)?
+            .map(|publish_at| publish_at.parse())
+            .transpose()?),
+        None => Ok(None),
+    }
+}
+
+/// Publish scheduled posts in the background as their publication time comes
+pub fn spawn_publish_scheduler() {
+    tokio::spawn(async {
+        let conn = get_db();
+
+        loop {
+            if let Err(e) = publish_due_posts(conn).await {
+                error!("Failed to publish scheduled posts: {e}");
+            }
+
+            let sleep = match next_publication(conn).await {
+                Ok(Some(publish_at)) => (publish_at - Utc::now())
+                    .to_std()
+                    .unwrap_or_default()
+                    .min(MAX_SLEEP),
+                Ok(None) => MAX_SLEEP,
+                Err(e) => {
+                    error!("Failed to find the next scheduled post: {e}");
+                    MAX_SLEEP
+                }
+            };
+
+            tokio::select! {
+                _ = tokio::time::sleep(sleep) => {}
+                _ = SCHEDULE_CHANGED.notified() => {}
+            }
+        }
+    });
+}
@@ -0,0 +1,127 @@
+use crate::models::search::SearchResults;
+use crate::server::csrf::CsrfClient;
+use leptos::prelude::*;
+
+type Result<T> = std::result::Result<T, ServerFnError>;
+
+/// Number of results per search page
+#[cfg(feature = "ssr")]
+const PAGE_SIZE: u32 = 10;
+
+/// Private use characters delimiting matches in FTS5 highlights, replaced by `<mark>` once escaped
+#[cfg(feature = "ssr")]
+const MATCH_START: char = '\u{E000}';
+#[cfg(feature = "ssr")]
+const MATCH_END: char = '\u{E001}';
+
+/// Turn user input into an FTS5 query matching every term, the last one as a prefix.
+///
+/// Terms are quoted so that FTS5 operators and syntax characters are searched literally.
+#[cfg(feature = "ssr")]
+fn fts_query(input: &str) -> Option<String> {
+    let terms = input
+        .split_whitespace()
+        .map(|term| term.replace('"', ""))
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{term}\""))
+        .collect::<Vec<_>>();
+
+    if terms.is_empty() {
+        return None;
+    }
+
+    Some(format!("{}*", terms.join(" ")))
+}
+
+/// Escape an FTS5 highlight or snippet, then turn its match delimiters into `<mark>` elements
+#[cfg(feature = "ssr")]
+fn highlight_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace(MATCH_START, "<mark>")
+        .replace(MATCH_END, "</mark>")
+}
+
+/// Search posts by title and content, best matches first.
+/// Unpublished posts are only searched for admins.
+#[server(SearchPosts, "/api/search", client = CsrfClient)]
+pub async fn search_posts(query: String, page: u32) -> Result<SearchResults> {
+    use super::blog::{post_from_row, visible_filter, POST_COLUMNS, POST_COLUMN_COUNT};
+    use super::tags::load_tags;
+    use crate::models::permission::Permission;
+    use crate::models::search::SearchResult;
+
+    let page = page.max(1);
+    let Some(fts_query) = fts_query(&query) else {
+        return Ok(SearchResults {
+            results: Vec::new(),
+            page,
+            has_more: false,
+        });
+    };
+
+    let user = crate::server::utils::session::get_user_session().await?;
+    let published_filter = if user.is_some_and(|u| u.has_permission(Permission::ViewDrafts)) {
+        String::new()
+    } else {
+        format!("AND {}", visible_filter(6))
+    };
+
+    let conn = crate::server::utils::db::get_db();
+
+    // Fetch one extra row to know whether there is a next page
+    let mut rows = conn
+        .query(
+            &format!(
+                "SELECT {POST_COLUMNS},
+                    highlight(posts_fts, 0, ?2, ?3),
+                    snippet(posts_fts, 1, ?2, ?3, '…', 24)
+                 FROM posts_fts
+                 JOIN posts ON posts.id = posts_fts.rowid
+                 WHERE posts_fts MATCH ?1 {published_filter}
+                 ORDER BY bm25(posts_fts, 10.0, 1.0)
+                 LIMIT ?4 OFFSET ?5"
+            ),
+            libsql::params![
+                fts_query,
+                MATCH_START.to_string(),
+                MATCH_END.to_string(),
+                PAGE_SIZE + 1,
+                (page - 1) * PAGE_SIZE,
+                chrono::Utc::now().timestamp()
+            ],
+        )
+        .await?;
+
+    let mut posts = Vec::new();
+    let mut highlights = Vec::new();
+    while let Some(row) = rows.next().await? {
+        posts.push(post_from_row(&row)?);
+        highlights.push((
+            row.get::<String>(POST_COLUMN_COUNT)?,
+            row.get::<String>(POST_COLUMN_COUNT + 1)?,
+        ));
+    }
+
+    let has_more = posts.len() > PAGE_SIZE as usize;
+    posts.truncate(PAGE_SIZE as usize);
+    load_tags(conn, &mut posts).await?;
+
+    let results = posts
+        .into_iter()
+        .zip(highlights)
+        .map(|(post, (title, snippet))| SearchResult {
+            post,
+            title_html: highlight_html(&title),
+            snippet_html: highlight_html(&snippet),
+        })
+        .collect();
+
+    Ok(SearchResults {
+        results,
+        page,
+        has_more,
+    })
+}
@@ -1,7 +1,8 @@
 use leptos::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::models::session::ThemePreference;
+use crate::models::session::{DeviceSession, ThemePreference};
+use crate::server::csrf::CsrfClient;
 
 type Result<T> = std::result::Result<T, ServerFnError>;
 
@@ -11,10 +12,9 @@ pub struct GetThemePreferenceResponse {
     pub theme_preference_header: Option<ThemePreference>,
 }
 
-#[server(GetThemePreference, "/api/session")]
+#[server(GetThemePreference, "/api/session", endpoint = "theme", client = CsrfClient)]
 pub async fn get_theme_preference() -> Result<GetThemePreferenceResponse> {
-    use super::utils::session::{get_user_session, set_user_session};
-    use crate::models::session::SessionUser;
+    use super::utils::session::{get_user_session, get_visitor_theme};
     use axum::http::header::VARY;
     use axum::http::HeaderMap;
     use leptos_axum::extract;
@@ -55,30 +55,26 @@ pub async fn get_theme_preference() -> Result<GetThemePreferenceResponse> {
             })
         }
         Ok(None) => {
-            let theme_preference = get_theme_from_header().await?;
-            set_user_session(&SessionUser {
-                id: None,
-                username: None,
-                is_admin: false,
-                theme_preference: theme_preference.unwrap_or_default(),
-            })
-            .await?;
+            let theme_preference_header = get_theme_from_header().await?;
+            let theme_preference = get_visitor_theme()
+                .await?
+                .or(theme_preference_header)
+                .unwrap_or_default();
             Ok(GetThemePreferenceResponse {
-                theme_preference: theme_preference.unwrap_or_default(),
-                theme_preference_header: theme_preference,
+                theme_preference,
+                theme_preference_header,
             })
         }
         _ => Err(ServerFnError::new("Not found")),
     }
 }
 
-#[server(SetThemePreference, "/api/session")]
+#[server(SetThemePreference, "/api/session", endpoint = "set_theme", client = CsrfClient)]
 pub async fn set_theme_preference(theme_preference: ThemePreference) -> Result<()> {
     use super::utils::{
         db::get_db,
-        session::{get_user_session, set_user_session},
+        session::{get_user_session, set_user_session, set_visitor_theme},
     };
-    use crate::models::session::SessionUser;
 
     match get_user_session().await {
         Ok(Some(mut user)) => {
@@ -97,16 +93,106 @@ pub async fn set_theme_preference(theme_preference: ThemePreference) -> Result<(
             }
             Ok(())
         }
-        Ok(None) => {
-            set_user_session(&SessionUser {
-                id: None,
-                username: None,
-                is_admin: false,
-                theme_preference,
-            })
-            .await?;
-            Ok(())
-        }
+        Ok(None) => set_visitor_theme(theme_preference),
         _ => Err(ServerFnError::new("Not found")),
     }
 }
+
+/// Active sessions of the current user, most recently used first
+#[server(GetDeviceSessions, "/api/session", endpoint = "devices", client = CsrfClient)]
+pub async fn get_device_sessions() -> Result<Vec<DeviceSession>> {
+    use super::utils::db::{get_db, parse_sqlite_datetime};
+    use super::utils::session::{get_session_id, get_user_session, hash_session_id};
+
+    let Some(user_id) = get_user_session().await?.and_then(|user| user.id) else {
+        return Err(ServerFnError::new("Unauthorized"));
+    };
+    let current_session_hash = get_session_id().await.ok().map(|id| hash_session_id(&id));
+
+    let mut rows = get_db()
+        .query(
+            "SELECT public_id, user_agent, ip, created_at, COALESCE(last_seen_at, created_at),
+                remember, id_hash IS ?1
+             FROM sessions
+             WHERE user_id = ?2 AND expires > datetime(?3, 'unixepoch')
+             ORDER BY COALESCE(last_seen_at, created_at) DESC",
+            libsql::params![
+                current_session_hash,
+                user_id,
+                chrono::Utc::now().timestamp()
+            ],
+        )
+        .await?;
+
+    let mut sessions = Vec::new();
+    while let Some(row) = rows.next().await? {
+        sessions.push(DeviceSession {
+            id: row.get(0)?,
+            user_agent: row.get(1)?,
+            ip: row.get(2)?,
+            created_at: parse_sqlite_datetime(&row.get::<String>(3)?)?,
+            last_seen_at: parse_sqlite_datetime(&row.get::<String>(4)?)?,
+            remembered: row.get(5)?,
+            current: row.get(6)?,
+        });
+    }
+
+    Ok(sessions)
+}
+
+/// Log the current user out on the device of the session `id`, which may be this one
+#[server(SignOutDevice, "/api/session", endpoint = "sign_out", client = CsrfClient)]
+pub async fn sign_out_device(id: String) -> Result<()> {
+    use super::utils::db::get_db;
+    use super::utils::session::{clear_user, get_session_id, get_user_session, hash_session_id};
+
+    let Some(user_id) = get_user_session().await?.and_then(|user| user.id) else {
+        return Err(ServerFnError::new("Unauthorized"));
+    };
+
+    let conn = get_db();
+    let mut rows = conn
+        .query(
+            "SELECT id_hash FROM sessions WHERE public_id = ? AND user_id = ?",
+            libsql::params![id, user_id],
+        )
+        .await?;
+    let Some(row) = rows.next().await? else {
+        return Err(ServerFnError::new("Not found"));
+    };
+    let id_hash: String = row.get(0)?;
+    drop(rows);
+
+    if get_session_id()
+        .await
+        .is_ok_and(|current| hash_session_id(&current) == id_hash)
+    {
+        return clear_user().await;
+    }
+    conn.execute(
+        "DELETE FROM sessions WHERE id_hash = ?",
+        libsql::params![id_hash],
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Log the current user out on all their devices, this one included
+#[server(SignOutEverywhere, "/api/session", endpoint = "sign_out_everywhere", client = CsrfClient)]
+pub async fn sign_out_everywhere() -> Result<()> {
+    use super::utils::db::get_db;
+    use super::utils::session::{clear_user, get_user_session};
+
+    let Some(user_id) = get_user_session().await?.and_then(|user| user.id) else {
+        return Err(ServerFnError::new("Unauthorized"));
+    };
+
+    get_db()
+        .execute(
+            "DELETE FROM sessions WHERE user_id = ?",
+            libsql::params![user_id],
+        )
+        .await?;
+    clear_user().await
+}
@@ -0,0 +1,86 @@
+use std::env;
+use std::time::Duration;
+
+use leptos::prelude::*;
+use libsql::Connection;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, error, info};
+
+use crate::server::utils::db::get_db;
+use crate::server::utils::session::purge_expired_sessions_batch;
+
+type Result<T> = std::result::Result<T, ServerFnError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SessionGcSetupError {
+    #[error("{0} must be a positive number")]
+    InvalidNumber(&'static str),
+}
+
+/// What a purge of expired sessions did
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PurgeStats {
+    pub deleted: u64,
+    pub batches: u64,
+    /// Sessions left after the purge
+    pub remaining: u64,
+}
+
+/// Delete the expired sessions `batch_size` at a time, letting other tasks run in between
+pub async fn purge_sessions_in_batches(conn: &Connection, batch_size: u64) -> Result<PurgeStats> {
+    let mut stats = PurgeStats::default();
+    loop {
+        let deleted = purge_expired_sessions_batch(conn, batch_size).await?;
+        stats.deleted += deleted;
+        stats.batches += 1;
+        if deleted < batch_size {
+            break;
+        }
+        tokio::task::yield_now().await;
+    }
+
+    let mut rows = conn.query("SELECT COUNT(*) FROM sessions", ()).await?;
+    if let Some(row) = rows.next().await? {
+        stats.remaining = row.get(0)?;
+    }
+
+    Ok(stats)
+}
+
+/// Purge expired sessions right away, then every `SESSION_GC_INTERVAL_SECS` (an hour by
+/// default), `SESSION_GC_BATCH_SIZE` (1000 by default) at a time
+pub fn spawn_session_gc() -> std::result::Result<(), SessionGcSetupError> {
+    let var = |name: &'static str, default: u64| match env::var(name) {
+        Ok(value) => value
+            .parse()
+            .ok()
+            .filter(|value| *value > 0)
+            .ok_or(SessionGcSetupError::InvalidNumber(name)),
+        Err(_) => Ok(default),
+    };
+    let interval = Duration::from_secs(var("SESSION_GC_INTERVAL_SECS", 60 * 60)?);
+    let batch_size = var("SESSION_GC_BATCH_SIZE", 1000)?;
+
+    tokio::spawn(async move {
+        let conn = get_db();
+        // The first tick is immediate, which purges what piled up while the server was down
+        let mut ticks = tokio::time::interval(interval);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticks.tick().await;
+            match purge_sessions_in_batches(conn, batch_size).await {
+                Ok(stats) if stats.deleted > 0 => info!(
+                    deleted = stats.deleted,
+                    batches = stats.batches,
+                    remaining = stats.remaining,
+                    "Purged expired sessions"
+                ),
+                Ok(stats) => debug!(remaining = stats.remaining, "No expired sessions to purge"),
+                Err(e) => error!("Failed to purge expired sessions: {e}"),
+            }
+        }
+    });
+
+    Ok(())
+}
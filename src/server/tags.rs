@@ -0,0 +1,164 @@
+use leptos::prelude::*;
+
+use crate::models::post::PostSummary;
+use crate::models::tag::TagCount;
+use crate::server::csrf::CsrfClient;
+
+#[cfg(feature = "ssr")]
+use crate::models::{post::Post, tag::Tag};
+
+type Result<T> = std::result::Result<T, ServerFnError>;
+
+/// A post, or a listing entry of a post, carrying its tags
+#[cfg(feature = "ssr")]
+pub(crate) trait Tagged {
+    fn post_id(&self) -> i64;
+    fn tags_mut(&mut self) -> &mut Vec<Tag>;
+}
+
+#[cfg(feature = "ssr")]
+impl Tagged for Post {
+    fn post_id(&self) -> i64 {
+        self.id
+    }
+
+    fn tags_mut(&mut self) -> &mut Vec<Tag> {
+        &mut self.tags
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl Tagged for PostSummary {
+    fn post_id(&self) -> i64 {
+        self.id
+    }
+
+    fn tags_mut(&mut self) -> &mut Vec<Tag> {
+        &mut self.tags
+    }
+}
+
+/// Attach their tags to the given posts, using a single query
+#[cfg(feature = "ssr")]
+pub(crate) async fn load_tags<T: Tagged + Send>(
+    conn: &libsql::Connection,
+    posts: &mut [T],
+) -> Result<()> {
+    if posts.is_empty() {
+        return Ok(());
+    }
+
+    let ids: Vec<i64> = posts.iter().map(Tagged::post_id).collect();
+    let query = format!(
+        "SELECT post_tags.post_id, tags.name, tags.slug FROM post_tags
+         JOIN tags ON tags.id = post_tags.tag_id
+         WHERE post_tags.post_id IN ({})
+         ORDER BY tags.name",
+        vec!["?"; ids.len()].join(", ")
+    );
+    let mut rows = conn.query(&query, libsql::params_from_iter(ids)).await?;
+
+    while let Some(row) = rows.next().await? {
+        let post_id: i64 = row.get(0)?;
+        if let Some(post) = posts.iter_mut().find(|post| post.post_id() == post_id) {
+            post.tags_mut().push(Tag {
+                name: row.get(1)?,
+                slug: row.get(2)?,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Replace the tags of a post, creating missing tags and removing unused ones
+#[cfg(feature = "ssr")]
+pub(crate) async fn set_post_tags(
+    conn: &libsql::Connection,
+    post_id: i64,
+    names: &[String],
+) -> Result<()> {
+    use crate::models::post::slugify;
+
+    conn.execute(
+        "DELETE FROM post_tags WHERE post_id = ?",
+        libsql::params![post_id],
+    )
+    .await?;
+
+    for name in names {
+        let name = name.trim().to_string();
+        let slug = slugify(&name);
+        if slug.is_empty() {
+            continue;
+        }
+
+        conn.execute(
+            "INSERT INTO tags (name, slug) VALUES (?, ?) ON CONFLICT (slug) DO NOTHING",
+            libsql::params![name, slug.clone()],
+        )
+        .await?;
+        conn.execute(
+            "INSERT OR IGNORE INTO post_tags (post_id, tag_id) SELECT ?, id FROM tags WHERE slug = ?",
+            libsql::params![post_id, slug],
+        )
+        .await?;
+    }
+
+    conn.execute(
+        "DELETE FROM tags WHERE id NOT IN (SELECT tag_id FROM post_tags)",
+        (),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// List tags used by published posts, most used first
+#[server(GetTags, "/api/tags", client = CsrfClient)]
+pub async fn get_tags() -> Result<Vec<TagCount>> {
+    use super::blog::visible_filter;
+
+    let conn = crate::server::utils::db::get_db();
+
+    let mut rows = conn
+        .query(
+            &format!(
+                "SELECT tags.name, tags.slug, COUNT(posts.id) AS post_count FROM tags
+                 JOIN post_tags ON post_tags.tag_id = tags.id
+                 JOIN posts ON posts.id = post_tags.post_id
+                 WHERE {}
+                 GROUP BY tags.id
+                 ORDER BY post_count DESC, tags.name",
+                visible_filter(1)
+            ),
+            libsql::params![chrono::Utc::now().timestamp()],
+        )
+        .await?;
+
+    let mut tags = Vec::new();
+    while let Some(row) = rows.next().await? {
+        tags.push(TagCount {
+            tag: Tag {
+                name: row.get(0)?,
+                slug: row.get(1)?,
+            },
+            post_count: row.get(2)?,
+        });
+    }
+
+    Ok(tags)
+}
+
+#[server(GetPostsByTag, "/api/tags", client = CsrfClient)]
+pub async fn get_posts_by_tag(tag: String, only_published: bool) -> Result<Vec<PostSummary>> {
+    use super::blog::{ensure_can_list_drafts, list_posts};
+
+    ensure_can_list_drafts(only_published).await?;
+
+    let conn = crate::server::utils::db::get_db();
+
+    let posts = list_posts(conn, only_published, Some(tag)).await?;
+
+    Ok(posts.into_iter().map(PostSummary::from).collect())
+}
@@ -0,0 +1,446 @@
+use crate::models::two_factor::TotpEnrollment;
+use crate::models::user::User;
+use crate::server::csrf::CsrfClient;
+use leptos::prelude::*;
+
+#[cfg(feature = "ssr")]
+use crate::server::utils::crypto::sha256_hex;
+
+type Result<T> = std::result::Result<T, ServerFnError>;
+
+/// How long the second step of a login may take
+#[cfg(feature = "ssr")]
+const CHALLENGE_LIFETIME_SECS: i64 = 5 * 60;
+
+/// Wrong codes allowed per login, or per session to change two-factor authentication, before
+/// the password must be entered again
+#[cfg(feature = "ssr")]
+const CHALLENGE_MAX_ATTEMPTS: i64 = 5;
+
+#[cfg(feature = "ssr")]
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Characters of recovery codes, without the ones easily mistaken for each other
+#[cfg(feature = "ssr")]
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+/// Authenticated data binding an encrypted TOTP secret to its user
+#[cfg(feature = "ssr")]
+fn secret_context(user_id: i64) -> String {
+    format!("totp:{user_id}")
+}
+
+#[cfg(feature = "ssr")]
+fn build_totp(secret: Vec<u8>, username: &str) -> Result<totp_rs::TOTP> {
+    totp_rs::TOTP::new(
+        totp_rs::Algorithm::SHA1,
+        6,
+        1,
+        30,
+        secret,
+        Some(super::utils::totp::issuer().to_string()),
+        username.replace(':', "_"),
+    )
+    .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[cfg(feature = "ssr")]
+async fn current_user_id() -> Result<i64> {
+    super::utils::session::get_user_session()
+        .await?
+        .and_then(|user| user.id)
+        .ok_or_else(|| ServerFnError::new("Unauthorized"))
+}
+
+/// Check a TOTP code against the secret of a user. Codes of the previous and next time
+/// steps are accepted for clock drift, but never a step at or before the last accepted one.
+#[cfg(feature = "ssr")]
+async fn check_totp(conn: &libsql::Connection, user_id: i64, code: &str) -> Result<bool> {
+    use super::utils::crypto::decrypt;
+
+    let mut rows = conn
+        .query(
+            "SELECT username, totp_secret, totp_last_step FROM users WHERE id = ?",
+            libsql::params![user_id],
+        )
+        .await?;
+    let Some(row) = rows.next().await? else {
+        return Ok(false);
+    };
+    let username: String = row.get(0)?;
+    let Some(encrypted) = row.get::<Option<String>>(1)? else {
+        return Ok(false);
+    };
+    let last_step: Option<i64> = row.get(2)?;
+
+    let totp = build_totp(decrypt(&encrypted, &secret_context(user_id))?, &username)?;
+    let current_step = chrono::Utc::now().timestamp() / 30;
+    let matched = (current_step - 1..=current_step + 1)
+        .filter(|&step| last_step.is_none_or(|last_step| step > last_step))
+        .find(|&step| totp.generate(step as u64 * 30) == code);
+    let Some(step) = matched else {
+        return Ok(false);
+    };
+
+    conn.execute(
+        "UPDATE users SET totp_last_step = ? WHERE id = ?",
+        libsql::params![step, user_id],
+    )
+    .await?;
+
+    Ok(true)
+}
+
+/// Check a TOTP code, or else a recovery code which is then used up
+#[cfg(feature = "ssr")]
+async fn check_code(conn: &libsql::Connection, user_id: i64, code: &str) -> Result<bool> {
+    let code: String = code
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect::<String>()
+        .to_lowercase();
+
+    if code.len() == 6 && code.chars().all(|c| c.is_ascii_digit()) {
+        return check_totp(conn, user_id, &code).await;
+    }
+
+    let used = conn
+        .execute(
+            "DELETE FROM recovery_codes WHERE user_id = ? AND code_hash = ?",
+            libsql::params![user_id, sha256_hex(&code)],
+        )
+        .await?;
+
+    Ok(used > 0)
+}
+
+/// Check a code the logged in user gave to change their two-factor authentication. The
+/// session ends after [`CHALLENGE_MAX_ATTEMPTS`] wrong codes, like a login waiting for one.
+#[cfg(feature = "ssr")]
+async fn check_session_code(conn: &libsql::Connection, user_id: i64, code: &str) -> Result<()> {
+    use super::utils::session::{clear_user, get_session_id, hash_session_id};
+
+    let session_hash = hash_session_id(&get_session_id().await?);
+
+    // Counted before checking, parallel guesses would otherwise all get through
+    let mut rows = conn
+        .query(
+            "UPDATE sessions SET totp_failures = totp_failures + 1
+             WHERE id_hash = ? AND user_id = ? AND totp_failures < ?
+             RETURNING totp_failures",
+            libsql::params![session_hash.clone(), user_id, CHALLENGE_MAX_ATTEMPTS],
+        )
+        .await?;
+    let mut failures = None;
+    while let Some(row) = rows.next().await? {
+        failures = Some(row.get::<i64>(0)?);
+    }
+
+    let too_many = || ServerFnError::new("Too many invalid codes, please log in again");
+    let Some(failures) = failures else {
+        clear_user().await?;
+        return Err(too_many());
+    };
+
+    if !check_code(conn, user_id, code).await? {
+        if failures >= CHALLENGE_MAX_ATTEMPTS {
+            tracing::warn!(user_id, "Ended a session after too many invalid codes");
+            clear_user().await?;
+            return Err(too_many());
+        }
+        return Err(ServerFnError::new("Invalid code"));
+    }
+
+    conn.execute(
+        "UPDATE sessions SET totp_failures = 0 WHERE id_hash = ?",
+        libsql::params![session_hash],
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Replace the recovery codes of a user with new ones, returned formatted for display
+#[cfg(feature = "ssr")]
+async fn replace_recovery_codes(conn: &libsql::Connection, user_id: i64) -> Result<Vec<String>> {
+    use rand::Rng;
+
+    let codes: Vec<String> = {
+        let mut rng = rand::rng();
+        (0..RECOVERY_CODE_COUNT)
+            .map(|_| {
+                (0..10)
+                    .map(|_| {
+                        RECOVERY_CODE_ALPHABET[rng.random_range(0..RECOVERY_CODE_ALPHABET.len())]
+                            as char
+                    })
+                    .collect()
+            })
+            .collect()
+    };
+
+    conn.execute(
+        "DELETE FROM recovery_codes WHERE user_id = ?",
+        libsql::params![user_id],
+    )
+    .await?;
+    for code in &codes {
+        conn.execute(
+            "INSERT INTO recovery_codes (user_id, code_hash) VALUES (?, ?)",
+            libsql::params![user_id, sha256_hex(code)],
+        )
+        .await?;
+    }
+
+    Ok(codes
+        .into_iter()
+        .map(|code| format!("{}-{}", &code[..5], &code[5..]))
+        .collect())
+}
+
+/// Record a login waiting for its second factor, which starts a session to `remember` once
+/// completed. Returns the challenge to send back with the code.
+#[cfg(feature = "ssr")]
+pub(crate) async fn start_login_challenge(
+    conn: &libsql::Connection,
+    user_id: i64,
+    remember: bool,
+) -> Result<String> {
+    use super::utils::crypto::random_token;
+
+    let challenge = random_token();
+    let now = chrono::Utc::now().timestamp();
+
+    conn.execute(
+        "DELETE FROM login_challenges WHERE user_id = ? OR expires <= datetime(?, 'unixepoch')",
+        libsql::params![user_id, now],
+    )
+    .await?;
+    conn.execute(
+        "INSERT INTO login_challenges (token_hash, user_id, expires, remember)
+         VALUES (?, ?, datetime(?, 'unixepoch'), ?)",
+        libsql::params![
+            sha256_hex(&challenge),
+            user_id,
+            now + CHALLENGE_LIFETIME_SECS,
+            remember
+        ],
+    )
+    .await?;
+
+    Ok(challenge)
+}
+
+/// Second step of a login: check the TOTP or recovery code, then log the user in. Wrong codes
+/// count as failed logins of the username, see [`throttle`](super::utils::throttle).
+#[server(
+    VerifyLoginCode,
+    "/api/two-factor",
+    endpoint = "verify_login_code",
+    client = CsrfClient
+)]
+pub async fn verify_login_code(challenge: String, code: String) -> Result<User> {
+    use super::auth::load_user;
+    use super::utils::db::get_db;
+    use super::utils::request::client_ip;
+    use super::utils::session;
+    use super::utils::throttle::{login_failed, login_succeeded, reserve_login_attempt};
+
+    let conn = get_db();
+    let challenge_hash = sha256_hex(&challenge);
+
+    // Counted before checking, parallel guesses would otherwise all get through
+    let mut rows = conn
+        .query(
+            "UPDATE login_challenges SET attempts = attempts + 1
+             WHERE token_hash = ? AND attempts < ? AND expires > datetime(?, 'unixepoch')
+                AND user_id IN (SELECT id FROM users WHERE NOT disabled)
+             RETURNING user_id, remember,
+                (SELECT username FROM users WHERE users.id = login_challenges.user_id)",
+            libsql::params![
+                challenge_hash.clone(),
+                CHALLENGE_MAX_ATTEMPTS,
+                chrono::Utc::now().timestamp()
+            ],
+        )
+        .await?;
+    let mut reserved = None;
+    while let Some(row) = rows.next().await? {
+        reserved = Some((
+            row.get::<i64>(0)?,
+            row.get::<bool>(1)?,
+            row.get::<String>(2)?,
+        ));
+    }
+    let Some((user_id, remember, username)) = reserved else {
+        return Err(ServerFnError::new(
+            "This login has expired, please enter your password again",
+        ));
+    };
+
+    let ip = client_ip().await;
+    reserve_login_attempt(conn, &username, ip).await?;
+    if !check_code(conn, user_id, &code).await? {
+        login_failed(conn, &username, ip).await?;
+        return Err(ServerFnError::new("Invalid code"));
+    }
+    login_succeeded(conn, &username, ip).await?;
+
+    conn.execute(
+        "DELETE FROM login_challenges WHERE token_hash = ?",
+        libsql::params![challenge_hash],
+    )
+    .await?;
+
+    let Some(user) = load_user(conn, user_id).await? else {
+        return Err(ServerFnError::new("Not found"));
+    };
+    session::start_session(&user.get_session_user(), remember).await?;
+
+    Ok(user)
+}
+
+/// Generate a TOTP secret for the current user, to be confirmed with
+/// [`confirm_totp_enrollment`]. Replaces any secret that wasn't confirmed.
+#[server(
+    BeginTotpEnrollment,
+    "/api/two-factor",
+    endpoint = "begin_totp_enrollment",
+    client = CsrfClient
+)]
+pub async fn begin_totp_enrollment() -> Result<TotpEnrollment> {
+    use super::utils::crypto::encrypt;
+    use super::utils::db::get_db;
+    use qrcode::{render::svg, QrCode};
+    use rand::RngCore;
+
+    let user_id = current_user_id().await?;
+    let conn = get_db();
+
+    let mut rows = conn
+        .query(
+            "SELECT username, totp_enabled FROM users WHERE id = ?",
+            libsql::params![user_id],
+        )
+        .await?;
+    let Some(row) = rows.next().await? else {
+        return Err(ServerFnError::new("Not found"));
+    };
+    let username: String = row.get(0)?;
+    if row.get::<bool>(1)? {
+        return Err(ServerFnError::new(
+            "Two-factor authentication is already enabled",
+        ));
+    }
+
+    let mut secret = vec![0u8; 20];
+    rand::rng().fill_bytes(&mut secret);
+    let encrypted = encrypt(&secret, &secret_context(user_id))?;
+    conn.execute(
+        "UPDATE users SET totp_secret = ?, totp_last_step = NULL WHERE id = ?",
+        libsql::params![encrypted, user_id],
+    )
+    .await?;
+
+    let totp = build_totp(secret, &username)?;
+    let uri = totp.get_url();
+    let qr_svg = QrCode::new(uri.as_bytes())
+        .map_err(|e| ServerFnError::new(e.to_string()))?
+        .render::<svg::Color>()
+        .min_dimensions(200, 200)
+        .build();
+
+    Ok(TotpEnrollment {
+        secret: totp.get_secret_base32(),
+        uri,
+        qr_svg,
+    })
+}
+
+/// Enable two-factor authentication once the user proved their authenticator works.
+/// Returns the recovery codes, which are never shown again.
+#[server(ConfirmTotpEnrollment, "/api/two-factor", client = CsrfClient)]
+pub async fn confirm_totp_enrollment(code: String) -> Result<Vec<String>> {
+    use super::utils::db::get_db;
+
+    let user_id = current_user_id().await?;
+    let conn = get_db();
+
+    let code = code.trim();
+    if !check_totp(conn, user_id, code).await? {
+        return Err(ServerFnError::new("Invalid code"));
+    }
+
+    conn.execute(
+        "UPDATE users SET totp_enabled = TRUE WHERE id = ? AND totp_secret IS NOT NULL",
+        libsql::params![user_id],
+    )
+    .await?;
+
+    replace_recovery_codes(conn, user_id).await
+}
+
+/// Replace the recovery codes of the current user, given a valid code
+#[server(
+    RegenerateRecoveryCodes,
+    "/api/two-factor",
+    endpoint = "regenerate_recovery_codes",
+    client = CsrfClient
+)]
+pub async fn regenerate_recovery_codes(code: String) -> Result<Vec<String>> {
+    use super::utils::db::get_db;
+
+    let user_id = current_user_id().await?;
+    let conn = get_db();
+
+    check_session_code(conn, user_id, &code).await?;
+
+    replace_recovery_codes(conn, user_id).await
+}
+
+/// Turn off two-factor authentication for the current user, given a valid code. Fails when
+/// it would leave nobody able to manage users.
+#[server(
+    DisableTwoFactor,
+    "/api/two-factor",
+    endpoint = "disable_two_factor",
+    client = CsrfClient
+)]
+pub async fn disable_two_factor(code: String) -> Result<()> {
+    use super::roles::ensure_user_manager_remains;
+    use super::utils::db::get_db;
+
+    let user_id = current_user_id().await?;
+    let conn = get_db();
+
+    check_session_code(conn, user_id, &code).await?;
+
+    let tx = conn.transaction().await?;
+    reset_two_factor(&tx, user_id).await?;
+    if let Err(e) = ensure_user_manager_remains(&tx).await {
+        tx.rollback().await?;
+        return Err(e);
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Remove the TOTP secret and recovery codes of a user
+#[cfg(feature = "ssr")]
+pub async fn reset_two_factor(conn: &libsql::Connection, user_id: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE users SET totp_secret = NULL, totp_enabled = FALSE, totp_last_step = NULL
+         WHERE id = ?",
+        libsql::params![user_id],
+    )
+    .await?;
+    for query in [
+        "DELETE FROM recovery_codes WHERE user_id = ?",
+        "DELETE FROM login_challenges WHERE user_id = ?",
+    ] {
+        conn.execute(query, libsql::params![user_id]).await?;
+    }
+
+    Ok(())
+}
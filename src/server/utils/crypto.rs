@@ -0,0 +1,143 @@
+use std::env;
+use std::sync::OnceLock;
+
+use chacha20poly1305::aead::{Aead, AeadCore, OsRng, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce};
+use hmac::{Hmac, Mac};
+use leptos::prelude::ServerFnError;
+use sha2::Sha256;
+
+type Result<T> = std::result::Result<T, ServerFnError>;
+type HmacSha256 = Hmac<Sha256>;
+
+/// Hex encoded 32 bytes key secrets are encrypted with, `openssl rand -hex 32` makes one
+const KEY_VARIABLE: &str = "SECRET_ENCRYPTION_KEY";
+
+static CIPHER: OnceLock<Option<ChaCha20Poly1305>> = OnceLock::new();
+
+fn cipher() -> Result<&'static ChaCha20Poly1305> {
+    CIPHER
+        .get_or_init(|| {
+            let key = env::var(KEY_VARIABLE).ok()?;
+            match hex::decode(key.trim()) {
+                Ok(key) if key.len() == 32 => Some(ChaCha20Poly1305::new(Key::from_slice(&key))),
+                _ => {
+                    tracing::error!("{KEY_VARIABLE} must be 32 bytes, hex encoded");
+                    None
+                }
+            }
+        })
+        .as_ref()
+        .ok_or_else(|| {
+            ServerFnError::new(format!("{KEY_VARIABLE} is not configured on this server"))
+        })
+}
+
+/// Encrypt `plaintext`, bound to `context` so that the result can't be moved to another
+/// row. Returns the hex encoded nonce and ciphertext.
+pub fn encrypt(plaintext: &[u8], context: &str) -> Result<String> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher()?
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: context.as_bytes(),
+            },
+        )
+        .map_err(|_| ServerFnError::new("Encryption failed"))?;
+
+    Ok(hex::encode([nonce.as_slice(), &ciphertext].concat()))
+}
+
+/// Decrypt what [`encrypt`] returned for the same `context`
+pub fn decrypt(encrypted: &str, context: &str) -> Result<Vec<u8>> {
+    let bytes = hex::decode(encrypted)?;
+    if bytes.len() < 12 {
+        return Err(ServerFnError::new("Decryption failed"));
+    }
+    let (nonce, ciphertext) = bytes.split_at(12);
+
+    cipher()?
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: context.as_bytes(),
+            },
+        )
+        .map_err(|_| ServerFnError::new("Decryption failed"))
+}
+
+/// Key values are signed with. Derived from the encryption key when there is one, random
+/// otherwise, in which case signatures don't survive a restart.
+fn signing_key() -> &'static [u8] {
+    static SIGNING_KEY: OnceLock<Vec<u8>> = OnceLock::new();
+
+    SIGNING_KEY.get_or_init(|| {
+        match env::var(KEY_VARIABLE)
+            .ok()
+            .and_then(|key| hex::decode(key.trim()).ok())
+        {
+            Some(key) => {
+                let mut mac =
+                    <HmacSha256 as Mac>::new_from_slice(&key).expect("any key size works");
+                mac.update(b"signing key");
+                mac.finalize().into_bytes().to_vec()
+            }
+            None => {
+                use rand::RngCore;
+
+                let mut key = vec![0u8; 32];
+                rand::rng().fill_bytes(&mut key);
+                key
+            }
+        }
+    })
+}
+
+fn signature(value: &str, context: &str) -> HmacSha256 {
+    let mut mac = <HmacSha256 as Mac>::new_from_slice(signing_key()).expect("any key size works");
+    mac.update(context.as_bytes());
+    mac.update(&[0]);
+    mac.update(value.as_bytes());
+    mac
+}
+
+/// Signature binding `value` to `context`, for clients to prove they were given it
+pub fn mac(value: &str, context: &str) -> String {
+    hex::encode(signature(value, context).finalize().into_bytes())
+}
+
+/// Whether `mac` is what [`mac`] returns for `value` and `context`
+pub fn verify_mac(value: &str, context: &str, mac: &str) -> bool {
+    hex::decode(mac).is_ok_and(|mac| signature(value, context).verify_slice(&mac).is_ok())
+}
+
+/// `value` followed by a signature binding it to `context`, for values handed to clients
+pub fn sign(value: &str, context: &str) -> String {
+    format!("{value}.{}", mac(value, context))
+}
+
+/// The value [`sign`] returned `signed` for with the same `context`, if it wasn't tampered with
+pub fn verify(signed: &str, context: &str) -> Option<String> {
+    let (value, mac) = signed.rsplit_once('.')?;
+    verify_mac(value, context, mac).then(|| value.to_string())
+}
+
+/// Hex encoded SHA-256 of `value`. Tokens handed to clients are only stored by this hash, so
+/// that a leaked database can't be used to replay them.
+pub fn sha256_hex(value: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    format!("{:x}", Sha256::digest(value.as_bytes()))
+}
+
+/// 32 random bytes, hex encoded, for session ids and other tokens which must not be guessed
+pub fn random_token() -> String {
+    use rand::RngCore;
+
+    let mut bytes = [0u8; 32];
+    rand::rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
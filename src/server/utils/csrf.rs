@@ -0,0 +1,150 @@
+use std::sync::OnceLock;
+
+use axum::extract::Request;
+use axum::http::header::{AsHeaderName, HOST, ORIGIN, REFERER, SET_COOKIE};
+use axum::http::request::Parts;
+use axum::http::{HeaderMap, Method, StatusCode, Uri};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum_extra::extract::CookieJar;
+use leptos::prelude::*;
+use leptos::server_fn::error::ServerFnErrorSerde;
+use leptos_axum::ResponseOptions;
+use tracing::warn;
+
+use super::crypto::{mac, random_token, verify_mac};
+use super::session::{cookie_name, lax_cookie, script_cookie};
+use crate::server::csrf::{CSRF_COOKIE, CSRF_HEADER};
+
+#[derive(Debug, thiserror::Error)]
+pub enum CsrfSetupError {
+    #[error("SITE_URL must be an absolute http or https URL")]
+    InvalidSiteUrl,
+}
+
+/// Why a call to a server function was refused
+#[derive(Debug, thiserror::Error)]
+enum CsrfError {
+    #[error("Requests must have an Origin or Referer header")]
+    MissingOrigin,
+    #[error("Requests from {0} aren't allowed")]
+    ForeignOrigin(String),
+    #[error("Missing CSRF token, reload the page and try again")]
+    MissingToken,
+    #[error("Invalid CSRF token, reload the page and try again")]
+    InvalidToken,
+}
+
+static SITE_ORIGIN: OnceLock<String> = OnceLock::new();
+
+/// How long the secret of a browser lasts, it is only issued when missing
+const SECRET_LIFETIME_SECS: i64 = 365 * 24 * 60 * 60;
+
+/// Accept calls to server functions from the origin of `site_url`, in addition to the host
+/// they are sent to
+pub fn init_csrf(site_url: &str) -> Result<(), CsrfSetupError> {
+    let origin = origin(site_url).ok_or(CsrfSetupError::InvalidSiteUrl)?;
+    let _ = SITE_ORIGIN.set(origin);
+
+    Ok(())
+}
+
+/// `scheme://host[:port]` of `url`
+fn origin(url: &str) -> Option<String> {
+    let uri: Uri = url.parse().ok()?;
+    Some(format!("{}://{}", uri.scheme_str()?, uri.authority()?))
+}
+
+fn header(headers: &HeaderMap, name: impl AsHeaderName) -> Option<&str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// The secret CSRF tokens are derived from, kept in a cookie
+fn secret(headers: &HeaderMap) -> Option<String> {
+    CookieJar::from_headers(headers)
+        .get(&cookie_name("csrf"))
+        .map(|cookie| cookie.value().to_string())
+        .filter(|secret| !secret.is_empty())
+}
+
+/// The CSRF token the shell gives the page, issuing a secret to browsers which don't have one
+/// yet. The cookie is Lax, a page opened from another site would otherwise replace it and
+/// break the tokens of the pages already open.
+pub fn issue_token() -> String {
+    let secret = use_context::<Parts>().and_then(|parts| secret(&parts.headers));
+    let secret = secret.unwrap_or_else(|| {
+        let secret = random_token();
+        if let Some(response) = use_context::<ResponseOptions>() {
+            let _ = set_secret(&response, &secret);
+        }
+        secret
+    });
+
+    mac(&secret, "csrf")
+}
+
+/// Give the browser a new secret, so that the tokens it was given before are refused. Done
+/// when a user logs in or out, a token seen in one session is then worthless in the next.
+pub fn renew_csrf_secret() -> Result<(), ServerFnError> {
+    set_secret(&expect_context::<ResponseOptions>(), &random_token())
+}
+
+/// Send the browser `secret`, along with its token in a cookie the pages read the token from
+/// when it changes after they were rendered
+fn set_secret(response: &ResponseOptions, secret: &str) -> Result<(), ServerFnError> {
+    response.append_header(
+        SET_COOKIE,
+        lax_cookie("csrf", secret, Some(SECRET_LIFETIME_SECS), "/")?,
+    );
+    response.append_header(
+        SET_COOKIE,
+        script_cookie(
+            CSRF_COOKIE,
+            &mac(secret, "csrf"),
+            Some(SECRET_LIFETIME_SECS),
+        )?,
+    );
+
+    Ok(())
+}
+
+/// Refuse calls to server functions made by other sites. Requests to `/api/` other than GET
+/// must come from the site and carry the CSRF token of the page they were made from.
+pub async fn protect_server_fns(request: Request, next: Next) -> Response {
+    let safe = matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    );
+    if !safe
+        && request.uri().path().starts_with("/api/")
+        && let Err(e) = check(request.headers())
+    {
+        warn!(path = request.uri().path(), "Refused request: {e}");
+        let body = ServerFnError::new(e.to_string())
+            .ser()
+            .unwrap_or_else(|_| e.to_string());
+        return (StatusCode::FORBIDDEN, body).into_response();
+    }
+
+    next.run(request).await
+}
+
+fn check(headers: &HeaderMap) -> Result<(), CsrfError> {
+    // Browsers send an Origin with such requests, the Referer is for the ones that don't
+    let origin = match header(headers, ORIGIN) {
+        Some(origin) => origin.to_string(),
+        None => header(headers, REFERER)
+            .and_then(origin)
+            .ok_or(CsrfError::MissingOrigin)?,
+    };
+    let authority = origin.split_once("://").map(|(_, authority)| authority);
+    if SITE_ORIGIN.get() != Some(&origin) && authority != header(headers, HOST) {
+        return Err(CsrfError::ForeignOrigin(origin));
+    }
+
+    let token = header(headers, CSRF_HEADER).ok_or(CsrfError::MissingToken)?;
+    match secret(headers) {
+        Some(secret) if verify_mac(&secret, "csrf", token) => Ok(()),
+        _ => Err(CsrfError::InvalidToken),
+    }
+}
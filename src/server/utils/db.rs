@@ -1,8 +1,22 @@
 use libsql::{Builder, Connection};
 use std::env;
 use std::sync::OnceLock;
+use tracing::info;
 
-type Result<T> = std::result::Result<T, libsql::Error>;
+use super::markdown::summarize_markdown;
+use super::migrations::{run_migrations, MigrationError};
+
+type Result<T> = std::result::Result<T, MigrationError>;
+
+/// Format of the timestamps SQLite fills in by itself, with `CURRENT_TIMESTAMP` or `datetime()`
+pub const SQLITE_DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// A timestamp in [`SQLITE_DATETIME_FORMAT`], which SQLite always writes in UTC
+pub fn parse_sqlite_datetime(
+    value: &str,
+) -> std::result::Result<chrono::DateTime<chrono::Utc>, chrono::ParseError> {
+    Ok(chrono::NaiveDateTime::parse_from_str(value, SQLITE_DATETIME_FORMAT)?.and_utc())
+}
 
 static DB_INSTANCE: OnceLock<Connection> = OnceLock::new();
 
@@ -19,106 +33,42 @@ pub async fn init_db() -> Result<()> {
         Builder::new_local("blog.db").build().await?
     };
 
-    // Initialize database schema
     let conn = db.connect()?;
 
-    // Create users table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS users (
-            id INTEGER PRIMARY KEY,
-            username TEXT NOT NULL UNIQUE,
-            password_hash TEXT NOT NULL,
-            is_admin BOOLEAN NOT NULL DEFAULT FALSE,
-            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
-        )",
-        (),
-    )
-    .await?;
+    let version = run_migrations(&conn).await?;
+    info!("Database schema up to date at version {version}");
 
-    // Create posts table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS posts (
-            id INTEGER PRIMARY KEY,
-            title TEXT NOT NULL,
-            content TEXT NOT NULL,
-            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            published BOOLEAN NOT NULL DEFAULT FALSE
-        )",
-        (),
-    )
-    .await?;
-
-    // Create sessions table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS sessions (
-            id TEXT PRIMARY KEY,
-            user_id INTEGER,
-            username TEXT,
-            is_admin BOOLEAN,
-            expires TIMESTAMP NOT NULL,
-            FOREIGN KEY (user_id) REFERENCES users(id)
-        )",
-        (),
-    )
-    .await?;
-
-    // Add theme_preference column to users table if it doesn't exist
-    let mut existing_users_tp = conn
-        .query(
-            "SELECT 1 FROM pragma_table_info('users') WHERE name='theme_preference';",
-            (),
-        )
+    // Give every post created before slugs existed a slug derived from its title
+    let mut posts_without_slug = conn
+        .query("SELECT id, title FROM posts WHERE slug IS NULL", ())
         .await?;
-    if existing_users_tp.next().await?.is_none() {
+    while let Some(row) = posts_without_slug.next().await? {
+        let id: i64 = row.get(0)?;
+        let title: String = row.get(1)?;
+        let slug = format!("{}-{}", crate::models::post::post_slug(&title), id);
         conn.execute(
-            "ALTER TABLE users ADD COLUMN theme_preference TEXT NOT NULL DEFAULT 'system'",
-            (),
+            "UPDATE posts SET slug = ? WHERE id = ?",
+            libsql::params![slug, id],
         )
         .await?;
     }
 
-    // Add theme_preference column to sessions table if it doesn't exist
-    let mut existing_session_tp = conn
-        .query(
-            "SELECT 1 FROM pragma_table_info('sessions') WHERE name='theme_preference';",
-            (),
-        )
+    // Compute the excerpt and reading time of posts saved before they were stored
+    let mut posts_without_summary = conn
+        .query("SELECT id, content FROM posts WHERE word_count IS NULL", ())
         .await?;
-    if existing_session_tp.next().await?.is_none() {
+    while let Some(row) = posts_without_summary.next().await? {
+        let id: i64 = row.get(0)?;
+        let content: String = row.get(1)?;
+        let summary = summarize_markdown(&content);
         conn.execute(
-            "ALTER TABLE sessions ADD COLUMN theme_preference TEXT NOT NULL DEFAULT 'system'",
-            (),
-        )
-        .await?;
-    }
-
-    // Make username in session optional if it's not already
-    let mut existing_session_username = conn
-        .query(
-            "SELECT 1 FROM pragma_table_info('sessions') WHERE name='username';",
-            (),
-        )
-        .await?;
-    if existing_session_username.next().await?.is_none() {
-        // Make column nullable with a temporary table approach since SQLite doesn't support ALTER COLUMN DROP NOT NULL directly
-        conn.execute_transactional_batch(
-            "
-                BEGIN TRANSACTION;
-                CREATE TABLE sessions_new (
-                    id TEXT PRIMARY KEY,
-                    user_id INTEGER,
-                    username TEXT, -- Removed NOT NULL constraint
-                    is_admin BOOLEAN,
-                    expires TIMESTAMP NOT NULL,
-                    theme_preference TEXT NOT NULL DEFAULT 'system',
-                    FOREIGN KEY (user_id) REFERENCES users(id)
-                );
-                INSERT INTO sessions_new SELECT * FROM sessions;
-                DROP TABLE sessions;
-                ALTER TABLE sessions_new RENAME TO sessions;
-                COMMIT;
-                ",
+            "UPDATE posts SET auto_excerpt = ?, word_count = ?, reading_time = ? WHERE id = ?",
+            libsql::params![
+                summary.excerpt,
+                summary.word_count,
+                crate::models::post::reading_time(summary.word_count),
+                id
+            ],
         )
         .await?;
     }
@@ -0,0 +1,226 @@
+use std::fmt::Write;
+use std::sync::OnceLock;
+use syntect::html::{line_tokens_to_classed_spans, ClassStyle};
+use syntect::parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+/// Prefix of every class emitted for a syntax scope, styled in `style/main.scss`
+pub const CLASS_PREFIX: &str = "hl-";
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed {
+    prefix: CLASS_PREFIX,
+};
+
+static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+
+fn syntax_set() -> &'static SyntaxSet {
+    SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+/// Language and highlighted lines parsed from a fenced code block info string,
+/// e.g. `rust {3-5,8}`
+#[derive(Debug, Default, PartialEq, Eq)]
+struct CodeBlockInfo {
+    lang: Option<String>,
+    highlighted_lines: Vec<(usize, usize)>,
+}
+
+impl CodeBlockInfo {
+    fn parse(info: &str) -> Self {
+        let info = info.trim();
+        let (lang, rest) = match info.find('{') {
+            Some(start) => (info[..start].trim(), &info[start..]),
+            None => (info, ""),
+        };
+
+        let highlighted_lines = rest
+            .strip_prefix('{')
+            .and_then(|rest| rest.split_once('}'))
+            .map(|(ranges, _)| {
+                ranges
+                    .split(',')
+                    .filter_map(|range| {
+                        let range = range.trim();
+                        match range.split_once('-') {
+                            Some((start, end)) => {
+                                Some((start.trim().parse().ok()?, end.trim().parse().ok()?))
+                            }
+                            None => range.parse().ok().map(|line| (line, line)),
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            lang: lang
+                .split_whitespace()
+                .next()
+                .map(|lang| lang.to_lowercase()),
+            highlighted_lines,
+        }
+    }
+
+    fn is_highlighted(&self, line: usize) -> bool {
+        self.highlighted_lines
+            .iter()
+            .any(|&(start, end)| start <= line && line <= end)
+    }
+}
+
+fn find_syntax(lang: Option<&str>) -> &'static SyntaxReference {
+    let syntax_set = syntax_set();
+    lang.and_then(|lang| syntax_set.find_syntax_by_token(lang))
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text())
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn open_scope_spans(out: &mut String, stack: &ScopeStack) {
+    for scope in stack.as_slice() {
+        let classes = scope
+            .build_string()
+            .split('.')
+            .map(|atom| format!("{CLASS_PREFIX}{atom}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = write!(out, "<span class=\"{classes}\">");
+    }
+}
+
+/// Highlight a code block into class-annotated spans, one `.line` span per source line.
+///
+/// Scopes spanning several lines are closed at the end of each line and reopened on the
+/// next one, so every line is a self-contained element that can be numbered and highlighted.
+fn highlight_lines(code: &str, info: &CodeBlockInfo) -> String {
+    let syntax = find_syntax(info.lang.as_deref());
+    let mut parse_state = ParseState::new(syntax);
+    let mut stack = ScopeStack::new();
+    let mut out = String::with_capacity(code.len() * 4);
+
+    for (index, line) in LinesWithEndings::from(code).enumerate() {
+        let line_number = index + 1;
+        if info.is_highlighted(line_number) {
+            out.push_str("<span class=\"line highlighted\">");
+        } else {
+            out.push_str("<span class=\"line\">");
+        }
+
+        open_scope_spans(&mut out, &stack);
+        let spans = parse_state
+            .parse_line(line, syntax_set())
+            .ok()
+            .and_then(|ops| line_tokens_to_classed_spans(line, &ops, CLASS_STYLE, &mut stack).ok());
+
+        match spans {
+            // Lines are block elements, so the trailing newline would render as an empty line
+            Some((html, _)) => out.push_str(&html.replacen('\n', "", 1)),
+            None => out.push_str(&escape_html(line.trim_end_matches('\n'))),
+        }
+
+        for _ in 0..stack.len() {
+            out.push_str("</span>");
+        }
+        out.push_str("</span>");
+    }
+
+    out
+}
+
+/// Render a fenced code block with syntax highlighting, line numbers and a copy button.
+pub fn render_code_block(code: &str, info: &str) -> String {
+    let info = CodeBlockInfo::parse(info);
+    let lines = highlight_lines(code, &info);
+    let lang = info.lang.as_deref().map(escape_html).unwrap_or_default();
+    let code_class = if lang.is_empty() {
+        String::new()
+    } else {
+        format!(" class=\"language-{lang}\"")
+    };
+
+    format!(
+        "<div class=\"code-block\">\
+            <div class=\"code-header\">\
+                <span class=\"code-lang\">{lang}</span>\
+                <button class=\"copy-code\" aria-label=\"Copy code\">Copy</button>\
+            </div>\
+            <pre class=\"highlight\"><code{code_class}>{lines}</code></pre>\
+        </div>"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn info_strings_give_the_language_and_highlighted_lines() {
+        assert_eq!(
+            CodeBlockInfo::parse("Rust {3-5, 8}"),
+            CodeBlockInfo {
+                lang: Some("rust".to_string()),
+                highlighted_lines: vec![(3, 5), (8, 8)],
+            }
+        );
+        assert_eq!(
+            CodeBlockInfo::parse("{ 1 - 2 }"),
+            CodeBlockInfo {
+                lang: None,
+                highlighted_lines: vec![(1, 2)],
+            }
+        );
+        assert_eq!(
+            CodeBlockInfo::parse("python title=main.py"),
+            CodeBlockInfo {
+                lang: Some("python".to_string()),
+                highlighted_lines: Vec::new(),
+            }
+        );
+        assert_eq!(CodeBlockInfo::parse(""), CodeBlockInfo::default());
+    }
+
+    #[test]
+    fn invalid_ranges_are_ignored() {
+        let info = CodeBlockInfo::parse("js {a-3,4,5-,7}");
+        assert_eq!(info.highlighted_lines, vec![(4, 4), (7, 7)]);
+
+        let info = CodeBlockInfo::parse("js {3-5");
+        assert!(info.highlighted_lines.is_empty());
+    }
+
+    #[test]
+    fn lines_in_ranges_are_highlighted() {
+        let info = CodeBlockInfo::parse("{3-5,8}");
+        let highlighted: Vec<_> = (1..=9).filter(|&line| info.is_highlighted(line)).collect();
+        assert_eq!(highlighted, [3, 4, 5, 8]);
+    }
+
+    #[test]
+    fn code_blocks_have_one_span_per_line() {
+        let html = render_code_block("let a = 1;\nlet b = 2;\n", "rust {2}");
+
+        assert_eq!(html.matches("<span class=\"line\">").count(), 1, "{html}");
+        assert_eq!(
+            html.matches("<span class=\"line highlighted\">").count(),
+            1,
+            "{html}"
+        );
+        assert!(html.contains("<code class=\"language-rust\">"), "{html}");
+        assert!(html.contains("hl-keyword"), "{html}");
+    }
+
+    #[test]
+    fn unknown_languages_are_escaped_as_plain_text() {
+        let html = render_code_block("<b>&</b>\n", "\"><script>");
+
+        assert!(
+            !html.contains("<script>") && !html.contains("<b>"),
+            "{html}"
+        );
+        assert!(html.contains("&lt;b&gt;&amp;&lt;/b&gt;"), "{html}");
+    }
+}
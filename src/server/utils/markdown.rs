@@ -0,0 +1,306 @@
+use ammonia::Builder;
+use pulldown_cmark::{html, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+use super::highlight::{render_code_block, CLASS_PREFIX};
+
+static SANITIZER: OnceLock<Builder<'static>> = OnceLock::new();
+
+/// Prefix of the ids in rendered posts, so that they can't clash with the ids of the page
+const ID_PREFIX: &str = "user-content-";
+
+fn markdown_options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_GFM
+}
+
+fn sanitizer() -> &'static Builder<'static> {
+    SANITIZER.get_or_init(|| {
+        let mut builder = Builder::default();
+
+        // Task list items are rendered as read-only checkboxes
+        builder
+            .add_tags(["input"])
+            .add_tag_attribute_values("input", "type", ["checkbox"])
+            .add_tag_attributes("input", ["checked"])
+            .set_tag_attribute_value("input", "disabled", "");
+
+        // Highlighted code blocks: `language-*` and syntax scope classes, plus a copy button.
+        // The classes are filtered below.
+        builder
+            .add_tags(["button"])
+            .add_tag_attributes("code", ["class"])
+            .add_tag_attributes("span", ["class"])
+            .add_tag_attributes("button", ["aria-label"])
+            .set_tag_attribute_value("button", "type", "button")
+            .add_allowed_classes("pre", ["highlight"])
+            .add_allowed_classes("button", ["copy-code"]);
+
+        // Footnote references link to definitions by id, both prefixed below
+        builder
+            .id_prefix(Some(ID_PREFIX))
+            .add_tag_attributes("div", ["id"])
+            .add_allowed_classes("div", ["footnote-definition", "code-block", "code-header"])
+            .add_allowed_classes("sup", ["footnote-reference", "footnote-definition-label"]);
+
+        // Table column alignment is emitted as an inline style
+        let alignments = [
+            "text-align: left",
+            "text-align: center",
+            "text-align: right",
+        ];
+        builder
+            .add_tag_attribute_values("th", "style", alignments)
+            .add_tag_attribute_values("td", "style", alignments);
+
+        builder.attribute_filter(|element, attribute, value| match (element, attribute) {
+            ("span" | "code", "class") => {
+                let classes = value
+                    .split_whitespace()
+                    .filter(|class| code_class_allowed(element, class))
+                    .collect::<Vec<_>>();
+                (!classes.is_empty()).then(|| classes.join(" ").into())
+            }
+            ("a", "href") => match value.strip_prefix('#') {
+                Some(id) if !id.starts_with(ID_PREFIX) => Some(format!("#{ID_PREFIX}{id}").into()),
+                _ => Some(Cow::Borrowed(value)),
+            },
+            _ => Some(Cow::Borrowed(value)),
+        });
+
+        builder
+    })
+}
+
+/// Whether `class` is one of those [`render_code_block`] gives the `span` and `code` elements
+/// of code blocks
+fn code_class_allowed(element: &str, class: &str) -> bool {
+    match element {
+        "span" => {
+            class.starts_with(CLASS_PREFIX) || ["line", "highlighted", "code-lang"].contains(&class)
+        }
+        "code" => class.starts_with("language-"),
+        _ => false,
+    }
+}
+
+/// Render a Markdown document (CommonMark with GFM extensions) to sanitized HTML.
+pub fn render_markdown(source: &str) -> String {
+    let mut code_block: Option<(String, String)> = None;
+    let parser = Parser::new_ext(source, markdown_options()).filter_map(|event| {
+        // Buffer code block contents and replace the whole block with highlighted HTML
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let info = match kind {
+                    CodeBlockKind::Fenced(info) => info.to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code_block = Some((info, String::new()));
+                None
+            }
+            Event::Text(text) if code_block.is_some() => {
+                if let Some((_, code)) = code_block.as_mut() {
+                    code.push_str(&text);
+                }
+                None
+            }
+            Event::End(TagEnd::CodeBlock) => code_block
+                .take()
+                .map(|(info, code)| Event::Html(render_code_block(&code, &info).into())),
+            event => Some(event),
+        }
+    });
+
+    let mut unsafe_html = String::with_capacity(source.len() * 3 / 2);
+    html::push_html(&mut unsafe_html, parser);
+
+    sanitizer().clean(&unsafe_html).to_string()
+}
+
+/// Longest derived excerpt, in characters
+const EXCERPT_LENGTH: usize = 280;
+
+/// Plain text facts about a Markdown document, computed when a post is saved
+pub struct MarkdownSummary {
+    /// Text of the first paragraph, shortened to [`EXCERPT_LENGTH`] on a word boundary
+    pub excerpt: String,
+    /// Number of words of the prose, code blocks and images excluded
+    pub word_count: i64,
+}
+
+pub fn summarize_markdown(source: &str) -> MarkdownSummary {
+    let mut first_paragraph: Option<String> = None;
+    let mut paragraph: Option<String> = None;
+    let mut in_code_block = false;
+    let mut in_image = false;
+    let mut word_count = 0;
+
+    for event in Parser::new_ext(source, markdown_options()) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            // Alternative texts of images are not read along with the prose
+            Event::Start(Tag::Image { .. }) => in_image = true,
+            Event::End(TagEnd::Image) => in_image = false,
+            Event::Start(Tag::Paragraph) if first_paragraph.is_none() => {
+                paragraph = Some(String::new());
+            }
+            Event::End(TagEnd::Paragraph) => {
+                // Paragraphs holding only an image or markup have no text to show
+                if let Some(text) = paragraph.take().filter(|text| !text.trim().is_empty()) {
+                    first_paragraph = Some(text);
+                }
+            }
+            Event::Text(text) | Event::Code(text) if !in_code_block && !in_image => {
+                word_count += text.split_whitespace().count() as i64;
+                if let Some(paragraph) = paragraph.as_mut() {
+                    paragraph.push_str(&text);
+                }
+            }
+            Event::SoftBreak | Event::HardBreak => {
+                if let Some(paragraph) = paragraph.as_mut() {
+                    paragraph.push(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+
+    MarkdownSummary {
+        excerpt: shorten(first_paragraph.unwrap_or_default().trim(), EXCERPT_LENGTH),
+        word_count,
+    }
+}
+
+/// Cut a text after at most `max_chars` characters, on a word boundary, with an ellipsis
+fn shorten(text: &str, max_chars: usize) -> String {
+    let Some((cut, _)) = text.char_indices().nth(max_chars) else {
+        return text.to_string();
+    };
+
+    let shortened = &text[..cut];
+    let shortened = shortened
+        .rsplit_once(char::is_whitespace)
+        .map_or(shortened, |(words, _)| words);
+
+    format!(
+        "{}…",
+        shortened.trim_end_matches(|c: char| c.is_whitespace() || c.is_ascii_punctuation())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts_and_event_handlers_are_removed() {
+        let html = render_markdown(
+            "Hello <script>alert(1)</script>\n\n\
+             <img src=\"/a.png\" onerror=\"alert(2)\">\n\n\
+             [link](javascript:alert(3)) <a href=\"javascript:alert(4)\">raw</a>",
+        );
+
+        for removed in ["<script", "alert(1)", "onerror", "javascript:"] {
+            assert!(!html.contains(removed), "{removed} in {html}");
+        }
+        assert!(html.contains("src=\"/a.png\""), "{html}");
+    }
+
+    #[test]
+    fn ids_are_prefixed() {
+        let html =
+            render_markdown("<div id=\"csrf-token\">div</div>\n\nNote[^1]\n\n[^1]: The note");
+
+        assert!(!html.contains("id=\"csrf-token\""), "{html}");
+        assert!(html.contains("id=\"user-content-csrf-token\""), "{html}");
+        // Footnote links still point to their definition
+        assert!(html.contains("href=\"#user-content-1\""), "{html}");
+        assert!(html.contains("id=\"user-content-1\""), "{html}");
+        // Links which already had the prefix keep a single one
+        let html = render_markdown("[note](#user-content-1)");
+        assert!(html.contains("href=\"#user-content-1\""), "{html}");
+    }
+
+    #[test]
+    fn only_the_classes_of_code_blocks_are_kept() {
+        let html = render_markdown(
+            "<div class=\"admin hl-keyword\">div</div>\n\n\
+             <span class=\"hidden hl-keyword line\">span</span> <span class=\"hidden\">bare</span>\n\n\
+             <code class=\"language-rust sr-only\">code</code>",
+        );
+
+        assert!(
+            !html.contains("admin") && !html.contains("hidden") && !html.contains("sr-only"),
+            "{html}"
+        );
+        assert!(
+            html.contains("<span class=\"hl-keyword line\">span</span>"),
+            "{html}"
+        );
+        assert!(html.contains("<span>bare</span>"), "{html}");
+        assert!(html.contains("<code class=\"language-rust\">"), "{html}");
+    }
+
+    #[test]
+    fn highlighted_code_blocks_survive_sanitizing() {
+        let html = render_markdown("```rust {1}\nfn main() {}\n```");
+
+        assert!(html.contains("<pre class=\"highlight\">"), "{html}");
+        assert!(html.contains("class=\"language-rust\""), "{html}");
+        assert!(html.contains("class=\"line highlighted\""), "{html}");
+        assert!(html.contains("class=\"hl-source hl-rust\""), "{html}");
+        assert!(html.contains("type=\"button\">Copy</button>"), "{html}");
+    }
+
+    #[test]
+    fn gfm_extensions_are_rendered() {
+        let html = render_markdown(
+            "| a | b |\n|:-:|--:|\n| 1 | 2 |\n\n~~gone~~\n\n- [x] done\n- [ ] todo",
+        );
+
+        assert!(
+            html.contains("<th style=\"text-align: center\">a</th>"),
+            "{html}"
+        );
+        assert!(
+            html.contains("<td style=\"text-align: right\">2</td>"),
+            "{html}"
+        );
+        assert!(html.contains("<del>gone</del>"), "{html}");
+        assert!(html.contains("type=\"checkbox\""), "{html}");
+        assert!(html.contains("checked"), "{html}");
+    }
+
+    #[test]
+    fn excerpts_are_the_first_paragraph_with_text() {
+        let summary = summarize_markdown(
+            "# Title\n\n![A cat](cat.png)\n\nThe *first*\nparagraph with `code`.\n\nThe second.",
+        );
+        assert_eq!(summary.excerpt, "The first paragraph with code.");
+    }
+
+    #[test]
+    fn long_excerpts_are_cut_on_a_word() {
+        let summary = summarize_markdown(&"word, ".repeat(100));
+        assert!(summary.excerpt.ends_with("word…"), "{}", summary.excerpt);
+        assert!(summary.excerpt.chars().count() <= EXCERPT_LENGTH + 1);
+
+        assert_eq!(shorten("Short enough", 12), "Short enough");
+        assert_eq!(shorten("Cut right here", 10), "Cut right…");
+        assert_eq!(shorten("Cut right here", 8), "Cut…");
+        assert_eq!(shorten("Cut, here", 5), "Cut…");
+    }
+
+    #[test]
+    fn words_of_code_blocks_and_images_are_not_counted() {
+        let summary = summarize_markdown(
+            "Two words\n\n```rust\nfn main() {}\n```\n\n![four words of alt](a.png) `one`",
+        );
+        assert_eq!(summary.word_count, 3);
+    }
+}
@@ -0,0 +1,394 @@
+use libsql::Connection;
+use sha2::{Digest, Sha256};
+use tracing::info;
+
+/// A numbered schema change, applied at most once and in order
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+    /// Query returning a row when a database created before versioned migrations already
+    /// has this migration's schema. Only migrations that predate the migration system have one.
+    legacy_probe: Option<&'static str>,
+}
+
+impl Migration {
+    pub fn checksum(&self) -> String {
+        format!("{:x}", Sha256::digest(self.sql.as_bytes()))
+    }
+}
+
+/// Every migration, ordered by version. Applied migrations must never be edited, add a new one instead.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        sql: include_str!("../../../migrations/0001_initial_schema.sql"),
+        legacy_probe: Some("SELECT 1 FROM sqlite_master WHERE type='table' AND name='posts'"),
+    },
+    Migration {
+        version: 2,
+        name: "post_slugs",
+        sql: include_str!("../../../migrations/0002_post_slugs.sql"),
+        legacy_probe: Some("SELECT 1 FROM pragma_table_info('posts') WHERE name='slug'"),
+    },
+    Migration {
+        version: 3,
+        name: "tags",
+        sql: include_str!("../../../migrations/0003_tags.sql"),
+        legacy_probe: Some("SELECT 1 FROM sqlite_master WHERE type='table' AND name='tags'"),
+    },
+    Migration {
+        version: 4,
+        name: "posts_fts",
+        sql: include_str!("../../../migrations/0004_posts_fts.sql"),
+        legacy_probe: Some("SELECT 1 FROM sqlite_master WHERE type='table' AND name='posts_fts'"),
+    },
+    Migration {
+        version: 5,
+        name: "post_excerpts",
+        sql: include_str!("../../../migrations/0005_post_excerpts.sql"),
+        legacy_probe: None,
+    },
+    Migration {
+        version: 6,
+        name: "post_publish_at",
+        sql: include_str!("../../../migrations/0006_post_publish_at.sql"),
+        legacy_probe: None,
+    },
+    Migration {
+        version: 7,
+        name: "post_revisions",
+        sql: include_str!("../../../migrations/0007_post_revisions.sql"),
+        legacy_probe: None,
+    },
+    Migration {
+        version: 8,
+        name: "post_authors",
+        sql: include_str!("../../../migrations/0008_post_authors.sql"),
+        legacy_probe: None,
+    },
+    Migration {
+        version: 9,
+        name: "roles",
+        sql: include_str!("../../../migrations/0009_roles.sql"),
+        legacy_probe: None,
+    },
+    Migration {
+        version: 10,
+        name: "admin",
+        sql: include_str!("../../../migrations/0010_admin.sql"),
+        legacy_probe: None,
+    },
+    Migration {
+        version: 11,
+        name: "password_reset",
+        sql: include_str!("../../../migrations/0011_password_reset.sql"),
+        legacy_probe: None,
+    },
+    Migration {
+        version: 12,
+        name: "two_factor",
+        sql: include_str!("../../../migrations/0012_two_factor.sql"),
+        legacy_probe: None,
+    },
+    Migration {
+        version: 13,
+        name: "passkeys",
+        sql: include_str!("../../../migrations/0013_passkeys.sql"),
+        legacy_probe: None,
+    },
+    Migration {
+        version: 14,
+        name: "identities",
+        sql: include_str!("../../../migrations/0014_identities.sql"),
+        legacy_probe: None,
+    },
+    Migration {
+        version: 15,
+        name: "login_failures",
+        sql: include_str!("../../../migrations/0015_login_failures.sql"),
+        legacy_probe: None,
+    },
+    Migration {
+        version: 16,
+        name: "session_hardening",
+        sql: include_str!("../../../migrations/0016_session_hardening.sql"),
+        legacy_probe: None,
+    },
+    Migration {
+        version: 17,
+        name: "session_devices",
+        sql: include_str!("../../../migrations/0017_session_devices.sql"),
+        legacy_probe: None,
+    },
+    Migration {
+        version: 18,
+        name: "session_totp_failures",
+        sql: include_str!("../../../migrations/0018_session_totp_failures.sql"),
+        legacy_probe: None,
+    },
+    Migration {
+        version: 19,
+        name: "publish_at_rfc3339",
+        sql: include_str!("../../../migrations/0019_publish_at_rfc3339.sql"),
+        legacy_probe: None,
+    },
+    Migration {
+        version: 20,
+        name: "session_public_ids",
+        sql: include_str!("../../../migrations/0020_session_public_ids.sql"),
+        legacy_probe: None,
+    },
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum MigrationError {
+    #[error(transparent)]
+    Database(#[from] libsql::Error),
+    #[error("migration {version} ({name}) was modified after being applied")]
+    ChecksumMismatch { version: i64, name: String },
+    #[error("migration {version} ({name}) is applied but unknown to this version of the blog")]
+    UnknownMigration { version: i64, name: String },
+    #[error(
+        "database schema version {database} is newer than the latest known migration {latest}, \
+         refusing to downgrade"
+    )]
+    Downgrade { database: i64, latest: i64 },
+}
+
+type Result<T> = std::result::Result<T, MigrationError>;
+
+/// Latest schema version known to this build
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+async fn table_exists(conn: &Connection, name: &str) -> Result<bool> {
+    let mut rows = conn
+        .query(
+            "SELECT 1 FROM sqlite_master WHERE type='table' AND name=?",
+            libsql::params![name],
+        )
+        .await?;
+
+    Ok(rows.next().await?.is_some())
+}
+
+async fn record_migration(conn: &Connection, migration: &Migration) -> Result<()> {
+    conn.execute(
+        "INSERT INTO schema_migrations (version, name, checksum) VALUES (?, ?, ?)",
+        libsql::params![migration.version, migration.name, migration.checksum()],
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Bring a database created by the ad-hoc schema setup up to the shape of the first
+/// migrations, and record the ones whose schema it already has as applied
+async fn adopt_legacy_schema(conn: &Connection) -> Result<()> {
+    info!("Adopting existing database schema into versioned migrations");
+
+    // Columns that were added after the tables were first created
+    for table in ["users", "sessions"] {
+        let mut existing_theme_preference = conn
+            .query(
+                &format!(
+                    "SELECT 1 FROM pragma_table_info('{table}') WHERE name='theme_preference'"
+                ),
+                (),
+            )
+            .await?;
+        if existing_theme_preference.next().await?.is_none() {
+            conn.execute(
+                &format!(
+                    "ALTER TABLE {table} ADD COLUMN theme_preference TEXT NOT NULL DEFAULT 'system'"
+                ),
+                (),
+            )
+            .await?;
+        }
+    }
+
+    for migration in MIGRATIONS {
+        let Some(probe) = migration.legacy_probe else {
+            break;
+        };
+        if conn.query(probe, ()).await?.next().await?.is_none() {
+            break;
+        }
+
+        info!(
+            "Marking migration {} ({}) as applied",
+            migration.version, migration.name
+        );
+        record_migration(conn, migration).await?;
+    }
+
+    Ok(())
+}
+
+/// Verify the applied migrations, then apply the pending ones in order, each in its own
+/// transaction. Returns the resulting schema version.
+pub async fn run_migrations(conn: &Connection) -> Result<i64> {
+    let is_legacy =
+        !table_exists(conn, "schema_migrations").await? && table_exists(conn, "posts").await?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            checksum TEXT NOT NULL,
+            applied_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        (),
+    )
+    .await?;
+
+    if is_legacy {
+        adopt_legacy_schema(conn).await?;
+    }
+
+    let latest = latest_version();
+    let current = verify_applied_migrations(conn, latest).await?;
+    info!("Database schema at version {current} (latest {latest})");
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        info!(
+            "Applying migration {} ({})",
+            migration.version, migration.name
+        );
+
+        let tx = conn.transaction().await?;
+        tx.execute_batch(migration.sql).await?;
+        record_migration(&tx, migration).await?;
+        tx.commit().await?;
+    }
+
+    Ok(latest)
+}
+
+/// Check that every applied migration is known and unchanged, returning the schema version
+async fn verify_applied_migrations(conn: &Connection, latest: i64) -> Result<i64> {
+    let mut current = 0;
+    let mut rows = conn
+        .query(
+            "SELECT version, name, checksum FROM schema_migrations ORDER BY version",
+            (),
+        )
+        .await?;
+    while let Some(row) = rows.next().await? {
+        let version: i64 = row.get(0)?;
+        let name: String = row.get(1)?;
+        let checksum: String = row.get(2)?;
+
+        if version > latest {
+            return Err(MigrationError::Downgrade {
+                database: version,
+                latest,
+            });
+        }
+
+        let Some(migration) = MIGRATIONS.iter().find(|m| m.version == version) else {
+            return Err(MigrationError::UnknownMigration { version, name });
+        };
+        if migration.checksum() != checksum {
+            return Err(MigrationError::ChecksumMismatch { version, name });
+        }
+
+        current = version;
+    }
+
+    Ok(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn migrated_db() -> Connection {
+        let db = libsql::Builder::new_local(":memory:")
+            .build()
+            .await
+            .unwrap();
+        let conn = db.connect().unwrap();
+        run_migrations(&conn).await.unwrap();
+        conn
+    }
+
+    #[test]
+    fn versions_follow_each_other() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, index as i64 + 1, "{}", migration.name);
+        }
+    }
+
+    #[tokio::test]
+    async fn migrations_are_applied_once() {
+        let conn = migrated_db().await;
+        assert_eq!(run_migrations(&conn).await.unwrap(), latest_version());
+
+        let mut rows = conn
+            .query("SELECT count(*) FROM schema_migrations", ())
+            .await
+            .unwrap();
+        let count: i64 = rows.next().await.unwrap().unwrap().get(0).unwrap();
+        assert_eq!(count, MIGRATIONS.len() as i64);
+    }
+
+    #[tokio::test]
+    async fn modified_migrations_are_refused() {
+        let conn = migrated_db().await;
+        conn.execute(
+            "UPDATE schema_migrations SET checksum = 'edited' WHERE version = 2",
+            (),
+        )
+        .await
+        .unwrap();
+
+        let error = run_migrations(&conn).await.unwrap_err();
+        assert!(
+            matches!(error, MigrationError::ChecksumMismatch { version: 2, .. }),
+            "{error}"
+        );
+    }
+
+    #[tokio::test]
+    async fn newer_databases_are_not_downgraded() {
+        let conn = migrated_db().await;
+        let next = latest_version() + 1;
+        conn.execute(
+            "INSERT INTO schema_migrations (version, name, checksum) VALUES (?, 'future', '')",
+            libsql::params![next],
+        )
+        .await
+        .unwrap();
+
+        let error = run_migrations(&conn).await.unwrap_err();
+        assert!(
+            matches!(
+                error,
+                MigrationError::Downgrade { database, latest } if database == next && latest == next - 1
+            ),
+            "{error}"
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_migrations_are_refused() {
+        let conn = migrated_db().await;
+        conn.execute(
+            "INSERT INTO schema_migrations (version, name, checksum) VALUES (0, 'removed', '')",
+            (),
+        )
+        .await
+        .unwrap();
+
+        let error = run_migrations(&conn).await.unwrap_err();
+        assert!(
+            matches!(error, MigrationError::UnknownMigration { version: 0, .. }),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use std::env;
+use std::sync::OnceLock;
+
+use leptos::prelude::ServerFnError;
+use openidconnect::core::{CoreClient, CoreProviderMetadata};
+use openidconnect::{
+    reqwest, ClientId, ClientSecret, EndpointMaybeSet, EndpointNotSet, EndpointSet, IssuerUrl,
+    RedirectUrl,
+};
+
+use super::crypto::sha256_hex;
+
+type Result<T> = std::result::Result<T, ServerFnError>;
+
+/// How long the user may take to log in at the provider
+pub const LOGIN_LIFETIME_SECS: i64 = 10 * 60;
+
+#[derive(Debug, thiserror::Error)]
+pub enum OidcSetupError {
+    #[error("{0} must be set")]
+    MissingConfig(String),
+    #[error("invalid provider id {0:?}, expected letters, digits and underscores")]
+    InvalidProviderId(String),
+    #[error("{0} is not a valid URL")]
+    InvalidUrl(String),
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+}
+
+/// A provider users can log in with, configured by the `OIDC_<ID>_*` environment variables
+pub struct OidcProvider {
+    pub id: String,
+    pub name: String,
+    pub issuer: IssuerUrl,
+    pub client_id: ClientId,
+    /// Not set for public clients, which only rely on PKCE
+    pub client_secret: Option<ClientSecret>,
+}
+
+/// A client for a provider, with the endpoints of its discovery document
+pub type OidcClient = CoreClient<
+    EndpointSet,
+    EndpointNotSet,
+    EndpointNotSet,
+    EndpointNotSet,
+    EndpointMaybeSet,
+    EndpointMaybeSet,
+>;
+
+struct OidcConfig {
+    providers: Vec<OidcProvider>,
+    redirect_url: RedirectUrl,
+    http_client: reqwest::Client,
+}
+
+static OIDC: OnceLock<OidcConfig> = OnceLock::new();
+
+/// Read the providers listed in `OIDC_PROVIDERS`, separated by commas. Each one is set up by
+/// `OIDC_<ID>_ISSUER`, `OIDC_<ID>_CLIENT_ID`, and optionally `OIDC_<ID>_CLIENT_SECRET` and
+/// `OIDC_<ID>_NAME`. Providers send users back to the `/oidc/callback` page of `site_url`.
+pub fn init_oidc(site_url: &str) -> std::result::Result<(), OidcSetupError> {
+    let var = |name: String| env::var(&name).map_err(|_| OidcSetupError::MissingConfig(name));
+
+    let mut providers = Vec::new();
+    for id in env::var("OIDC_PROVIDERS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|id| id.trim().to_lowercase())
+        .filter(|id| !id.is_empty())
+    {
+        if !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(OidcSetupError::InvalidProviderId(id));
+        }
+        let prefix = format!("OIDC_{}", id.to_uppercase());
+
+        let issuer = var(format!("{prefix}_ISSUER"))?;
+        let issuer =
+            IssuerUrl::new(issuer.clone()).map_err(|_| OidcSetupError::InvalidUrl(issuer))?;
+        let name = env::var(format!("{prefix}_NAME")).unwrap_or_else(|_| {
+            let mut chars = id.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        });
+
+        providers.push(OidcProvider {
+            name,
+            issuer,
+            client_id: ClientId::new(var(format!("{prefix}_CLIENT_ID"))?),
+            client_secret: env::var(format!("{prefix}_CLIENT_SECRET"))
+                .ok()
+                .map(ClientSecret::new),
+            id,
+        });
+    }
+
+    let redirect_url = format!("{site_url}/oidc/callback");
+    let redirect_url = RedirectUrl::new(redirect_url.clone())
+        .map_err(|_| OidcSetupError::InvalidUrl(redirect_url))?;
+    // Following redirects would let a provider send requests anywhere
+    let http_client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let _ = OIDC.set(OidcConfig {
+        providers,
+        redirect_url,
+        http_client,
+    });
+
+    Ok(())
+}
+
+fn config() -> &'static OidcConfig {
+    OIDC.get().expect("OpenID Connect not initialized")
+}
+
+pub fn providers() -> &'static [OidcProvider] {
+    &config().providers
+}
+
+pub fn provider(id: &str) -> Result<&'static OidcProvider> {
+    providers()
+        .iter()
+        .find(|provider| provider.id == id)
+        .ok_or_else(|| ServerFnError::new("Unknown provider"))
+}
+
+pub fn http_client() -> &'static reqwest::Client {
+    &config().http_client
+}
+
+/// Failure of an exchange with a provider, logged with its cause which isn't meant for users
+pub fn oidc_error(provider: &OidcProvider, e: impl std::fmt::Debug) -> ServerFnError {
+    // Debug shows the underlying errors, Display only says which step failed
+    tracing::warn!("Login with {} failed: {e:?}", provider.id);
+    ServerFnError::new(format!("The login with {} failed", provider.name))
+}
+
+/// A client for `provider`. Its discovery document is fetched every time so that new signing
+/// keys are picked up.
+pub async fn client(provider: &OidcProvider) -> Result<OidcClient> {
+    let metadata = CoreProviderMetadata::discover_async(provider.issuer.clone(), http_client())
+        .await
+        .map_err(|e| oidc_error(provider, e))?;
+
+    Ok(CoreClient::from_provider_metadata(
+        metadata,
+        provider.client_id.clone(),
+        provider.client_secret.clone(),
+    )
+    .set_redirect_uri(config().redirect_url.clone()))
+}
+
+/// What the server remembers of a login between sending the user to the provider and their
+/// coming back
+pub struct PendingLogin {
+    pub provider: String,
+    pub nonce: String,
+    pub pkce_verifier: String,
+    /// The user linking an account at the provider, if any
+    pub link_user_id: Option<i64>,
+    /// Whether the session started by the login is to be remembered
+    pub remember: bool,
+}
+
+/// Store a login under its `state` parameter
+pub async fn save_login(
+    conn: &libsql::Connection,
+    state: &str,
+    login: &PendingLogin,
+) -> Result<()> {
+    let now = chrono::Utc::now().timestamp();
+
+    conn.execute(
+        "DELETE FROM oidc_logins WHERE expires <= datetime(?, 'unixepoch')",
+        libsql::params![now],
+    )
+    .await?;
+    conn.execute(
+        "INSERT INTO oidc_logins
+            (state_hash, provider, nonce, pkce_verifier, link_user_id, remember, expires)
+         VALUES (?, ?, ?, ?, ?, ?, datetime(?, 'unixepoch'))",
+        libsql::params![
+            sha256_hex(state),
+            login.provider.clone(),
+            login.nonce.clone(),
+            login.pkce_verifier.clone(),
+            login.link_user_id,
+            login.remember,
+            now + LOGIN_LIFETIME_SECS
+        ],
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Remove and return the login of a `state` parameter, so that it can only be finished once
+pub async fn take_login(conn: &libsql::Connection, state: &str) -> Result<PendingLogin> {
+    let mut rows = conn
+        .query(
+            "DELETE FROM oidc_logins
+             WHERE state_hash = ? AND expires > datetime(?, 'unixepoch')
+             RETURNING provider, nonce, pkce_verifier, link_user_id, remember",
+            libsql::params![sha256_hex(state), chrono::Utc::now().timestamp()],
+        )
+        .await?;
+    // Read until the end, the statement must be done before anything else runs
+    let mut logins = Vec::new();
+    while let Some(row) = rows.next().await? {
+        logins.push(PendingLogin {
+            provider: row.get(0)?,
+            nonce: row.get(1)?,
+            pkce_verifier: row.get(2)?,
+            link_user_id: row.get(3)?,
+            remember: row.get(4)?,
+        });
+    }
+
+    logins
+        .pop()
+        .ok_or_else(|| ServerFnError::new("This login has expired, please try again"))
+}
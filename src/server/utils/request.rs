@@ -0,0 +1,36 @@
+use std::env;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::OnceLock;
+
+use axum::extract::ConnectInfo;
+use axum::http::HeaderMap;
+
+/// Whether `TRUST_PROXY` is `true`, meaning that the server is only reached through a reverse
+/// proxy which appends the address of its clients to `X-Forwarded-For`
+fn trust_proxy() -> bool {
+    static TRUST_PROXY: OnceLock<bool> = OnceLock::new();
+    *TRUST_PROXY.get_or_init(|| env::var("TRUST_PROXY").is_ok_and(|value| value == "true"))
+}
+
+/// IP address of the client of the current request
+pub async fn client_ip() -> Option<IpAddr> {
+    if trust_proxy() {
+        let headers: HeaderMap = leptos_axum::extract().await.ok()?;
+        // The last address is the one the proxy added, clients can put anything before it
+        let forwarded = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .next_back()
+            .and_then(|ip| ip.trim().parse().ok());
+        if forwarded.is_some() {
+            return forwarded;
+        }
+    }
+
+    let ConnectInfo(addr) = leptos_axum::extract::<ConnectInfo<SocketAddr>>()
+        .await
+        .ok()?;
+    Some(addr.ip())
+}
@@ -1,122 +1,433 @@
+use super::crypto::{random_token, sha256_hex, sign, verify};
+use super::csrf::renew_csrf_secret;
 use super::db;
+use super::request::client_ip;
+use crate::models::permission::Permission;
 use crate::models::session::{SessionUser, ThemePreference};
+use crate::server::roles::user_permissions;
 use axum::http::header::SET_COOKIE;
-use axum::http::{HeaderName, HeaderValue};
+use axum::http::HeaderValue;
 use axum_extra::extract::CookieJar;
 use leptos::prelude::*;
 use leptos_axum::ResponseOptions;
 use libsql::params;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::env;
+use std::sync::OnceLock;
 
-async fn get_session_id() -> Result<String, ServerFnError> {
+#[derive(Debug, thiserror::Error)]
+pub enum SessionSetupError {
+    #[error("{0} must be a positive number")]
+    InvalidNumber(&'static str),
+    #[error("{0} must be true or false")]
+    InvalidBool(&'static str),
+    #[error(
+        "SESSION_IDLE_SECS, SESSION_REMEMBER_SECS and SESSION_MAX_SECS must be in increasing order"
+    )]
+    UnorderedLifetimes,
+    #[error("SESSION_COOKIE_HOST_PREFIX requires a Secure cookie without SESSION_COOKIE_DOMAIN")]
+    InvalidHostPrefix,
+}
+
+#[derive(Debug, Clone)]
+pub struct SessionConfig {
+    /// Sessions unused for that long expire
+    pub idle_secs: i64,
+    /// Same for sessions of users who asked to stay logged in
+    pub remember_secs: i64,
+    /// Sessions expire that long after they were created, however much they are used
+    pub max_secs: i64,
+    /// Empty, or `__Host-` for browsers to only accept the cookies from this exact origin
+    pub cookie_prefix: &'static str,
+    pub secure: bool,
+    pub domain: Option<String>,
+}
+
+static CONFIG: OnceLock<SessionConfig> = OnceLock::new();
+
+/// How long the theme of a visitor who isn't logged in is remembered
+const VISITOR_THEME_LIFETIME_SECS: i64 = 365 * 24 * 60 * 60;
+
+/// Read the session settings from `SESSION_IDLE_SECS`, `SESSION_REMEMBER_SECS`,
+/// `SESSION_MAX_SECS`, `SESSION_COOKIE_SECURE`, `SESSION_COOKIE_DOMAIN` and `SESSION_COOKIE_HOST_PREFIX`. Cookies
+/// are `Secure` by default when the site is served over https.
+pub fn init_sessions(site_url: &str) -> Result<(), SessionSetupError> {
+    let number = |name: &'static str, default: i64| match env::var(name) {
+        Ok(value) => value
+            .parse()
+            .ok()
+            .filter(|value| *value > 0)
+            .ok_or(SessionSetupError::InvalidNumber(name)),
+        Err(_) => Ok(default),
+    };
+    let boolean = |name: &'static str, default: bool| match env::var(name).as_deref() {
+        Ok("true") => Ok(true),
+        Ok("false") => Ok(false),
+        Ok(_) => Err(SessionSetupError::InvalidBool(name)),
+        Err(_) => Ok(default),
+    };
+
+    let idle_secs = number("SESSION_IDLE_SECS", 24 * 60 * 60)?;
+    let remember_secs = number("SESSION_REMEMBER_SECS", 30 * 24 * 60 * 60)?;
+    let max_secs = number("SESSION_MAX_SECS", 90 * 24 * 60 * 60)?;
+    if remember_secs < idle_secs || max_secs < remember_secs {
+        return Err(SessionSetupError::UnorderedLifetimes);
+    }
+
+    let secure = boolean("SESSION_COOKIE_SECURE", site_url.starts_with("https://"))?;
+    let domain = env::var("SESSION_COOKIE_DOMAIN")
+        .ok()
+        .filter(|domain| !domain.is_empty());
+    let host_prefix = boolean("SESSION_COOKIE_HOST_PREFIX", false)?;
+    if host_prefix && (!secure || domain.is_some()) {
+        return Err(SessionSetupError::InvalidHostPrefix);
+    }
+
+    let _ = CONFIG.set(SessionConfig {
+        idle_secs,
+        remember_secs,
+        max_secs,
+        cookie_prefix: if host_prefix { "__Host-" } else { "" },
+        secure,
+        domain,
+    });
+
+    Ok(())
+}
+
+fn config() -> &'static SessionConfig {
+    CONFIG.get().expect("Sessions not initialized")
+}
+
+/// Only the hash of a session id is stored, so a leaked database can't be used to take over
+/// sessions
+pub fn hash_session_id(session_id: &str) -> String {
+    sha256_hex(session_id)
+}
+
+/// Name of the cookie `name`, with the prefix browsers enforce restrictions for
+pub fn cookie_name(name: &str) -> String {
+    format!("{}{name}", config().cookie_prefix)
+}
+
+/// The `Set-Cookie` value giving the cookie `name` the `value` for `max_age` seconds, or until
+/// the browser is closed
+pub fn cookie(name: &str, value: &str, max_age: Option<i64>) -> Result<HeaderValue, ServerFnError> {
+    build_cookie(name, value, max_age, "Strict", "/", true)
+}
+
+/// Like [`cookie`], but also sent with navigations coming from other sites, and only to `path`.
+/// Cookies with the `__Host-` prefix are always sent to the whole site, browsers require it.
+pub fn lax_cookie(
+    name: &str,
+    value: &str,
+    max_age: Option<i64>,
+    path: &str,
+) -> Result<HeaderValue, ServerFnError> {
+    build_cookie(name, value, max_age, "Lax", path, true)
+}
+
+/// Like [`lax_cookie`] for the whole site, but readable by the scripts of its pages
+pub fn script_cookie(
+    name: &str,
+    value: &str,
+    max_age: Option<i64>,
+) -> Result<HeaderValue, ServerFnError> {
+    build_cookie(name, value, max_age, "Lax", "/", false)
+}
+
+fn build_cookie(
+    name: &str,
+    value: &str,
+    max_age: Option<i64>,
+    same_site: &str,
+    path: &str,
+    http_only: bool,
+) -> Result<HeaderValue, ServerFnError> {
+    let config = config();
+    let path = if config.cookie_prefix.is_empty() {
+        path
+    } else {
+        "/"
+    };
+    let mut cookie = format!("{}={value}; Path={path}", cookie_name(name));
+    if http_only {
+        cookie.push_str("; HttpOnly");
+    }
+    cookie.push_str(&format!("; SameSite={same_site}"));
+    if let Some(max_age) = max_age {
+        cookie.push_str(&format!("; Max-Age={max_age}"));
+    }
+    if config.secure {
+        cookie.push_str("; Secure");
+    }
+    if let Some(domain) = &config.domain {
+        cookie.push_str(&format!("; Domain={domain}"));
+    }
+
+    Ok(HeaderValue::from_str(&cookie)?)
+}
+
+/// Value of the cookie `name` sent with the request
+async fn get_cookie(name: &str) -> Result<Option<String>, ServerFnError> {
     let cookie_jar: CookieJar = leptos_axum::extract().await?;
 
-    cookie_jar
-        .get("session")
-        .map(|cookie| cookie.value().to_string())
+    Ok(cookie_jar
+        .get(&cookie_name(name))
+        .map(|cookie| cookie.value().to_string()))
+}
+
+pub async fn get_session_id() -> Result<String, ServerFnError> {
+    get_cookie("session")
+        .await?
         .ok_or_else(|| ServerFnError::new("No session cookie found"))
 }
 
+/// Theme chosen by a visitor who isn't logged in. It is kept in a signed cookie rather than a
+/// session, so that visitors don't cost a row in the database.
+pub async fn get_visitor_theme() -> Result<Option<ThemePreference>, ServerFnError> {
+    Ok(get_cookie("theme")
+        .await?
+        .and_then(|value| verify(&value, "theme"))
+        .and_then(|value| value.parse().ok()))
+}
+
+/// Remember the theme of a visitor who isn't logged in, see [`get_visitor_theme`]. The cookie
+/// is Lax, pages opened from links on other sites would otherwise show the default theme.
+pub fn set_visitor_theme(theme_preference: ThemePreference) -> Result<(), ServerFnError> {
+    expect_context::<ResponseOptions>().append_header(
+        SET_COOKIE,
+        lax_cookie(
+            "theme",
+            &sign(theme_preference.as_str(), "theme"),
+            Some(VISITOR_THEME_LIFETIME_SECS),
+            "/",
+        )?,
+    );
+
+    Ok(())
+}
+
 pub async fn get_user_session() -> Result<Option<SessionUser>, ServerFnError> {
     let Ok(session_id) = get_session_id().await else {
         return Ok(None);
     };
+    let id_hash = hash_session_id(&session_id);
 
     let conn = db::get_db();
-
-    // Get the current timestamp in seconds
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
+    let config = config();
+    let now = chrono::Utc::now().timestamp();
 
     let mut rows = conn
         .query(
-            "SELECT user_id, username, is_admin, theme_preference FROM sessions WHERE id = ?1 AND expires > datetime(?2, 'unixepoch')",
-            params![session_id, now],
+            "SELECT user_id, username, theme_preference, remember,
+                CAST(strftime('%s', expires) AS INTEGER)
+             FROM sessions WHERE id_hash = ?1 AND expires > datetime(?2, 'unixepoch')",
+            params![id_hash.clone(), now],
         )
         .await?;
 
-    let row = rows
+    let Some(row) = rows
         .next()
         .await
-        .map_err(|e| ServerFnError::new(e.to_string()))?;
-
-    if let Some(row) = row {
-        let user = SessionUser {
-            id: row.get(0)?,
-            username: row.get(1)?,
-            is_admin: row.get(2)?,
-            theme_preference: ThemePreference::from_libsql_value(row.get(3)?),
-        };
-        Ok(Some(user))
+        .map_err(|e| ServerFnError::new(e.to_string()))?
+    else {
+        return Ok(None);
+    };
+    let id: Option<i64> = row.get(0)?;
+    let username = row.get(1)?;
+    let theme_preference = ThemePreference::from_libsql_value(row.get(2)?);
+    let idle_secs = if row.get::<bool>(3)? {
+        config.remember_secs
     } else {
-        Ok(None)
+        config.idle_secs
+    };
+    let expires: i64 = row.get(4)?;
+    drop(rows);
+
+    // Push back the expiry, never past the absolute lifetime, and note where the session was
+    // last used. Skipped while it was done less than a minute ago, or a tenth of the idle
+    // timeout when that is shorter.
+    let refresh_margin = (idle_secs / 10).min(60);
+    if expires < now + idle_secs - refresh_margin {
+        conn.execute(
+            "UPDATE sessions
+             SET expires = MIN(datetime(?1, 'unixepoch'), datetime(created_at, ?2)),
+                last_seen_at = datetime(?3, 'unixepoch'), ip = ?4
+             WHERE id_hash = ?5",
+            params![
+                now + idle_secs,
+                format!("+{} seconds", config.max_secs),
+                now,
+                client_ip().await.map(|ip| ip.to_string()),
+                id_hash,
+            ],
+        )
+        .await?;
     }
+
+    // Resolved on every request so that role changes apply to existing sessions
+    let permissions = match id {
+        Some(id) => user_permissions(conn, id).await?,
+        None => Default::default(),
+    };
+    Ok(Some(SessionUser {
+        id,
+        username,
+        permissions,
+        theme_preference,
+    }))
+}
+
+/// The current user, provided they were granted `permission`. This is the guard server
+/// functions use for authorization.
+pub async fn require_permission(permission: Permission) -> Result<SessionUser, ServerFnError> {
+    get_user_session()
+        .await?
+        .filter(|user| user.has_permission(permission))
+        .ok_or_else(|| ServerFnError::new("Forbidden"))
 }
 
+/// Store `user` in the current session. A session of someone else, such as the anonymous
+/// session of a visitor logging in, is replaced by a new one, see [`start_session`].
 pub async fn set_user_session(user: &SessionUser) -> Result<(), ServerFnError> {
-    let existing_session_id = get_session_id().await;
-    let session_id = existing_session_id
-        .clone()
-        .unwrap_or_else(|_| uuid::Uuid::new_v4().to_string());
-
-    // Calculate expiration time (24 hours from now)
-    let expires = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs()
-        + 24 * 60 * 60;
+    if let Ok(session_id) = get_session_id().await {
+        let conn = db::get_db();
+        let id_hash = hash_session_id(&session_id);
+        let mut rows = conn
+            .query(
+                "SELECT user_id FROM sessions WHERE id_hash = ? AND expires > datetime(?, 'unixepoch')",
+                params![id_hash.clone(), chrono::Utc::now().timestamp()],
+            )
+            .await?;
+        let session_user_id = match rows.next().await? {
+            Some(row) => Some(row.get::<Option<i64>>(0)?),
+            None => None,
+        };
+        drop(rows);
+
+        if session_user_id == Some(user.id) {
+            conn.execute(
+                "UPDATE sessions SET username = ?, theme_preference = ? WHERE id_hash = ?",
+                params![user.username.clone(), user.theme_preference, id_hash],
+            )
+            .await?;
+            return Ok(());
+        }
+    }
+
+    start_session(user, false).await
+}
+
+/// Log `user` in with a new session replacing the current one, so that an id planted before a
+/// login is worthless after it, and so are CSRF tokens. Sessions to `remember` last longer and
+/// outlive the browser. The theme the user chose before logging in, if any, becomes theirs.
+pub async fn start_session(user: &SessionUser, remember: bool) -> Result<(), ServerFnError> {
+    use axum::http::{header::USER_AGENT, HeaderMap};
 
     let conn = db::get_db();
+    let config = config();
+    let now = chrono::Utc::now().timestamp();
+
+    if let Ok(session_id) = get_session_id().await {
+        conn.execute(
+            "DELETE FROM sessions WHERE id_hash = ?",
+            params![hash_session_id(&session_id)],
+        )
+        .await?;
+    }
+
+    let mut theme_preference = user.theme_preference;
+    let visitor_theme = get_visitor_theme().await?;
+    if let (Some(user_id), Some(visitor_theme)) = (user.id, visitor_theme) {
+        conn.execute(
+            "UPDATE users SET theme_preference = ? WHERE id = ?",
+            params![visitor_theme, user_id],
+        )
+        .await?;
+        theme_preference = visitor_theme;
+    }
+
+    let session_id = random_token();
+
+    let headers: HeaderMap = leptos_axum::extract().await?;
+    let user_agent = headers
+        .get(USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.chars().take(512).collect::<String>());
+    let idle_secs = if remember {
+        config.remember_secs
+    } else {
+        config.idle_secs
+    };
 
-    // Insert new session into database
     conn.execute(
-        "INSERT OR REPLACE INTO sessions (id, user_id, username, is_admin, expires, theme_preference) VALUES (?1, ?2, ?3, ?4, datetime(?5, 'unixepoch'), ?6)",
+        "INSERT INTO sessions (id_hash, user_id, username, theme_preference, created_at, expires,
+            user_agent, ip, last_seen_at, remember, public_id)
+         VALUES (?1, ?2, ?3, ?4, datetime(?5, 'unixepoch'), datetime(?6, 'unixepoch'), ?7, ?8,
+            datetime(?5, 'unixepoch'), ?9, ?10)",
         params![
-            session_id.clone(),
+            hash_session_id(&session_id),
             user.id,
             user.username.clone(),
-            user.is_admin,
-            expires,
-            user.theme_preference,
+            theme_preference,
+            now,
+            now + idle_secs,
+            user_agent,
+            client_ip().await.map(|ip| ip.to_string()),
+            remember,
+            random_token(),
         ],
     )
     .await
     .map_err(|e| ServerFnError::new(e.to_string()))?;
 
-    // We need to set the cookie in the response unless the user already has a session cookie
-    if existing_session_id.is_err() {
-        let header_name: HeaderName = "Set-Cookie".parse().unwrap();
-        let header_value: HeaderValue = HeaderValue::from_str(&format!(
-            "session={}; Path=/; HttpOnly; SameSite=Strict; Max-Age=86400",
-            session_id
-        ))?;
-
-        let response = expect_context::<ResponseOptions>();
-        response.insert_header(header_name, header_value);
+    // The database decides when the session expires, the cookie only has to outlive it
+    let response = expect_context::<ResponseOptions>();
+    response.append_header(
+        SET_COOKIE,
+        cookie("session", &session_id, remember.then_some(config.max_secs))?,
+    );
+    if visitor_theme.is_some() {
+        response.append_header(SET_COOKIE, lax_cookie("theme", "", Some(0), "/")?);
     }
+    renew_csrf_secret()?;
 
     Ok(())
 }
 
+/// Log the user out, also renewing the CSRF tokens of the browser
 pub async fn clear_user() -> Result<(), ServerFnError> {
     if let Ok(session_id) = get_session_id().await {
         let conn = db::get_db();
 
         // Delete session from database
-        conn.execute("DELETE FROM sessions WHERE id = ?1", params![session_id])
-            .await?;
+        conn.execute(
+            "DELETE FROM sessions WHERE id_hash = ?1",
+            params![hash_session_id(&session_id)],
+        )
+        .await?;
     }
 
-    let header_name = SET_COOKIE;
-    let header_value = HeaderValue::from_str(
-        "session=; Path=/; HttpOnly; SameSite=Strict; Expires=Thu, 01 Jan 1970 00:00:00 GMT",
-    )
-    .unwrap();
-
     let response = expect_context::<ResponseOptions>();
-    response.insert_header(header_name, header_value);
+    response.append_header(SET_COOKIE, cookie("session", "", Some(0))?);
+    renew_csrf_secret()?;
 
     Ok(())
 }
+
+/// Delete up to `limit` sessions that have expired. Returns how many were deleted.
+pub async fn purge_expired_sessions_batch(
+    conn: &libsql::Connection,
+    limit: u64,
+) -> Result<u64, ServerFnError> {
+    let now = chrono::Utc::now().timestamp();
+
+    Ok(conn
+        .execute(
+            "DELETE FROM sessions WHERE rowid IN (
+                SELECT rowid FROM sessions WHERE expires <= datetime(?1, 'unixepoch') LIMIT ?2
+             )",
+            params![now, limit],
+        )
+        .await?)
+}
@@ -0,0 +1,235 @@
+use std::env;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+
+use leptos::prelude::ServerFnError;
+
+type Result<T> = std::result::Result<T, ServerFnError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ThrottleSetupError {
+    #[error("{0} must be a positive number")]
+    InvalidNumber(&'static str),
+}
+
+/// When failed logins start to slow down and then block a username or an address
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// Failures allowed before each new one makes the next login wait, twice as long every time
+    pub backoff_after: i64,
+    /// Failures after which logins are refused for [`ThrottleConfig::lockout_secs`]
+    pub lockout_after: i64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ThrottleConfig {
+    pub username: Limits,
+    /// Higher than per username, several users may share an address
+    pub ip: Limits,
+    /// Longest wait between two logins before the lockout
+    pub backoff_max_secs: i64,
+    /// How long a lockout lasts. Failures are also forgotten after that long without any.
+    pub lockout_secs: i64,
+}
+
+static CONFIG: OnceLock<ThrottleConfig> = OnceLock::new();
+
+/// Read the limits from `LOGIN_BACKOFF_AFTER`, `LOGIN_LOCKOUT_AFTER`, `LOGIN_IP_BACKOFF_AFTER`,
+/// `LOGIN_IP_LOCKOUT_AFTER`, `LOGIN_BACKOFF_MAX_SECS` and `LOGIN_LOCKOUT_SECS`
+pub fn init_login_throttle() -> std::result::Result<(), ThrottleSetupError> {
+    let var = |name: &'static str, default: i64| match env::var(name) {
+        Ok(value) => value
+            .parse()
+            .ok()
+            .filter(|value| *value > 0)
+            .ok_or(ThrottleSetupError::InvalidNumber(name)),
+        Err(_) => Ok(default),
+    };
+
+    let _ = CONFIG.set(ThrottleConfig {
+        username: Limits {
+            backoff_after: var("LOGIN_BACKOFF_AFTER", 3)?,
+            lockout_after: var("LOGIN_LOCKOUT_AFTER", 10)?,
+        },
+        ip: Limits {
+            backoff_after: var("LOGIN_IP_BACKOFF_AFTER", 20)?,
+            lockout_after: var("LOGIN_IP_LOCKOUT_AFTER", 100)?,
+        },
+        backoff_max_secs: var("LOGIN_BACKOFF_MAX_SECS", 60)?,
+        lockout_secs: var("LOGIN_LOCKOUT_SECS", 15 * 60)?,
+    });
+
+    Ok(())
+}
+
+fn config() -> &'static ThrottleConfig {
+    CONFIG.get().expect("Login throttling not initialized")
+}
+
+/// The usernames and addresses logins are tracked by
+fn keys(username: &str, ip: Option<IpAddr>) -> Vec<(&'static str, String, Limits)> {
+    let config = config();
+    let mut keys = vec![("username", username.to_string(), config.username)];
+    if let Some(ip) = ip {
+        keys.push(("ip", ip.to_string(), config.ip));
+    }
+    keys
+}
+
+/// Count a login of `username` from `ip` before its password is checked, refusing it while
+/// either is blocked. Counting up front keeps parallel guesses from all getting through before
+/// the first failure is recorded. The count is given back by [`login_succeeded`].
+pub async fn reserve_login_attempt(
+    conn: &libsql::Connection,
+    username: &str,
+    ip: Option<IpAddr>,
+) -> Result<()> {
+    let config = config();
+    let now = chrono::Utc::now().timestamp();
+
+    conn.execute(
+        "DELETE FROM login_failures WHERE last_failure_at <= datetime(?, 'unixepoch')
+            AND (blocked_until IS NULL OR blocked_until <= datetime(?, 'unixepoch'))",
+        libsql::params![now - config.lockout_secs, now],
+    )
+    .await?;
+
+    for (scope, key, limits) in keys(username, ip) {
+        // Nothing is returned while blocked
+        let mut rows = conn
+            .query(
+                "INSERT INTO login_failures (scope, key, failures, last_failure_at)
+                 VALUES (?1, ?2, 1, datetime(?3, 'unixepoch'))
+                 ON CONFLICT (scope, key) DO UPDATE
+                 SET failures = failures + 1, last_failure_at = excluded.last_failure_at
+                 WHERE blocked_until IS NULL OR blocked_until <= excluded.last_failure_at
+                 RETURNING failures",
+                libsql::params![scope, key.clone(), now],
+            )
+            .await?;
+        // Read until the end, the statement must be done before anything else runs
+        let mut failures = None;
+        while let Some(row) = rows.next().await? {
+            failures = Some(row.get::<i64>(0)?);
+        }
+
+        let Some(failures) = failures else {
+            let mut rows = conn
+                .query(
+                    "SELECT CAST(strftime('%s', blocked_until) AS INTEGER) FROM login_failures
+                     WHERE scope = ? AND key = ?",
+                    libsql::params![scope, key.clone()],
+                )
+                .await?;
+            let blocked_until = match rows.next().await? {
+                Some(row) => row.get::<i64>(0)?,
+                None => now,
+            };
+            let wait = blocked_until - now;
+            tracing::info!(scope, key, wait, "Refused a blocked login");
+            return Err(ServerFnError::new(format!(
+                "Too many failed logins, try again in {}",
+                duration(wait)
+            )));
+        };
+
+        // Blocks the attempts after this one, until it succeeds
+        let block_secs = if failures >= limits.lockout_after {
+            tracing::warn!(
+                scope,
+                key,
+                failures,
+                lockout_secs = config.lockout_secs,
+                "Locked out logins after too many failures"
+            );
+            config.lockout_secs
+        } else if failures > limits.backoff_after {
+            let doublings = (failures - limits.backoff_after - 1).min(32) as u32;
+            2_i64.pow(doublings).min(config.backoff_max_secs)
+        } else {
+            continue;
+        };
+
+        conn.execute(
+            "UPDATE login_failures SET blocked_until = datetime(?, 'unixepoch')
+             WHERE scope = ? AND key = ?",
+            libsql::params![now + block_secs, scope, key],
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+fn duration(secs: i64) -> String {
+    match secs {
+        ..=1 => "a second".to_string(),
+        2..=59 => format!("{secs} seconds"),
+        60..=119 => "a minute".to_string(),
+        _ => format!("{} minutes", (secs + 59) / 60),
+    }
+}
+
+/// Start the wait [`reserve_login_attempt`] may have set from now that the login of `username`
+/// from `ip` failed, rather than from when it started, checking a password takes a while
+pub async fn login_failed(
+    conn: &libsql::Connection,
+    username: &str,
+    ip: Option<IpAddr>,
+) -> Result<()> {
+    let now = chrono::Utc::now().timestamp();
+
+    for (scope, key, _) in keys(username, ip) {
+        conn.execute(
+            "UPDATE login_failures SET blocked_until = datetime(
+                ?1 + strftime('%s', blocked_until) - strftime('%s', last_failure_at), 'unixepoch'
+             ), last_failure_at = datetime(?1, 'unixepoch')
+             WHERE scope = ?2 AND key = ?3 AND blocked_until IS NOT NULL",
+            libsql::params![now, scope, key],
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Forget the failed logins of `username` once it logged in. Only the attempt reserved for
+/// this login is given back to its address, a guesser could otherwise reset the failures of
+/// the address with an account of their own.
+pub async fn login_succeeded(
+    conn: &libsql::Connection,
+    username: &str,
+    ip: Option<IpAddr>,
+) -> Result<()> {
+    conn.execute(
+        "DELETE FROM login_failures WHERE scope = 'username' AND key = ?",
+        libsql::params![username],
+    )
+    .await?;
+
+    password_accepted(conn, ip).await
+}
+
+/// Give back the attempt reserved from `ip` for a password that was right, when the login still
+/// waits for a second factor. The attempt of the username stays counted until the login
+/// succeeds, so that new logins with the password don't reset the failures of codes.
+pub async fn password_accepted(conn: &libsql::Connection, ip: Option<IpAddr>) -> Result<()> {
+    let Some(ip) = ip else {
+        return Ok(());
+    };
+
+    // Lifts the backoff this attempt may have started, not a lockout
+    conn.execute(
+        "UPDATE login_failures SET failures = failures - 1,
+            blocked_until = CASE WHEN failures - 1 > ?1 THEN blocked_until END
+         WHERE scope = 'ip' AND key = ?2 AND failures > 0 AND failures < ?3",
+        libsql::params![
+            config().ip.backoff_after,
+            ip.to_string(),
+            config().ip.lockout_after
+        ],
+    )
+    .await?;
+
+    Ok(())
+}
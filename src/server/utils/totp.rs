@@ -0,0 +1,32 @@
+use std::env;
+use std::sync::OnceLock;
+
+use axum::http::Uri;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TotpSetupError {
+    #[error("the site URL is not a valid URL with a host")]
+    InvalidUrl,
+}
+
+static ISSUER: OnceLock<String> = OnceLock::new();
+
+/// Name authenticator apps list accounts under: `TOTP_ISSUER`, or else the host of `site_url`
+pub fn init_totp(site_url: &str) -> Result<(), TotpSetupError> {
+    let issuer = match env::var("TOTP_ISSUER") {
+        Ok(issuer) if !issuer.trim().is_empty() => issuer.trim().to_string(),
+        _ => site_url
+            .parse::<Uri>()
+            .ok()
+            .and_then(|uri| uri.host().map(str::to_string))
+            .ok_or(TotpSetupError::InvalidUrl)?,
+    };
+    // A colon separates the issuer from the account name in the key URI
+    let _ = ISSUER.set(issuer.replace(':', "_"));
+
+    Ok(())
+}
+
+pub fn issuer() -> &'static str {
+    ISSUER.get().expect("TOTP not initialized")
+}
@@ -0,0 +1,200 @@
+use std::net::IpAddr;
+use std::sync::OnceLock;
+
+use leptos::prelude::ServerFnError;
+use serde::{Deserialize, Serialize};
+use webauthn_rs::prelude::*;
+
+use super::crypto::{random_token, sha256_hex};
+
+type Result<T> = std::result::Result<T, ServerFnError>;
+
+/// How long the authenticator may take to answer
+const CEREMONY_LIFETIME_SECS: i64 = 5 * 60;
+
+#[derive(Debug, thiserror::Error)]
+pub enum WebauthnSetupError {
+    #[error("the site URL is not a valid URL with a host")]
+    InvalidUrl,
+    #[error(transparent)]
+    Webauthn(#[from] WebauthnError),
+}
+
+static WEBAUTHN: OnceLock<Webauthn> = OnceLock::new();
+
+/// Set up passkeys for the site at `site_url`, which is their relying party. Browsers don't
+/// accept IP addresses as relying party, so a local address is replaced by `localhost`.
+pub fn init_webauthn(site_url: &str) -> std::result::Result<(), WebauthnSetupError> {
+    let mut origin = Url::parse(site_url).map_err(|_| WebauthnSetupError::InvalidUrl)?;
+    let host = origin
+        .host_str()
+        .ok_or(WebauthnSetupError::InvalidUrl)?
+        .trim_matches(['[', ']'])
+        .to_string();
+    if host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback()) {
+        origin
+            .set_host(Some("localhost"))
+            .map_err(|_| WebauthnSetupError::InvalidUrl)?;
+    }
+
+    let rp_id = origin
+        .host_str()
+        .ok_or(WebauthnSetupError::InvalidUrl)?
+        .to_string();
+    let webauthn = WebauthnBuilder::new(&rp_id, &origin)?
+        .rp_name("Léo Coletta's blog")
+        .build()?;
+    let _ = WEBAUTHN.set(webauthn);
+
+    Ok(())
+}
+
+pub fn webauthn() -> &'static Webauthn {
+    WEBAUTHN.get().expect("WebAuthn not initialized")
+}
+
+/// Failure of a ceremony, logged with its cause which isn't meant for users
+pub fn webauthn_error(e: WebauthnError) -> ServerFnError {
+    tracing::warn!("Passkey ceremony failed: {e}");
+    ServerFnError::new("The passkey could not be verified")
+}
+
+/// What the server remembers of a ceremony between sending options to the browser and
+/// checking the answer of the authenticator
+#[derive(Serialize, Deserialize)]
+pub enum CeremonyState {
+    /// Creating an account along with its first passkey
+    Signup {
+        username: String,
+        email: Option<String>,
+        user_handle: Uuid,
+        registration: PasskeyRegistration,
+    },
+    /// Adding a passkey to an existing account
+    Registration {
+        user_id: i64,
+        registration: PasskeyRegistration,
+    },
+    Login {
+        user_id: i64,
+        authentication: PasskeyAuthentication,
+    },
+}
+
+/// Store the state of a ceremony. Returns the token to send back with the answer.
+pub async fn start_ceremony(conn: &libsql::Connection, state: &CeremonyState) -> Result<String> {
+    let token = random_token();
+    let now = chrono::Utc::now().timestamp();
+
+    conn.execute(
+        "DELETE FROM passkey_ceremonies WHERE expires <= datetime(?, 'unixepoch')",
+        libsql::params![now],
+    )
+    .await?;
+    conn.execute(
+        "INSERT INTO passkey_ceremonies (token_hash, state, expires)
+         VALUES (?, ?, datetime(?, 'unixepoch'))",
+        libsql::params![
+            sha256_hex(&token),
+            serde_json::to_string(state)?,
+            now + CEREMONY_LIFETIME_SECS
+        ],
+    )
+    .await?;
+
+    Ok(token)
+}
+
+/// Remove and return the state of a ceremony, so that an answer can only be checked once
+pub async fn take_ceremony(conn: &libsql::Connection, token: &str) -> Result<CeremonyState> {
+    let mut rows = conn
+        .query(
+            "DELETE FROM passkey_ceremonies
+             WHERE token_hash = ? AND expires > datetime(?, 'unixepoch')
+             RETURNING state",
+            libsql::params![sha256_hex(token), chrono::Utc::now().timestamp()],
+        )
+        .await?;
+    // Read until the end, the statement must be done before anything else runs
+    let mut states = Vec::new();
+    while let Some(row) = rows.next().await? {
+        states.push(row.get::<String>(0)?);
+    }
+    let Some(state) = states.first() else {
+        return Err(ServerFnError::new(
+            "This passkey request has expired, please try again",
+        ));
+    };
+
+    Ok(serde_json::from_str(state)?)
+}
+
+/// Handle identifying a user to authenticators, assigned the first time it is needed
+pub async fn user_handle(conn: &libsql::Connection, user_id: i64) -> Result<Uuid> {
+    let mut rows = conn
+        .query(
+            "SELECT webauthn_id FROM users WHERE id = ?",
+            libsql::params![user_id],
+        )
+        .await?;
+    let Some(row) = rows.next().await? else {
+        return Err(ServerFnError::new("Not found"));
+    };
+    if let Some(handle) = row.get::<Option<String>>(0)? {
+        return Ok(handle.parse()?);
+    }
+
+    let handle = Uuid::new_v4();
+    conn.execute(
+        "UPDATE users SET webauthn_id = ? WHERE id = ?",
+        libsql::params![handle.to_string(), user_id],
+    )
+    .await?;
+
+    Ok(handle)
+}
+
+/// Every passkey of a user
+pub async fn user_passkeys(conn: &libsql::Connection, user_id: i64) -> Result<Vec<Passkey>> {
+    let mut rows = conn
+        .query(
+            "SELECT public_key FROM credentials WHERE user_id = ?",
+            libsql::params![user_id],
+        )
+        .await?;
+
+    let mut passkeys = Vec::new();
+    while let Some(row) = rows.next().await? {
+        passkeys.push(serde_json::from_str(&row.get::<String>(0)?)?);
+    }
+
+    Ok(passkeys)
+}
+
+/// Store a newly registered passkey of a user
+pub async fn insert_passkey(
+    conn: &libsql::Connection,
+    user_id: i64,
+    name: &str,
+    passkey: &Passkey,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO credentials (id, user_id, name, public_key) VALUES (?, ?, ?, ?)",
+        libsql::params![
+            hex::encode(passkey.cred_id()),
+            user_id,
+            name,
+            serde_json::to_string(passkey)?
+        ],
+    )
+    .await
+    .map_err(|e| {
+        if e.to_string().contains("UNIQUE constraint failed") {
+            ServerFnError::new("This passkey is already registered")
+        } else {
+            ServerFnError::new("Database error")
+        }
+    })?;
+
+    Ok(())
+}
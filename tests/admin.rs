@@ -0,0 +1,133 @@
+//! Actions of the admin dashboard need more than access to it
+
+mod common;
+
+use common::Server;
+
+/// The server with `alice` as admin, `bob` who may only open the dashboard and `carol` as
+/// reader, with ids 1, 2 and 3
+async fn start_server() -> Server {
+    let server = Server::start(&[]).await;
+    for username in ["alice", "bob", "carol"] {
+        server.create_admin(username);
+    }
+    server
+        .sql(
+            "INSERT INTO roles (name) VALUES ('moderator');
+             INSERT INTO role_permissions (role_id, permission_id)
+             SELECT roles.id, permissions.id FROM roles, permissions
+             WHERE roles.name = 'moderator' AND permissions.name = 'admin.access';
+             DELETE FROM user_roles WHERE user_id IN (2, 3);
+             INSERT INTO user_roles (user_id, role_id)
+             SELECT 2, id FROM roles WHERE name = 'moderator';
+             INSERT INTO user_roles (user_id, role_id)
+             SELECT 3, id FROM roles WHERE name = 'reader';",
+        )
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn dashboard_access_alone_changes_nothing() {
+    let server = start_server().await;
+    let alice = server.logged_in("alice").await;
+    let bob = server.logged_in("bob").await;
+    let post = server
+        .call(
+            &alice,
+            "/api/blog/create_post",
+            &[
+                ("new_post[title]", "Hello"),
+                ("new_post[slug]", ""),
+                ("new_post[content]", "Some content"),
+                ("new_post[published]", "false"),
+            ],
+        )
+        .await
+        .unwrap();
+    let post_id = post["id"].to_string();
+
+    for (path, args) in [
+        (
+            "/api/admin/set_user_disabled",
+            vec![("id", "3"), ("disabled", "true")],
+        ),
+        ("/api/admin/delete_user", vec![("id", "3")]),
+        ("/api/admin/revoke_session", vec![("id", "1")]),
+        (
+            "/api/admin/bulk_update_posts",
+            vec![("ids[0]", post_id.as_str()), ("action", "Publish")],
+        ),
+        (
+            "/api/admin/bulk_update_posts",
+            vec![("ids[0]", post_id.as_str()), ("action", "Delete")],
+        ),
+    ] {
+        let error = server.call(&bob, path, &args).await.unwrap_err();
+        assert!(error.contains("Forbidden"), "{path}: {error}");
+    }
+
+    // Admins may
+    server
+        .call(
+            &alice,
+            "/api/admin/bulk_update_posts",
+            &[("ids[0]", post_id.as_str()), ("action", "Delete")],
+        )
+        .await
+        .unwrap();
+    server
+        .call(
+            &alice,
+            "/api/admin/set_user_disabled",
+            &[("id", "3"), ("disabled", "true")],
+        )
+        .await
+        .unwrap();
+}
+
+/// The public id of the session of `username` in the admin list
+async fn session_id(server: &Server, admin: &reqwest::Client, username: &str) -> String {
+    let list = server
+        .call(admin, "/api/admin/sessions", &[])
+        .await
+        .unwrap();
+    let sessions = list["sessions"].as_array().unwrap();
+    let session = sessions
+        .iter()
+        .find(|session| session["username"] == username)
+        .unwrap();
+    session["id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn revoking_a_stale_session_leaves_newer_ones_alone() {
+    let server = start_server().await;
+    let alice = server.logged_in("alice").await;
+    let carol = server.logged_in("carol").await;
+
+    let id = session_id(&server, &alice, "carol").await;
+    server
+        .call(&alice, "/api/admin/revoke_session", &[("id", id.as_str())])
+        .await
+        .unwrap();
+    let user = server
+        .call(&carol, "/api/auth/current_user", &[])
+        .await
+        .unwrap();
+    assert!(user.is_null(), "{user}");
+
+    // The newest session is gone, a new one may get its row id back but not its public id
+    server.log_in(&carol, "carol").await;
+    assert_ne!(session_id(&server, &alice, "carol").await, id);
+    let error = server
+        .call(&alice, "/api/admin/revoke_session", &[("id", id.as_str())])
+        .await
+        .unwrap_err();
+    assert!(error.contains("Not found"), "{error}");
+    let user = server
+        .call(&carol, "/api/auth/current_user", &[])
+        .await
+        .unwrap();
+    assert_eq!(user["username"], "carol");
+}
@@ -0,0 +1,223 @@
+//! The server binary, run for integration tests
+
+// Each test crate uses a part of it
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::cookie::Jar;
+use reqwest::redirect::Policy;
+use serde_json::Value;
+
+/// Password of the users tests create
+pub const PASSWORD: &str = "correct horse battery staple";
+
+/// The server with its own database, stopped when dropped
+pub struct Server {
+    pub url: String,
+    process: Child,
+    dir: tempfile::TempDir,
+}
+
+fn command(dir: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_blog"));
+    command
+        .current_dir(dir)
+        .env("LEPTOS_OUTPUT_NAME", "blog")
+        .env("RUST_LOG", "warn")
+        .stdout(Stdio::null());
+    command
+}
+
+impl Server {
+    /// Start the server with these environment variables in addition to the ones it needs
+    pub async fn start(env: &[(&str, &str)]) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let url = format!("http://{addr}");
+
+        let process = command(dir.path())
+            .env("LEPTOS_SITE_ADDR", addr.to_string())
+            .env("SITE_URL", &url)
+            .envs(env.iter().copied())
+            .spawn()
+            .unwrap();
+
+        // Stopped when dropped, also if it doesn't start in time
+        let server = Self { url, process, dir };
+        for _ in 0..100 {
+            if tokio::net::TcpStream::connect(addr).await.is_ok() {
+                return server;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("the server didn't start");
+    }
+
+    /// Start the server like [`Server::start`], with `alice` as admin
+    pub async fn with_admin(env: &[(&str, &str)]) -> Self {
+        let server = Self::start(env).await;
+        server.create_admin("alice");
+        server
+    }
+
+    /// Create the admin `username`, with [`PASSWORD`] as password
+    pub fn create_admin(&self, username: &str) {
+        self.run(
+            &["create-admin", username, "--password-stdin"],
+            &format!("{PASSWORD}\n"),
+        );
+    }
+
+    /// Log `username` in with `browser`
+    pub async fn log_in(&self, browser: &reqwest::Client, username: &str) {
+        if let Err(error) = self
+            .call(
+                browser,
+                "/api/auth/login",
+                &[
+                    ("credentials[username]", username),
+                    ("credentials[password]", PASSWORD),
+                ],
+            )
+            .await
+        {
+            panic!("{username} couldn't log in: {error}");
+        }
+    }
+
+    /// A new browser with `username` logged in
+    pub async fn logged_in(&self, username: &str) -> reqwest::Client {
+        let browser = self.browser();
+        self.log_in(&browser, username).await;
+        browser
+    }
+
+    /// Run a command of the binary against the database of the server, with `stdin` as input.
+    /// Returns its output.
+    pub fn run(&self, args: &[&str], stdin: &str) -> String {
+        let mut process = command(self.dir.path())
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        process
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(stdin.as_bytes())
+            .unwrap();
+        let output = process.wait_with_output().unwrap();
+        assert!(output.status.success(), "{args:?} failed");
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    /// Run `statements` against the database of the server, for what no command or server
+    /// function does
+    pub async fn sql(&self, statements: &str) {
+        let db = libsql::Builder::new_local(self.dir.path().join("blog.db"))
+            .build()
+            .await
+            .unwrap();
+        db.connect()
+            .unwrap()
+            .execute_batch(statements)
+            .await
+            .unwrap();
+    }
+
+    /// A client with its own cookies, like a browser
+    pub fn browser(&self) -> reqwest::Client {
+        self.browser_builder().build().unwrap()
+    }
+
+    /// A browser behind a proxy which forwards its requests from `ip`
+    pub fn browser_from(&self, ip: &str) -> reqwest::Client {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("x-forwarded-for", ip.parse().unwrap());
+        self.browser_builder()
+            .default_headers(headers)
+            .build()
+            .unwrap()
+    }
+
+    /// A browser whose cookies tests can read and plant
+    pub fn browser_with_cookies(&self) -> (reqwest::Client, Arc<Jar>) {
+        let jar = Arc::new(Jar::default());
+        let browser = self
+            .browser_builder()
+            .cookie_provider(jar.clone())
+            .build()
+            .unwrap();
+        (browser, jar)
+    }
+
+    fn browser_builder(&self) -> reqwest::ClientBuilder {
+        reqwest::Client::builder()
+            .cookie_store(true)
+            .redirect(Policy::none())
+    }
+
+    /// CSRF token of the pages `browser` is shown
+    pub async fn csrf_token(&self, browser: &reqwest::Client) -> String {
+        let page = browser
+            .get(format!("{}/", self.url))
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        page.split(r#"<meta name="csrf-token" content=""#)
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .expect("the page has no CSRF token")
+            .to_string()
+    }
+
+    /// A request to the server function at `path`, made from a page of the site like the
+    /// browser would
+    pub async fn post(&self, browser: &reqwest::Client, path: &str) -> reqwest::RequestBuilder {
+        browser
+            .post(format!("{}{path}", self.url))
+            .header("origin", &self.url)
+            .header("x-csrf-token", self.csrf_token(browser).await)
+    }
+
+    /// Call the server function at `path` with `args`. Returns its output or the error message.
+    pub async fn call(
+        &self,
+        browser: &reqwest::Client,
+        path: &str,
+        args: &[(&str, &str)],
+    ) -> Result<Value, String> {
+        let response = self
+            .post(browser, path)
+            .await
+            .form(args)
+            .send()
+            .await
+            .unwrap();
+        if response.status().is_success() {
+            Ok(response.json().await.unwrap())
+        } else {
+            Err(response.text().await.unwrap())
+        }
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
@@ -0,0 +1,250 @@
+//! Server functions changing something refuse calls made by other sites
+
+mod common;
+
+use reqwest::cookie::{CookieStore, Jar};
+use serde_json::Value;
+
+use common::Server;
+
+const OTHER_SITE: &str = "https://evil.example";
+
+async fn create_post(server: &Server, browser: &reqwest::Client, title: &str) -> Value {
+    server
+        .call(
+            browser,
+            "/api/blog/create_post",
+            &[
+                ("new_post[title]", title),
+                ("new_post[slug]", ""),
+                ("new_post[content]", "Some content"),
+                ("new_post[published]", "true"),
+            ],
+        )
+        .await
+        .unwrap()
+}
+
+/// Call the server function at `path` with `args` and only these `headers`. Returns the status
+/// and the body of the response.
+async fn call_with(
+    server: &Server,
+    browser: &reqwest::Client,
+    path: &str,
+    args: &[(&str, &str)],
+    headers: &[(&str, &str)],
+) -> (u16, String) {
+    let mut request = browser.post(format!("{}{path}", server.url)).form(args);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let response = request.send().await.unwrap();
+    (response.status().as_u16(), response.text().await.unwrap())
+}
+
+/// Check that calls to `path` by `browser` are refused without the token of its pages, or when
+/// they come from another site
+async fn assert_refused_from_other_sites(
+    server: &Server,
+    browser: &reqwest::Client,
+    path: &str,
+    args: &[(&str, &str)],
+) {
+    let token = server.csrf_token(browser).await;
+    let other_token = server.csrf_token(&server.browser()).await;
+    let site = server.url.as_str();
+    let other_page = format!("{OTHER_SITE}/page");
+
+    for (headers, error) in [
+        (vec![("origin", site)], "Missing CSRF token"),
+        (
+            vec![("origin", site), ("x-csrf-token", "00")],
+            "Invalid CSRF token",
+        ),
+        (
+            vec![("origin", site), ("x-csrf-token", &other_token)],
+            "Invalid CSRF token",
+        ),
+        (
+            vec![("origin", OTHER_SITE), ("x-csrf-token", &token)],
+            "Requests from https://evil.example aren't allowed",
+        ),
+        (
+            vec![("referer", &other_page), ("x-csrf-token", &token)],
+            "Requests from https://evil.example aren't allowed",
+        ),
+        (
+            vec![("x-csrf-token", &token)],
+            "Requests must have an Origin or Referer header",
+        ),
+    ] {
+        let (status, body) = call_with(server, browser, path, args, &headers).await;
+        assert_eq!(status, 403, "{headers:?}: {body}");
+        assert!(body.contains(error), "{headers:?}: {body}");
+    }
+}
+
+#[tokio::test]
+async fn calls_from_other_sites_are_refused() {
+    let server = Server::with_admin(&[]).await;
+    let browser = server.logged_in("alice").await;
+    let post = create_post(&server, &browser, "Hello").await;
+    let id = post["id"].to_string();
+
+    for (path, args) in [
+        (
+            "/api/blog/create_post",
+            vec![
+                ("new_post[title]", "Hijacked"),
+                ("new_post[slug]", ""),
+                ("new_post[content]", "Some content"),
+                ("new_post[published]", "true"),
+            ],
+        ),
+        (
+            "/api/blog/update_post",
+            vec![
+                ("update[id]", id.as_str()),
+                ("update[title]", "Hijacked"),
+                ("update[slug]", "hello"),
+                ("update[content]", "Some content"),
+                ("update[published]", "true"),
+            ],
+        ),
+        ("/api/blog/delete_post", vec![("id", id.as_str())]),
+        ("/api/session/set_theme", vec![("theme_preference", "dark")]),
+        ("/api/auth/logout", vec![]),
+    ] {
+        assert_refused_from_other_sites(&server, &browser, path, &args).await;
+    }
+
+    // None of them went through
+    let feed = browser
+        .get(format!("{}/feed.json", server.url))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(
+        feed.contains("Hello") && !feed.contains("Hijacked"),
+        "{feed}"
+    );
+    let theme = server
+        .call(&browser, "/api/session/theme", &[])
+        .await
+        .unwrap();
+    assert_eq!(theme["theme_preference"], "system");
+    let user = server
+        .call(&browser, "/api/auth/current_user", &[])
+        .await
+        .unwrap();
+    assert_eq!(user["username"], "alice");
+
+    // With the token and from the site, they do
+    server
+        .call(&browser, "/api/blog/delete_post", &[("id", id.as_str())])
+        .await
+        .unwrap();
+    server
+        .call(&browser, "/api/auth/logout", &[])
+        .await
+        .unwrap();
+    let user = server
+        .call(&browser, "/api/auth/current_user", &[])
+        .await
+        .unwrap();
+    assert_eq!(user, Value::Null);
+}
+
+#[tokio::test]
+async fn calls_with_a_referer_from_the_site_are_accepted() {
+    let server = Server::start(&[]).await;
+    let browser = server.browser();
+
+    // Browsers which don't send an Origin send a Referer
+    let token = server.csrf_token(&browser).await;
+    let page = format!("{}/blog", server.url);
+    let (status, body) = call_with(
+        &server,
+        &browser,
+        "/api/session/set_theme",
+        &[("theme_preference", "dark")],
+        &[("referer", &page), ("x-csrf-token", &token)],
+    )
+    .await;
+    assert_eq!(status, 200, "{body}");
+    let theme = server
+        .call(&browser, "/api/session/theme", &[])
+        .await
+        .unwrap();
+    assert_eq!(theme["theme_preference"], "dark");
+}
+
+#[tokio::test]
+async fn tokens_are_renewed_when_logging_in_and_out() {
+    let server = Server::with_admin(&[]).await;
+    let (browser, jar) = server.browser_with_cookies();
+
+    let response = browser
+        .get(format!("{}/", server.url))
+        .send()
+        .await
+        .unwrap();
+    let cookies: Vec<_> = response
+        .headers()
+        .get_all("set-cookie")
+        .iter()
+        .map(|cookie| cookie.to_str().unwrap())
+        .collect();
+    assert!(cookies[0].starts_with("csrf="), "{cookies:?}");
+    // Sent with navigations from other sites, so that they don't issue another one
+    assert!(cookies[0].contains("SameSite=Lax"), "{cookies:?}");
+    // Pages read the token from the cookie once it changes
+    assert!(cookies[1].starts_with("csrf-token="), "{cookies:?}");
+    assert!(!cookies[1].contains("HttpOnly"), "{cookies:?}");
+
+    let site = server.url.as_str();
+    let before_login = server.csrf_token(&browser).await;
+    server.log_in(&browser, "alice").await;
+    let token = server.csrf_token(&browser).await;
+    assert_ne!(token, before_login);
+    assert_eq!(token_cookie(&server, &jar).as_deref(), Some(token.as_str()));
+    let (status, body) = call_with(
+        &server,
+        &browser,
+        "/api/auth/logout",
+        &[],
+        &[("origin", site), ("x-csrf-token", &before_login)],
+    )
+    .await;
+    assert_eq!(status, 403, "{body}");
+    assert!(body.contains("Invalid CSRF token"), "{body}");
+
+    server
+        .call(&browser, "/api/auth/logout", &[])
+        .await
+        .unwrap();
+    let (status, body) = call_with(
+        &server,
+        &browser,
+        "/api/session/set_theme",
+        &[("theme_preference", "dark")],
+        &[("origin", site), ("x-csrf-token", &token)],
+    )
+    .await;
+    assert_eq!(status, 403, "{body}");
+    assert_ne!(token_cookie(&server, &jar), Some(token));
+}
+
+/// The token in the cookie pages read it from
+fn token_cookie(server: &Server, jar: &Jar) -> Option<String> {
+    let cookies = jar.cookies(&server.url.parse().unwrap())?;
+    cookies
+        .to_str()
+        .unwrap()
+        .split("; ")
+        .find_map(|cookie| cookie.strip_prefix("csrf-token="))
+        .map(str::to_string)
+}
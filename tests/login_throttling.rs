@@ -0,0 +1,142 @@
+//! Throttling of failed logins per username and per address
+
+mod common;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use common::{Server, PASSWORD};
+
+/// The server with `alice` as user, behind a proxy so that tests pick the client addresses
+async fn start_server(limits: &[(&str, &str)]) -> Server {
+    let mut env = vec![("TRUST_PROXY", "true")];
+    env.extend_from_slice(limits);
+    Server::with_admin(&env).await
+}
+
+async fn log_in(
+    server: &Server,
+    ip: &str,
+    username: &str,
+    password: &str,
+) -> Result<Value, String> {
+    server
+        .call(
+            &server.browser_from(ip),
+            "/api/auth/login",
+            &[
+                ("credentials[username]", username),
+                ("credentials[password]", password),
+            ],
+        )
+        .await
+}
+
+fn assert_blocked(result: Result<Value, String>) {
+    let error = result.unwrap_err();
+    assert!(error.contains("Too many failed logins"), "{error}");
+}
+
+fn assert_invalid(result: Result<Value, String>) {
+    let error = result.unwrap_err();
+    assert!(error.contains("Invalid username or password"), "{error}");
+}
+
+#[tokio::test]
+async fn failures_slow_down_then_lock_out_a_username() {
+    let server = start_server(&[
+        ("LOGIN_BACKOFF_AFTER", "2"),
+        ("LOGIN_LOCKOUT_AFTER", "4"),
+        ("LOGIN_BACKOFF_MAX_SECS", "1"),
+        ("LOGIN_LOCKOUT_SECS", "3"),
+    ])
+    .await;
+
+    // Every attempt from another address, only the username is throttled
+    for ip in ["10.0.0.1", "10.0.0.2"] {
+        assert_invalid(log_in(&server, ip, "alice", "guess").await);
+    }
+    assert_invalid(log_in(&server, "10.0.0.3", "alice", "guess").await);
+    assert_blocked(log_in(&server, "10.0.0.4", "alice", PASSWORD).await);
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    assert_invalid(log_in(&server, "10.0.0.5", "alice", "guess").await);
+    assert_blocked(log_in(&server, "10.0.0.6", "alice", PASSWORD).await);
+
+    tokio::time::sleep(Duration::from_millis(3100)).await;
+    let user = log_in(&server, "10.0.0.7", "alice", PASSWORD)
+        .await
+        .unwrap();
+    assert_eq!(user["LoggedIn"]["username"], "alice");
+
+    // The login cleared the failures
+    assert_invalid(log_in(&server, "10.0.0.8", "alice", "guess").await);
+    assert!(log_in(&server, "10.0.0.9", "alice", PASSWORD).await.is_ok());
+}
+
+#[tokio::test]
+async fn failures_across_usernames_lock_out_an_address() {
+    let server = start_server(&[
+        ("LOGIN_IP_BACKOFF_AFTER", "10"),
+        ("LOGIN_IP_LOCKOUT_AFTER", "3"),
+    ])
+    .await;
+
+    for username in ["bob", "carol", "dave"] {
+        assert_invalid(log_in(&server, "10.0.0.1", username, "guess").await);
+    }
+    assert_blocked(log_in(&server, "10.0.0.1", "alice", PASSWORD).await);
+
+    assert!(log_in(&server, "10.0.0.2", "alice", PASSWORD).await.is_ok());
+}
+
+#[tokio::test]
+async fn parallel_guesses_are_throttled_too() {
+    let server =
+        Arc::new(start_server(&[("LOGIN_BACKOFF_AFTER", "3"), ("LOGIN_LOCKOUT_AFTER", "3")]).await);
+
+    let guesses: Vec<_> = (1..=10)
+        .map(|i| {
+            let server = server.clone();
+            tokio::spawn(
+                async move { log_in(&server, &format!("10.0.0.{i}"), "alice", "guess").await },
+            )
+        })
+        .collect();
+    let mut results = Vec::new();
+    for guess in guesses {
+        results.push(guess.await.unwrap());
+    }
+    let checked = results
+        .iter()
+        .filter(|result| matches!(result, Err(e) if e.contains("Invalid username or password")))
+        .count();
+    assert_eq!(checked, 3, "{results:?}");
+    assert_blocked(log_in(&server, "10.0.0.11", "alice", PASSWORD).await);
+}
+
+#[tokio::test]
+async fn unknown_usernames_take_as_long_as_wrong_passwords() {
+    let server = start_server(&[
+        ("LOGIN_BACKOFF_AFTER", "100"),
+        ("LOGIN_LOCKOUT_AFTER", "100"),
+    ])
+    .await;
+
+    async fn time(server: &Server, username: &str) -> Duration {
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert_invalid(log_in(server, "10.0.0.1", username, "guess").await);
+        }
+        start.elapsed()
+    }
+
+    let wrong_password = time(&server, "alice").await;
+    let unknown_username = time(&server, "nobody").await;
+    assert!(
+        unknown_username * 2 > wrong_password,
+        "{unknown_username:?} for unknown usernames, {wrong_password:?} for wrong passwords"
+    );
+}
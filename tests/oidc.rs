@@ -0,0 +1,392 @@
+//! Logins with an OpenID Connect provider, against a mock issuer and the server binary
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::extract::{Form, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use openidconnect::core::{
+    CoreIdToken, CoreIdTokenClaims, CoreJsonWebKeySet, CoreJwsSigningAlgorithm,
+    CoreRsaPrivateSigningKey,
+};
+use openidconnect::{
+    Audience, EmptyAdditionalClaims, EndUserEmail, EndUserUsername, IssuerUrl, JsonWebKeyId, Nonce,
+    PkceCodeChallenge, PkceCodeVerifier, PrivateSigningKey, StandardClaims, SubjectIdentifier,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use common::Server;
+
+const CLIENT_ID: &str = "blog";
+const CLIENT_SECRET: &str = "secret";
+
+/// An account at the mock issuer
+#[derive(Clone)]
+struct Account {
+    subject: &'static str,
+    username: &'static str,
+    email: &'static str,
+    email_verified: bool,
+}
+
+/// A code the mock issuer handed out, with what it was asked for
+struct Grant {
+    account: Account,
+    nonce: String,
+    code_challenge: String,
+    redirect_uri: String,
+}
+
+struct Issuer {
+    url: String,
+    key: CoreRsaPrivateSigningKey,
+    /// The account that logs in at the next authorization
+    account: Mutex<Account>,
+    grants: Mutex<HashMap<String, Grant>>,
+    /// Sign ID tokens with a nonce the server didn't ask for
+    wrong_nonce: Mutex<bool>,
+}
+
+impl Issuer {
+    async fn start() -> Arc<Self> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let pem = openssl::rsa::Rsa::generate(2048)
+            .unwrap()
+            .private_key_to_pem()
+            .unwrap();
+        let key = CoreRsaPrivateSigningKey::from_pem(
+            std::str::from_utf8(&pem).unwrap(),
+            Some(JsonWebKeyId::new("key".to_string())),
+        )
+        .unwrap();
+
+        let issuer = Arc::new(Self {
+            url,
+            key,
+            account: Mutex::new(ALICE),
+            grants: Mutex::default(),
+            wrong_nonce: Mutex::new(false),
+        });
+        let app = Router::new()
+            .route("/.well-known/openid-configuration", get(discovery))
+            .route("/jwks", get(jwks))
+            .route("/authorize", get(authorize))
+            .route("/token", post(token))
+            .with_state(issuer.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        issuer
+    }
+
+    fn log_in_as(&self, account: Account) {
+        *self.account.lock().unwrap() = account;
+    }
+}
+
+const ALICE: Account = Account {
+    subject: "1001",
+    username: "alice",
+    email: "alice@example.com",
+    email_verified: true,
+};
+
+async fn discovery(State(issuer): State<Arc<Issuer>>) -> Json<Value> {
+    Json(json!({
+        "issuer": issuer.url,
+        "authorization_endpoint": format!("{}/authorize", issuer.url),
+        "token_endpoint": format!("{}/token", issuer.url),
+        "jwks_uri": format!("{}/jwks", issuer.url),
+        "response_types_supported": ["code"],
+        "subject_types_supported": ["public"],
+        "id_token_signing_alg_values_supported": ["RS256"],
+    }))
+}
+
+async fn jwks(State(issuer): State<Arc<Issuer>>) -> Json<CoreJsonWebKeySet> {
+    Json(CoreJsonWebKeySet::new(vec![issuer
+        .key
+        .as_verification_key()]))
+}
+
+/// Approves every request right away
+async fn authorize(
+    State(issuer): State<Arc<Issuer>>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    assert_eq!(query["client_id"], CLIENT_ID);
+    assert_eq!(query["response_type"], "code");
+    assert_eq!(query["code_challenge_method"], "S256");
+
+    let code = uuid::Uuid::new_v4().to_string();
+    issuer.grants.lock().unwrap().insert(
+        code.clone(),
+        Grant {
+            account: issuer.account.lock().unwrap().clone(),
+            nonce: query["nonce"].clone(),
+            code_challenge: query["code_challenge"].clone(),
+            redirect_uri: query["redirect_uri"].clone(),
+        },
+    );
+
+    Redirect::to(&format!(
+        "{}?code={code}&state={}",
+        query["redirect_uri"], query["state"]
+    ))
+    .into_response()
+}
+
+#[derive(Deserialize)]
+struct TokenRequest {
+    code: String,
+    redirect_uri: String,
+    code_verifier: String,
+}
+
+async fn token(
+    State(issuer): State<Arc<Issuer>>,
+    headers: HeaderMap,
+    Form(request): Form<TokenRequest>,
+) -> Response {
+    use base64::Engine;
+
+    let credentials =
+        base64::engine::general_purpose::STANDARD.encode(format!("{CLIENT_ID}:{CLIENT_SECRET}"));
+    if headers
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        != Some(&format!("Basic {credentials}"))
+    {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({"error": "invalid_client"})),
+        )
+            .into_response();
+    }
+
+    let Some(grant) = issuer.grants.lock().unwrap().remove(&request.code) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "invalid_grant"})),
+        )
+            .into_response();
+    };
+    let challenge =
+        PkceCodeChallenge::from_code_verifier_sha256(&PkceCodeVerifier::new(request.code_verifier));
+    if challenge.as_str() != grant.code_challenge || request.redirect_uri != grant.redirect_uri {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "invalid_grant"})),
+        )
+            .into_response();
+    }
+
+    let nonce = if *issuer.wrong_nonce.lock().unwrap() {
+        "another nonce".to_string()
+    } else {
+        grant.nonce
+    };
+    let now = chrono::Utc::now();
+    let claims = CoreIdTokenClaims::new(
+        IssuerUrl::new(issuer.url.clone()).unwrap(),
+        vec![Audience::new(CLIENT_ID.to_string())],
+        now + chrono::Duration::minutes(5),
+        now,
+        StandardClaims::new(SubjectIdentifier::new(grant.account.subject.to_string()))
+            .set_preferred_username(Some(EndUserUsername::new(
+                grant.account.username.to_string(),
+            )))
+            .set_email(Some(EndUserEmail::new(grant.account.email.to_string())))
+            .set_email_verified(Some(grant.account.email_verified)),
+        EmptyAdditionalClaims {},
+    )
+    .set_nonce(Some(Nonce::new(nonce)));
+    let id_token = CoreIdToken::new(
+        claims,
+        &issuer.key,
+        CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        None,
+        None,
+    )
+    .unwrap();
+
+    Json(json!({
+        "access_token": "access-token",
+        "token_type": "Bearer",
+        "expires_in": 300,
+        "id_token": id_token.to_string(),
+    }))
+    .into_response()
+}
+
+/// The server, configured with the mock issuer as provider "mock"
+async fn start_server(issuer: &Issuer) -> Server {
+    Server::start(&[
+        ("OIDC_PROVIDERS", "mock"),
+        ("OIDC_MOCK_ISSUER", &issuer.url),
+        ("OIDC_MOCK_CLIENT_ID", CLIENT_ID),
+        ("OIDC_MOCK_CLIENT_SECRET", CLIENT_SECRET),
+    ])
+    .await
+}
+
+/// Start a login and go through the provider, returning the `state` and `code` it sends the
+/// browser back with
+async fn go_to_provider(server: &Server, browser: &reqwest::Client) -> (String, String) {
+    let authorize_url = server
+        .call(browser, "/api/oidc/start", &[("provider", "mock")])
+        .await
+        .unwrap();
+
+    let response = browser
+        .get(authorize_url.as_str().unwrap())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    let callback =
+        reqwest::Url::parse(response.headers()[header::LOCATION].to_str().unwrap()).unwrap();
+    assert_eq!(callback.path(), "/oidc/callback");
+    assert!(callback.as_str().starts_with(&server.url));
+
+    let query: HashMap<_, _> = callback.query_pairs().into_owned().collect();
+    (query["state"].clone(), query["code"].clone())
+}
+
+/// Finish a login as the callback page does
+async fn finish(
+    server: &Server,
+    browser: &reqwest::Client,
+    state: &str,
+    code: &str,
+) -> Result<Value, String> {
+    server
+        .call(
+            browser,
+            "/api/oidc/finish",
+            &[("state", state), ("code", code)],
+        )
+        .await
+}
+
+/// Log in with the provider in a new browser, returning the logged in user
+async fn log_in(server: &Server) -> Value {
+    let browser = server.browser();
+    let (state, code) = go_to_provider(server, &browser).await;
+    finish(server, &browser, &state, &code).await.unwrap()["LoggedIn"].clone()
+}
+
+#[tokio::test]
+async fn first_login_creates_a_user_and_later_logins_reuse_it() {
+    let issuer = Issuer::start().await;
+    let server = start_server(&issuer).await;
+
+    let user = log_in(&server).await;
+    assert_eq!(user["username"], "alice");
+    assert_eq!(user["email"], "alice@example.com");
+    assert_eq!(user["roles"], json!(["reader"]));
+
+    let again = log_in(&server).await;
+    assert_eq!(again["id"], user["id"]);
+}
+
+#[tokio::test]
+async fn taken_usernames_get_a_number_and_unverified_emails_are_dropped() {
+    let issuer = Issuer::start().await;
+    let server = start_server(&issuer).await;
+
+    let alice = log_in(&server).await;
+    issuer.log_in_as(Account {
+        subject: "1002",
+        email: "someone@example.com",
+        email_verified: false,
+        ..ALICE
+    });
+    let other = log_in(&server).await;
+
+    assert_ne!(other["id"], alice["id"]);
+    assert_eq!(other["username"], "alice-2");
+    assert_eq!(other["email"], Value::Null);
+}
+
+#[tokio::test]
+async fn login_started_in_another_browser_is_rejected() {
+    let issuer = Issuer::start().await;
+    let server = start_server(&issuer).await;
+
+    let (state, code) = go_to_provider(&server, &server.browser()).await;
+    let error = finish(&server, &server.browser(), &state, &code)
+        .await
+        .unwrap_err();
+    assert!(error.contains("started in another browser"), "{error}");
+}
+
+#[tokio::test]
+async fn login_can_only_be_finished_once() {
+    let issuer = Issuer::start().await;
+    let server = start_server(&issuer).await;
+
+    let browser = server.browser();
+    let (state, code) = go_to_provider(&server, &browser).await;
+    finish(&server, &browser, &state, &code).await.unwrap();
+    let error = finish(&server, &browser, &state, &code).await.unwrap_err();
+    assert!(error.contains("This login has expired"), "{error}");
+}
+
+#[tokio::test]
+async fn id_token_with_another_nonce_is_rejected() {
+    let issuer = Issuer::start().await;
+    let server = start_server(&issuer).await;
+
+    *issuer.wrong_nonce.lock().unwrap() = true;
+    let browser = server.browser();
+    let (state, code) = go_to_provider(&server, &browser).await;
+    let error = finish(&server, &browser, &state, &code).await.unwrap_err();
+    assert!(error.contains("The login with Mock failed"), "{error}");
+}
+
+#[tokio::test]
+async fn logged_in_users_link_accounts() {
+    let issuer = Issuer::start().await;
+    let server = start_server(&issuer).await;
+
+    let browser = server.browser();
+    let (state, code) = go_to_provider(&server, &browser).await;
+    let alice = finish(&server, &browser, &state, &code).await.unwrap()["LoggedIn"].clone();
+
+    let bob = Account {
+        subject: "1002",
+        username: "bob",
+        ..ALICE
+    };
+    issuer.log_in_as(bob.clone());
+    let (state, code) = go_to_provider(&server, &browser).await;
+    assert_eq!(
+        finish(&server, &browser, &state, &code).await.unwrap(),
+        json!("Linked")
+    );
+
+    // The linked account logs in as the user who linked it
+    let user = log_in(&server).await;
+    assert_eq!(user["id"], alice["id"]);
+
+    // It can't be linked to someone else
+    issuer.log_in_as(Account {
+        subject: "1003",
+        username: "carol",
+        ..ALICE
+    });
+    let carol = server.browser();
+    let (state, code) = go_to_provider(&server, &carol).await;
+    finish(&server, &carol, &state, &code).await.unwrap();
+    issuer.log_in_as(bob);
+    let (state, code) = go_to_provider(&server, &carol).await;
+    let error = finish(&server, &carol, &state, &code).await.unwrap_err();
+    assert!(error.contains("already linked to another user"), "{error}");
+}
@@ -0,0 +1,68 @@
+//! Readers page through the blog index both ways
+
+mod common;
+
+use serde_json::Value;
+
+use common::Server;
+
+/// The page of one post starting after `before`, as a reader sees it
+async fn page(server: &Server, before: Option<&Value>) -> Value {
+    let created_at;
+    let id;
+    let mut args = vec![("only_published", "true"), ("page_size", "1")];
+    if let Some(before) = before {
+        created_at = before["created_at"].as_str().unwrap().to_string();
+        id = before["id"].to_string();
+        args.push(("before[created_at]", &created_at));
+        args.push(("before[id]", &id));
+    }
+    server
+        .call(&server.browser(), "/api/blog/get_posts", &args)
+        .await
+        .unwrap()
+}
+
+fn title(page: &Value) -> &str {
+    page["posts"][0]["title"].as_str().unwrap()
+}
+
+#[tokio::test]
+async fn readers_page_forward_and_back() {
+    let server = Server::with_admin(&[]).await;
+    let browser = server.logged_in("alice").await;
+    for title in ["First", "Second", "Third"] {
+        server
+            .call(
+                &browser,
+                "/api/blog/create_post",
+                &[
+                    ("new_post[title]", title),
+                    ("new_post[slug]", ""),
+                    ("new_post[content]", "Some content"),
+                    ("new_post[published]", "true"),
+                ],
+            )
+            .await
+            .unwrap();
+    }
+
+    let first = page(&server, None).await;
+    assert_eq!(title(&first), "Third");
+    assert_eq!(first["has_previous"], false);
+
+    let second = page(&server, Some(&first["next_cursor"])).await;
+    assert_eq!(title(&second), "Second");
+    assert_eq!(second["has_previous"], true);
+    assert_eq!(second["previous_cursor"], Value::Null);
+
+    let third = page(&server, Some(&second["next_cursor"])).await;
+    assert_eq!(title(&third), "First");
+    assert_eq!(third["has_previous"], true);
+    assert_eq!(third["next_cursor"], Value::Null);
+
+    // Back to the second page
+    let back = page(&server, Some(&third["previous_cursor"])).await;
+    assert_eq!(title(&back), "Second");
+    assert_eq!(back["has_previous"], true);
+}
@@ -0,0 +1,86 @@
+//! Posts show up to readers once their publication time has come
+
+mod common;
+
+use std::time::Duration;
+
+use chrono::{SecondsFormat, Utc};
+
+use common::Server;
+
+async fn start_server() -> (Server, reqwest::Client) {
+    let server = Server::with_admin(&[]).await;
+    let browser = server.logged_in("alice").await;
+    (server, browser)
+}
+
+/// The JSON feed, which lists the posts readers can see
+async fn feed(server: &Server) -> String {
+    server
+        .browser()
+        .get(format!("{}/feed.json", server.url))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn scheduled_posts_go_live_at_their_time() {
+    let (server, browser) = start_server().await;
+    let publish_at =
+        (Utc::now() + chrono::Duration::seconds(2)).to_rfc3339_opts(SecondsFormat::Millis, true);
+    server
+        .call(
+            &browser,
+            "/api/blog/create_post",
+            &[
+                ("new_post[title]", "Scheduled"),
+                ("new_post[slug]", ""),
+                ("new_post[content]", "Some content"),
+                ("new_post[published]", "false"),
+                ("new_post[publish_at]", &publish_at),
+            ],
+        )
+        .await
+        .unwrap();
+    assert!(!feed(&server).await.contains("Scheduled"));
+
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    assert!(feed(&server).await.contains("Scheduled"));
+}
+
+#[tokio::test]
+async fn bulk_actions_hide_and_show_posts() {
+    let (server, browser) = start_server().await;
+    let post = server
+        .call(
+            &browser,
+            "/api/blog/create_post",
+            &[
+                ("new_post[title]", "Hello"),
+                ("new_post[slug]", ""),
+                ("new_post[content]", "Some content"),
+                ("new_post[published]", "true"),
+            ],
+        )
+        .await
+        .unwrap();
+    let id = post["id"].to_string();
+    assert!(feed(&server).await.contains("Hello"));
+
+    for (action, visible) in [("Unpublish", false), ("Publish", true)] {
+        let changed = server
+            .call(
+                &browser,
+                "/api/admin/bulk_update_posts",
+                &[("ids[0]", id.as_str()), ("action", action)],
+            )
+            .await
+            .unwrap();
+        assert_eq!(changed, 1, "{action}");
+        assert_eq!(feed(&server).await.contains("Hello"), visible, "{action}");
+    }
+}
@@ -0,0 +1,283 @@
+//! Session ids, expiry, cookie attributes, the devices users are logged in on, the purge of
+//! expired sessions and the theme of visitors who aren't logged in
+
+mod common;
+
+use std::time::Duration;
+
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::header;
+use serde_json::Value;
+
+use common::{Server, PASSWORD};
+
+async fn start_server(env: &[(&str, &str)]) -> Server {
+    let server = Server::with_admin(env).await;
+    server.create_admin("bob");
+    server
+}
+
+async fn login_response(
+    server: &Server,
+    browser: &reqwest::Client,
+    username: &str,
+    remember: bool,
+) -> reqwest::Response {
+    let response = server
+        .post(browser, "/api/auth/login")
+        .await
+        .form(&[
+            ("credentials[username]", username),
+            ("credentials[password]", PASSWORD),
+            ("credentials[remember]", &remember.to_string()),
+        ])
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    response
+}
+
+/// Username of the user logged in with `browser`
+async fn current_user(server: &Server, browser: &reqwest::Client) -> Option<String> {
+    match server
+        .call(browser, "/api/auth/current_user", &[])
+        .await
+        .unwrap()
+    {
+        Value::Null => None,
+        user => Some(user["username"].as_str().unwrap().to_string()),
+    }
+}
+
+fn get_cookie(server: &Server, jar: &Jar, name: &str) -> Option<String> {
+    let cookies = jar.cookies(&server.url.parse().unwrap())?;
+    cookies
+        .to_str()
+        .unwrap()
+        .split("; ")
+        .find_map(|cookie| cookie.strip_prefix(&format!("{name}=")))
+        .map(str::to_string)
+}
+
+fn session_cookie(server: &Server, jar: &Jar) -> Option<String> {
+    get_cookie(server, jar, "session")
+}
+
+async fn theme(server: &Server, browser: &reqwest::Client) -> Value {
+    server
+        .call(browser, "/api/session/theme", &[])
+        .await
+        .unwrap()["theme_preference"]
+        .clone()
+}
+
+/// A new browser holding the session cookie `id`
+fn browser_with_session(server: &Server, id: &str) -> reqwest::Client {
+    let (browser, jar) = server.browser_with_cookies();
+    jar.add_cookie_str(&format!("session={id}"), &server.url.parse().unwrap());
+    browser
+}
+
+#[tokio::test]
+async fn logging_in_replaces_the_session() {
+    let server = start_server(&[]).await;
+    let (browser, jar) = server.browser_with_cookies();
+
+    // An id planted by someone else isn't kept
+    jar.add_cookie_str("session=planted", &server.url.parse().unwrap());
+    server.log_in(&browser, "alice").await;
+    let alice_session = session_cookie(&server, &jar).unwrap();
+    assert_ne!(alice_session, "planted");
+    assert_eq!(
+        current_user(&server, &browser_with_session(&server, "planted")).await,
+        None
+    );
+
+    server.log_in(&browser, "bob").await;
+    let bob_session = session_cookie(&server, &jar).unwrap();
+    assert_ne!(bob_session, alice_session);
+    assert_eq!(
+        current_user(&server, &browser).await.as_deref(),
+        Some("bob")
+    );
+    assert_eq!(
+        current_user(&server, &browser_with_session(&server, &alice_session)).await,
+        None
+    );
+}
+
+#[tokio::test]
+async fn sessions_expire_when_idle_and_after_their_lifetime() {
+    let server = start_server(&[
+        ("SESSION_IDLE_SECS", "2"),
+        ("SESSION_REMEMBER_SECS", "3"),
+        ("SESSION_MAX_SECS", "5"),
+    ])
+    .await;
+
+    // Requests keep the session alive past the idle timeout, up to its lifetime
+    let browser = server.browser();
+    server.log_in(&browser, "alice").await;
+    for _ in 0..4 {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(
+            current_user(&server, &browser).await.as_deref(),
+            Some("alice")
+        );
+    }
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(current_user(&server, &browser).await, None);
+
+    let browser = server.browser();
+    server.log_in(&browser, "alice").await;
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    assert_eq!(current_user(&server, &browser).await, None);
+}
+
+#[tokio::test]
+async fn cookie_attributes_are_configurable() {
+    let server = start_server(&[
+        ("SESSION_COOKIE_SECURE", "true"),
+        ("SESSION_COOKIE_HOST_PREFIX", "true"),
+    ])
+    .await;
+
+    let response = login_response(&server, &server.browser(), "alice", false).await;
+    let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+    assert!(cookie.starts_with("__Host-session="), "{cookie}");
+    for attribute in ["Path=/", "HttpOnly", "SameSite=Strict", "Secure"] {
+        assert!(cookie.split("; ").any(|a| a == attribute), "{cookie}");
+    }
+    assert!(!cookie.contains("Domain="), "{cookie}");
+}
+
+#[tokio::test]
+async fn remembered_sessions_last_longer_and_outlive_the_browser() {
+    let server = start_server(&[("SESSION_IDLE_SECS", "1"), ("SESSION_REMEMBER_SECS", "10")]).await;
+
+    let browser = server.browser();
+    let response = login_response(&server, &browser, "alice", false).await;
+    let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+    assert!(!cookie.contains("Max-Age"), "{cookie}");
+
+    let remembered = server.browser();
+    let response = login_response(&server, &remembered, "alice", true).await;
+    let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+    assert!(cookie.contains("Max-Age="), "{cookie}");
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(current_user(&server, &browser).await, None);
+    assert_eq!(
+        current_user(&server, &remembered).await.as_deref(),
+        Some("alice")
+    );
+}
+
+#[tokio::test]
+async fn users_list_and_sign_out_their_devices() {
+    let server = start_server(&[("TRUST_PROXY", "true")]).await;
+    let laptop = server.browser_from("10.0.0.1");
+    let phone = server.browser_from("10.0.0.2");
+    login_response(&server, &laptop, "alice", true).await;
+    server.log_in(&phone, "alice").await;
+
+    let devices = server
+        .call(&laptop, "/api/session/devices", &[])
+        .await
+        .unwrap();
+    let devices = devices.as_array().unwrap();
+    assert_eq!(devices.len(), 2);
+    let laptop_session = devices.iter().find(|d| d["current"] == true).unwrap();
+    assert_eq!(laptop_session["ip"], "10.0.0.1");
+    assert_eq!(laptop_session["remembered"], true);
+    let phone_session = devices.iter().find(|d| d["current"] == false).unwrap();
+    assert_eq!(phone_session["ip"], "10.0.0.2");
+    let phone_id = phone_session["id"].as_str().unwrap();
+
+    // Sessions of others can't be signed out
+    let bob = server.browser();
+    server.log_in(&bob, "bob").await;
+    let error = server
+        .call(&bob, "/api/session/sign_out", &[("id", phone_id)])
+        .await
+        .unwrap_err();
+    assert!(error.contains("Not found"), "{error}");
+
+    server
+        .call(&laptop, "/api/session/sign_out", &[("id", phone_id)])
+        .await
+        .unwrap();
+    assert_eq!(current_user(&server, &phone).await, None);
+    assert_eq!(
+        current_user(&server, &laptop).await.as_deref(),
+        Some("alice")
+    );
+
+    let tablet = server.browser();
+    server.log_in(&tablet, "alice").await;
+    server
+        .call(&tablet, "/api/session/sign_out_everywhere", &[])
+        .await
+        .unwrap();
+    assert_eq!(current_user(&server, &tablet).await, None);
+    assert_eq!(current_user(&server, &laptop).await, None);
+    assert_eq!(current_user(&server, &bob).await.as_deref(), Some("bob"));
+}
+
+#[tokio::test]
+async fn expired_sessions_are_purged_in_the_background() {
+    let server = start_server(&[
+        ("SESSION_IDLE_SECS", "1"),
+        ("SESSION_REMEMBER_SECS", "1"),
+        ("SESSION_GC_INTERVAL_SECS", "1"),
+        ("SESSION_GC_BATCH_SIZE", "2"),
+    ])
+    .await;
+
+    for _ in 0..5 {
+        server.log_in(&server.browser(), "alice").await;
+    }
+    tokio::time::sleep(Duration::from_secs(3)).await;
+
+    let output = server.run(&["purge-sessions"], "");
+    assert_eq!(output.trim(), "Deleted 0 sessions");
+}
+
+#[tokio::test]
+async fn visitors_keep_their_theme_in_a_cookie_until_they_log_in() {
+    let server = start_server(&[]).await;
+    let (browser, jar) = server.browser_with_cookies();
+
+    assert_eq!(theme(&server, &browser).await, "system");
+    let response = server
+        .post(&browser, "/api/session/set_theme")
+        .await
+        .form(&[("theme_preference", "dark")])
+        .send()
+        .await
+        .unwrap();
+    let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+    // Sent with pages opened from other sites, so that they have the theme too
+    assert!(cookie.starts_with("theme="), "{cookie}");
+    assert!(cookie.contains("SameSite=Lax"), "{cookie}");
+    assert_eq!(theme(&server, &browser).await, "dark");
+    assert_eq!(session_cookie(&server, &jar), None);
+
+    // Signed, so that it can't be set to anything else
+    let (forged, forged_jar) = server.browser_with_cookies();
+    forged_jar.add_cookie_str("theme=light.00", &server.url.parse().unwrap());
+    assert_eq!(theme(&server, &forged).await, "system");
+
+    // Visitors don't cost a session
+    let output = server.run(&["purge-sessions", "--all"], "");
+    assert_eq!(output.trim(), "Deleted 0 sessions");
+
+    // The theme becomes the one of the user
+    server.log_in(&browser, "alice").await;
+    assert_eq!(get_cookie(&server, &jar, "theme"), None);
+    assert_eq!(theme(&server, &browser).await, "dark");
+    let browser = server.browser();
+    server.log_in(&browser, "alice").await;
+    assert_eq!(theme(&server, &browser).await, "dark");
+}
@@ -0,0 +1,151 @@
+//! Codes of two-factor authentication can't be guessed, neither to log in nor from a session
+
+mod common;
+
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use common::{Server, PASSWORD};
+
+const KEY: (&str, &str) = (
+    "SECRET_ENCRYPTION_KEY",
+    "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+);
+
+#[tokio::test]
+async fn too_many_invalid_codes_end_the_session() {
+    let server = Server::with_admin(&[]).await;
+
+    for path in [
+        "/api/two-factor/disable_two_factor",
+        "/api/two-factor/regenerate_recovery_codes",
+    ] {
+        let browser = server.logged_in("alice").await;
+        for _ in 0..4 {
+            let error = server
+                .call(&browser, path, &[("code", "000000")])
+                .await
+                .unwrap_err();
+            assert!(error.contains("Invalid code"), "{path}: {error}");
+        }
+        let error = server
+            .call(&browser, path, &[("code", "000000")])
+            .await
+            .unwrap_err();
+        assert!(error.contains("Too many invalid codes"), "{path}: {error}");
+
+        let user = server
+            .call(&browser, "/api/auth/current_user", &[])
+            .await
+            .unwrap();
+        assert_eq!(user, Value::Null, "{path}");
+    }
+}
+
+#[tokio::test]
+async fn the_issuer_defaults_to_the_site_host() {
+    let server = Server::with_admin(&[KEY]).await;
+    let browser = server.logged_in("alice").await;
+    let enrollment = server
+        .call(&browser, "/api/two-factor/begin_totp_enrollment", &[])
+        .await
+        .unwrap();
+    let uri = enrollment["uri"].as_str().unwrap();
+    assert!(uri.contains("issuer=127.0.0.1"), "{uri}");
+
+    let server = Server::with_admin(&[KEY, ("TOTP_ISSUER", "My blog")]).await;
+    let browser = server.logged_in("alice").await;
+    let enrollment = server
+        .call(&browser, "/api/two-factor/begin_totp_enrollment", &[])
+        .await
+        .unwrap();
+    let uri = enrollment["uri"].as_str().unwrap();
+    assert!(uri.contains("issuer=My%20blog"), "{uri}");
+}
+
+/// The server with `alice` as admin, who has two-factor authentication but no authenticator,
+/// so that every code is wrong
+async fn start_server_with_two_factor(env: &[(&str, &str)]) -> Server {
+    let server = Server::with_admin(env).await;
+    server
+        .sql("UPDATE users SET totp_enabled = TRUE WHERE username = 'alice'")
+        .await;
+    server
+}
+
+/// Enter the password of `alice`, returning the challenge to send the code with
+async fn start_login(server: &Server) -> Result<String, String> {
+    let response = server
+        .call(
+            &server.browser(),
+            "/api/auth/login",
+            &[
+                ("credentials[username]", "alice"),
+                ("credentials[password]", PASSWORD),
+            ],
+        )
+        .await?;
+    Ok(response["TwoFactorRequired"]["challenge"]
+        .as_str()
+        .unwrap()
+        .to_string())
+}
+
+async fn enter_code(server: &Server, challenge: &str) -> Result<Value, String> {
+    server
+        .call(
+            &server.browser(),
+            "/api/two-factor/verify_login_code",
+            &[("challenge", challenge), ("code", "000000")],
+        )
+        .await
+}
+
+#[tokio::test]
+async fn parallel_codes_of_a_login_are_limited() {
+    let server = Arc::new(
+        start_server_with_two_factor(&[
+            ("LOGIN_BACKOFF_AFTER", "100"),
+            ("LOGIN_LOCKOUT_AFTER", "100"),
+        ])
+        .await,
+    );
+    let challenge = start_login(&server).await.unwrap();
+
+    let guesses: Vec<_> = (0..10)
+        .map(|_| {
+            let server = server.clone();
+            let challenge = challenge.clone();
+            tokio::spawn(async move { enter_code(&server, &challenge).await })
+        })
+        .collect();
+    let mut results = Vec::new();
+    for guess in guesses {
+        results.push(guess.await.unwrap());
+    }
+    let checked = results
+        .iter()
+        .filter(|result| matches!(result, Err(e) if e.contains("Invalid code")))
+        .count();
+    assert_eq!(checked, 5, "{results:?}");
+}
+
+#[tokio::test]
+async fn wrong_codes_count_as_failed_logins() {
+    let server = start_server_with_two_factor(&[
+        ("LOGIN_BACKOFF_AFTER", "100"),
+        ("LOGIN_LOCKOUT_AFTER", "4"),
+    ])
+    .await;
+
+    // The password doesn't reset the failures of earlier codes
+    let challenge = start_login(&server).await.unwrap();
+    enter_code(&server, &challenge).await.unwrap_err();
+    let challenge = start_login(&server).await.unwrap();
+    let error = enter_code(&server, &challenge).await.unwrap_err();
+    assert!(error.contains("Invalid code"), "{error}");
+
+    let error = start_login(&server).await.unwrap_err();
+    assert!(error.contains("Too many failed logins"), "{error}");
+}